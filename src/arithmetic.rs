@@ -0,0 +1,452 @@
+use crate::{iroot, primes_in_range, Integer, SignedInteger};
+
+/// Runs the linear sieve over `[0, n]`, which reaches every composite `m = i*p` exactly once
+/// through its smallest prime factor `p`, so the table is built in `O(n)` time.
+///
+/// Value at `0` is the default, at `1` is `one`, at primes `prime(p)` and `f(i*p) = extend(f(i), p, p | i)`.
+fn linear_sieve<T, P, E>(n: u64, one: T, prime: P, extend: E) -> Vec<T>
+    where T: Copy + Default, P: Fn(u64) -> T, E: Fn(T, u64, bool) -> T
+{
+    let mut values = vec![T::default(); n as usize + 1];
+    let mut composite = vec![false; n as usize + 1];
+    let mut primes: Vec<u64> = Vec::new();
+    if n >= 1 {
+        values[1] = one;
+    }
+    for i in 2..=n {
+        if !composite[i as usize] {
+            values[i as usize] = prime(i);
+            primes.push(i);
+        }
+        for &p in &primes {
+            let m = i * p;
+            if m > n {
+                break;
+            }
+            composite[m as usize] = true;
+            let divides = i.is_multiple_of(p);
+            values[m as usize] = extend(values[i as usize], p, divides);
+            if divides {
+                break;
+            }
+        }
+    }
+    values
+}
+
+/// Computes a multiplicative function `f` for all `k` in `[0, n]` given its values `f(p, e) = f(p^e)` on prime powers,
+/// with `f(0) = 0` and `f(1) = 1`.
+///
+/// Every `k > 1` is written as `k = p^e * m` with `p` the smallest prime factor of `k` and `m` coprime with `p`,
+/// and `f(k) = f(p, e) * f(m)`. Uses the linear sieve, which reaches every `k` exactly once,
+/// so the table is built in `O(n)` time with one call of `f` per number.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // sum of squares of divisors σ_2
+/// let sigma_2 = multiplicative_sieve(10, |p, e| (0..e).fold(1, |acc, _| acc * p * p + 1));
+/// assert_eq!(sigma_2, vec![0, 1, 5, 10, 21, 26, 50, 50, 85, 91, 130]);
+/// ```
+pub fn multiplicative_sieve<T, F>(n: u64, f: F) -> Vec<T>
+    where T: Integer, F: Fn(u64, u32) -> T
+{
+    let mut values = vec![T::ZERO; n as usize + 1];
+    // smallest prime power dividing k exactly and its exponent, zero for primes not reached yet
+    let mut power = vec![0u64; n as usize + 1];
+    let mut exponent = vec![0u32; n as usize + 1];
+    let mut primes: Vec<u64> = Vec::new();
+    if n >= 1 {
+        values[1] = T::ONE;
+    }
+    for i in 2..=n {
+        if power[i as usize] == 0 {
+            values[i as usize] = f(i, 1);
+            power[i as usize] = i;
+            exponent[i as usize] = 1;
+            primes.push(i);
+        }
+        for &p in &primes {
+            let m = i * p;
+            if m > n {
+                break;
+            }
+            if i.is_multiple_of(p) {
+                let (pe, e) = (power[i as usize] * p, exponent[i as usize] + 1);
+                power[m as usize] = pe;
+                exponent[m as usize] = e;
+                values[m as usize] = f(p, e) * values[(m / pe) as usize];
+                break;
+            }
+            power[m as usize] = p;
+            exponent[m as usize] = 1;
+            values[m as usize] = f(p, 1) * values[i as usize];
+        }
+    }
+    values
+}
+
+/// Computes Euler's totient `φ(k)` for all `k` in `[0, n]`, with `φ(0) = 0`.
+///
+/// Uses the linear sieve in `O(n)` time. Go to [crate::totient] for further information.
+pub fn totient_sieve(n: u64) -> Vec<u64>
+{
+    multiplicative_sieve(n, |p, e| (p - 1) * p.pow(e - 1))
+}
+
+/// Computes the Möbius function `μ(k)` for all `k` in `[0, n]`, with `μ(0) = 0`.
+///
+/// Uses the linear sieve in `O(n)` time. Go to [crate::mobius] for further information.
+pub fn mobius_sieve(n: u64) -> Vec<i8>
+{
+    multiplicative_sieve(n, |_, e| if e == 1 { -1 } else { 0 })
+}
+
+/// Computes the number of prime factors with multiplicity `Ω(k)` for all `k` in `[0, n]`, with `Ω(0) = 0`.
+///
+/// Uses the linear sieve in `O(n)` time. Go to [crate::big_omega] for further information.
+pub fn big_omega_sieve(n: u64) -> Vec<u32>
+{
+    linear_sieve(n, 0, |_| 1, |omega, _, _| omega + 1)
+}
+
+/// Computes the number of distinct prime factors `ω(k)` for all `k` in `[0, n]`, with `ω(0) = 0`.
+///
+/// Uses the linear sieve in `O(n)` time. Go to [crate::little_omega] for further information.
+pub fn little_omega_sieve(n: u64) -> Vec<u32>
+{
+    linear_sieve(n, 0, |_| 1, |omega, _, divides| if divides { omega } else { omega + 1 })
+}
+
+/// Computes the Liouville function `λ(k)` for all `k` in `[0, n]`, with `λ(0) = 0`.
+///
+/// Uses the linear sieve in `O(n)` time. Go to [crate::liouville] for further information.
+pub fn liouville_sieve(n: u64) -> Vec<i8>
+{
+    linear_sieve(n, 1, |_| -1, |lambda, _, _| -lambda)
+}
+
+/// Computes the Dirichlet convolution `h(k) = Σ f(d) g(k/d)` over the divisors `d` of `k` for all `k` in `[1, n]`,
+/// where `f` and `g` are tables indexed by `k` as returned by the sieves of this module.
+///
+/// Index `0` of the inputs is ignored and set to zero in the result, `n` is the smaller of the two largest indices.
+/// Every pair `(d, k/d)` is visited once, so the convolution takes `O(n log n)` time.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // φ * 1 = id
+/// let ones = vec![1; 11];
+/// let id: Vec<u64> = (0..=10).collect();
+/// assert_eq!(dirichlet_convolution(&totient_sieve(10), &ones), id);
+/// ```
+pub fn dirichlet_convolution<T: Integer>(f: &[T], g: &[T]) -> Vec<T>
+{
+    let len = f.len().min(g.len());
+    let mut h = vec![T::ZERO; len];
+    for (d, &fd) in f.iter().enumerate().take(len).skip(1) {
+        for (e, m) in (d..len).step_by(d).enumerate() {
+            h[m] = h[m] + fd * g[e + 1];
+        }
+    }
+    h
+}
+
+/// Computes the divisor sum `g(k) = Σ f(d)` over the divisors `d` of `k` for all `k` in `[1, n]`,
+/// the Dirichlet convolution of `f` with the constant function `1`. Index `0` is set to zero.
+///
+/// Inverse to [mobius_inversion].
+pub fn divisor_sum_transform<T: Integer>(f: &[T]) -> Vec<T>
+{
+    dirichlet_convolution(f, &vec![T::ONE; f.len()])
+}
+
+/// Recovers `f` from its divisor sums `g(k) = Σ f(d)` over the divisors `d` of `k` using the Möbius inversion formula
+/// `f(k) = Σ μ(d) g(k/d)` for all `k` in `[1, n]`. Index `0` is set to zero.
+///
+/// Inverse to [divisor_sum_transform].
+pub fn mobius_inversion<T: SignedInteger>(g: &[T]) -> Vec<T>
+{
+    let n = g.len().saturating_sub(1) as u64;
+    let mu: Vec<T> = mobius_sieve(n).into_iter().map(|m| match m {
+        1 => T::ONE,
+        -1 => T::ZERO - T::ONE,
+        _ => T::ZERO
+    }).collect();
+    dirichlet_convolution(&mu, g)
+}
+
+/// Computes `f(k)` of a multiplicative function for all `k` in `[lo, hi)`, where `lo >= 1`, given the primes up to `sqrt(hi)`.
+///
+/// Every prime `p <= sqrt(hi)` multiplies the values of its multiples by `prime(p)` and of multiples of `p^e`
+/// for `e >= 2` by `extend(p)`, while recording the product of the found prime powers. What remains is 1
+/// or a single prime `q > sqrt(hi)`, which contributes `prime(q)`.
+fn multiplicative_segment<P, E>(lo: u64, hi: u64, primes: &[u64], prime: P, extend: E) -> Vec<i64>
+    where P: Fn(u64) -> i64, E: Fn(u64) -> i64
+{
+    let mut values = vec![1; (hi - lo) as usize];
+    let mut found = vec![1u64; (hi - lo) as usize];
+    for &p in primes.iter().take_while(|&&p| p * p < hi) {
+        let mut power = p;
+        let mut factor = prime(p);
+        while power < hi {
+            let mut j = (lo.div_ceil(power) * power - lo) as usize;
+            while j < values.len() {
+                values[j] *= factor;
+                found[j] *= p;
+                j += power as usize;
+            }
+            factor = extend(p);
+            power = match power.checked_mul(p) {
+                Some(power) => power,
+                None => break
+            };
+        }
+    }
+    for (k, (value, &found)) in (lo..hi).zip(values.iter_mut().zip(&found)) {
+        if found != k {
+            *value *= prime(k / found);
+        }
+    }
+    values
+}
+
+/// Computes `F(n)` for a summatory function `F(x) = Σ f(k)` over `k` in `[1, x]` satisfying `F(x) = total(x) - Σ F(x/d)`
+/// over `d` in `[2, x]`, given `segment(lo, hi, primes)` computing `f(k)` for `k` in `[lo, hi)` from the primes up to `sqrt(hi)`.
+///
+/// The values `f(k)` for `k <= L`, with `L` about `n^(2/3)`, are generated in segments of length about `n^(1/3)`
+/// and their prefix sums `F(q)` are added to the values `F(n/i)` above `L` as they pass, grouping the terms with equal `x/d`.
+/// Then the values `F(n/i)` are completed for decreasing `i` from the larger ones.
+/// This takes `O(n^(2/3))` time and `O(n^(1/3))` memory for a segment, the primes up to `sqrt(L)` and the `n/L` values `F(n/i)`.
+fn summatory<T, S>(n: u64, total: T, segment: S) -> i128
+    where T: Fn(u64) -> i128, S: Fn(u64, u64, &[u64]) -> Vec<i64>
+{
+    let cube_root = iroot(n, 3);
+    let limit = (cube_root * cube_root).max(n.isqrt()).min(n);
+    let primes = primes_in_range(2, limit.isqrt());
+    // large[i] = F(n/i) for n/i > L, x[i] = n/i and root[i] = sqrt(n/i)
+    let count = (n / (limit + 1)) as usize;
+    let x: Vec<u64> = (0..=count as u64).map(|i| n.checked_div(i).unwrap_or(0)).collect();
+    let root: Vec<u64> = x.iter().map(|x| x.isqrt()).collect();
+    let mut large = vec![0; count + 1];
+    let length = cube_root.max(1 << 16);
+    // prefix[q - lo] = F(q) for q in the current segment [lo, hi) and sum = F(hi - 1)
+    let mut prefix = Vec::new();
+    let mut sum = 0;
+    let mut lo = 1;
+    while lo <= limit {
+        let hi = limit.min(lo + length - 1) + 1;
+        prefix.clear();
+        for f in segment(lo, hi, &primes) {
+            sum += f as i128;
+            prefix.push(sum);
+        }
+        for i in 1..=count {
+            let (x, root) = (x[i], root[i]);
+            // the terms with d > sqrt(x) have q = x/d <= x/(sqrt(x) + 1) and x/q - x/(q + 1) of them share q
+            for q in lo..hi.min(x / (root + 1) + 1) {
+                large[i] += (x / q - x / (q + 1)) as i128 * prefix[(q - lo) as usize];
+            }
+            for d in (x / hi + 1).max(2)..=root.min(x / lo) {
+                large[i] += prefix[(x / d - lo) as usize];
+            }
+        }
+        lo = hi;
+    }
+    // the remaining terms x/d > L are F(n/(i*d)) with i*d <= count
+    for i in (1..=count).rev() {
+        large[i] = total(x[i]) - large[i] - (2..=count / i).map(|d| large[i * d]).sum::<i128>();
+    }
+    if count == 0 { sum } else { large[1] }
+}
+
+/// Computes the Mertens function `M(n)`, the sum of the Möbius function `μ(k)` for `k` in `[1, n]`.
+///
+/// Uses the identity `Σ M(n/d) = 1` over `d` in `[1, n]` with memoization of the values `M(n/i)`
+/// and a segmented sieve of `μ` up to about `n^(2/3)`, so it runs in `O(n^(2/3))` time and `O(n^(1/3))` memory
+/// instead of sieving up to `n`.
+pub fn mertens(n: u64) -> i64
+{
+    let segment = |lo, hi, primes: &[u64]| multiplicative_segment(lo, hi, primes, |_| -1, |_| 0);
+    summatory(n, |_| 1, segment) as i64
+}
+
+/// Computes the totient summatory function `Φ(n)`, the sum of Euler's totient `φ(k)` for `k` in `[1, n]`.
+///
+/// `2Φ(n) - 1` is the number of pairs `(a, b)` in `[1, n]^2` with `gcd(a, b) = 1` and `Φ(n) + 1` is the length
+/// of the Farey sequence of order `n`. Uses the identity `Σ Φ(n/d) = n(n + 1)/2` over `d` in `[1, n]`
+/// with memoization and a segmented sieve of `φ` up to about `n^(2/3)`, so it runs in `O(n^(2/3))` time
+/// and `O(n^(1/3))` memory.
+pub fn totient_sum(n: u64) -> u128
+{
+    let segment = |lo, hi, primes: &[u64]| multiplicative_segment(lo, hi, primes, |p| p as i64 - 1, |p| p as i64);
+    // x(x + 1)/2 with the even factor halved first, so that it fits in i128 for every x
+    let triangle = |x: u64| if x.is_multiple_of(2) { (x / 2) as i128 * (x as i128 + 1) } else { x as i128 * (x / 2 + 1) as i128 };
+    summatory(n, triangle, segment) as u128
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn totient_sieve_matches_totient() {
+        let phi = totient_sieve(20_000);
+        assert_eq!(phi.len(), 20_001);
+        assert_eq!(phi[0], 0);
+        for n in 1..=20_000 {
+            assert_eq!(phi[n as usize], crate::totient(n), "{}", n);
+        }
+    }
+    #[test]
+    fn totient_sieve_small() {
+        assert_eq!(totient_sieve(0), vec![0]);
+        assert_eq!(totient_sieve(1), vec![0, 1]);
+        assert_eq!(totient_sieve(12), vec![0, 1, 1, 2, 2, 4, 2, 6, 4, 6, 4, 10, 4]);
+        assert_eq!(totient_sieve(1_000_000).iter().sum::<u64>(), 303_963_552_392);
+    }
+
+    #[test]
+    fn mobius_sieve_matches_mobius() {
+        let mu = mobius_sieve(20_000);
+        assert_eq!(mu[0], 0);
+        for n in 1..=20_000 {
+            assert_eq!(mu[n as usize], crate::mobius(n), "{}", n);
+        }
+        assert_eq!(mobius_sieve(0), vec![0]);
+        assert_eq!(mobius_sieve(1), vec![0, 1]);
+    }
+    #[test]
+    fn mobius_sieve_inclusion_exclusion() {
+        // squarefree numbers up to n are counted by the sum of μ(d) * floor(n / d^2)
+        let n = 1_000_000u64;
+        let mu = mobius_sieve(n.isqrt());
+        let count: i64 = (1..=n.isqrt()).map(|d| mu[d as usize] as i64 * (n / (d * d)) as i64).sum();
+        assert_eq!(count, 607_926);
+    }
+
+    #[test]
+    fn omega_sieves_match_factorization() {
+        let big = big_omega_sieve(10_000);
+        let little = little_omega_sieve(10_000);
+        let lambda = liouville_sieve(10_000);
+        assert_eq!((big[0], little[0], lambda[0]), (0, 0, 0));
+        for n in 1..=10_000 {
+            assert_eq!(big[n as usize], crate::big_omega(n), "{}", n);
+            assert_eq!(little[n as usize], crate::little_omega(n), "{}", n);
+            assert_eq!(lambda[n as usize], crate::liouville(n), "{}", n);
+        }
+    }
+    #[test]
+    fn liouville_sieve_summatory() {
+        // the summatory Liouville function L(x) is not positive for 2 <= x < 906_150_257
+        let lambda = liouville_sieve(100_000);
+        let mut sum = 0i64;
+        for (x, &l) in lambda.iter().enumerate().skip(1) {
+            sum += l as i64;
+            assert!(x == 1 || sum <= 0, "{}", x);
+        }
+        assert_eq!(big_omega_sieve(12), vec![0, 0, 1, 1, 2, 1, 2, 1, 3, 2, 2, 1, 3]);
+        assert_eq!(little_omega_sieve(12), vec![0, 0, 1, 1, 1, 1, 2, 1, 1, 1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn multiplicative_sieve_sigma() {
+        let sigma_0 = multiplicative_sieve(5000, |_, e| e as u64 + 1);
+        let sigma_1 = multiplicative_sieve(5000, |p, e| (0..e).fold(1, |acc, _| acc * p + 1));
+        let sigma_3 = multiplicative_sieve(5000, |p, e| (0..e).fold(1u128, |acc, _| acc * (p as u128).pow(3) + 1));
+        for n in 1..=5000 {
+            assert_eq!(sigma_0[n as usize], crate::divisor_count(n), "{}", n);
+            assert_eq!(sigma_1[n as usize] as u128, crate::divisor_sum(n), "{}", n);
+            assert_eq!(Some(sigma_3[n as usize]), crate::sigma(3, n), "{}", n);
+        }
+    }
+    #[test]
+    fn multiplicative_sieve_custom() {
+        // number of solutions of x^2 ≡ 1 (mod k) is multiplicative
+        let roots = multiplicative_sieve(2000, |p, e| match (p, e) {
+            (2, 1) => 1,
+            (2, 2) => 2,
+            (2, _) => 4,
+            _ => 2
+        });
+        for k in 1..=2000u64 {
+            assert_eq!(roots[k as usize], (0..k).filter(|&x| x * x % k == 1 % k).count() as u32, "{}", k);
+        }
+        assert_eq!(multiplicative_sieve(0, |p, _| p), vec![0]);
+        assert_eq!(multiplicative_sieve(3, |p, _| p as i32), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn dirichlet_convolution_brute_force() {
+        let f: Vec<i64> = (0..300).map(|k| (k * k % 17) - 8).collect();
+        let g: Vec<i64> = (0..250).map(|k| (k * 7 % 13) - 6).collect();
+        let h = dirichlet_convolution(&f, &g);
+        assert_eq!(h.len(), 250);
+        assert_eq!(h[0], 0);
+        for k in 1..250 {
+            let expected: i64 = (1..=k).filter(|d| k % d == 0).map(|d| f[d] * g[k / d]).sum();
+            assert_eq!(h[k], expected, "{}", k);
+        }
+        assert!(dirichlet_convolution::<i32>(&[], &[1, 2]).is_empty());
+    }
+    #[test]
+    fn dirichlet_identities() {
+        let n = 3000;
+        let id: Vec<i64> = (0..=n as i64).collect();
+        let phi: Vec<i64> = totient_sieve(n).into_iter().map(|v| v as i64).collect();
+        let mu: Vec<i64> = mobius_sieve(n).into_iter().map(|v| v as i64).collect();
+        let ones = vec![1i64; n as usize + 1];
+        // φ * 1 = id, μ * 1 = ε, μ * id = φ
+        assert_eq!(dirichlet_convolution(&phi, &ones), id);
+        assert_eq!(divisor_sum_transform(&mu), (0..=n).map(|k| (k == 1) as i64).collect::<Vec<_>>());
+        assert_eq!(dirichlet_convolution(&mu, &id), phi);
+        assert_eq!(mobius_inversion(&id), phi);
+        let sigma: Vec<i64> = divisor_sum_transform(&id);
+        assert_eq!(sigma[12], 28);
+        assert_eq!(mobius_inversion(&sigma), id);
+        assert!(mobius_inversion::<i64>(&[]).is_empty());
+    }
+
+    #[test]
+    fn mertens_matches_sieve() {
+        let mu = mobius_sieve(20_000);
+        let mut sum = 0;
+        for n in 0..=20_000 {
+            sum += mu[n as usize] as i64;
+            assert_eq!(mertens(n), sum, "{}", n);
+        }
+    }
+    #[test]
+    fn mertens_large() {
+        assert_eq!(mertens(1_000_000), 212);
+        assert_eq!(mertens(10_000_000), 1037);
+        assert_eq!(mertens(1_000_000_000), -222);
+        // the segments keep the memory at about n^(1/3)
+        assert_eq!(mertens(10_000_000_000_000), 599_582);
+    }
+
+    #[test]
+    fn totient_sum_matches_sieve() {
+        let phi = totient_sieve(20_000);
+        let mut sum = 0;
+        for n in 0..=20_000 {
+            sum += phi[n as usize] as u128;
+            assert_eq!(totient_sum(n), sum, "{}", n);
+        }
+    }
+    #[test]
+    fn totient_sum_large() {
+        assert_eq!(totient_sum(1_000_000), 303_963_552_392);
+        assert_eq!(totient_sum(1_000_000_000), 303_963_551_173_008_414);
+        assert_eq!(totient_sum(1_000_000_000_000), 303_963_550_927_059_804_025_910);
+        // coprime pairs in [1, 1000]^2
+        let pairs = (1..=1000u64).flat_map(|a| (1..=1000).map(move |b| (a, b))).filter(|&(a, b)| crate::gcd(a, b) == 1).count();
+        assert_eq!(2 * totient_sum(1000) - 1, pairs as u128);
+    }
+}
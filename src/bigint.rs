@@ -0,0 +1,1051 @@
+//! Number theory for arbitrary precision integers.
+//!
+//! Available with the `bigint` feature. The functions mirror the primitive ones
+//! from the crate root and work with [`BigInt`], [`BigUint`] or both.
+
+use std::ops::{Rem, Mul, Div};
+
+use num_bigint::{BigInt, BigUint};
+use num_traits::{Num, One, Signed, ToPrimitive, Zero};
+
+use crate::GcdExtendedResult;
+use crate::binomial::factorial_valuation;
+use crate::counting::generalized_pentagonal_numbers;
+use crate::primality::{selfridge_parameter, SMALL_PRIMES};
+use crate::residues::jacobi_u128;
+
+/// Arbitrary precision integer type accepted by the functions of this module.
+pub trait BigInteger:
+    Clone + Ord + Num
+    + for<'a> Rem<&'a Self, Output = Self>
+    + for<'a> Mul<&'a Self, Output = Self>
+    + for<'a> Div<&'a Self, Output = Self>
+{
+    /// Absolute value of `self`. Identity for unsigned types.
+    fn abs(&self) -> Self;
+}
+
+impl BigInteger for BigInt {
+    fn abs(&self) -> Self {
+        Signed::abs(self)
+    }
+}
+
+impl BigInteger for BigUint {
+    fn abs(&self) -> Self {
+        self.clone()
+    }
+}
+
+/// Returns true if `a` divides `b`. Otherwise returns false.
+/// Go to [crate::divides] for further information.
+pub fn divides<T: BigInteger>(a: &T, b: &T) -> bool
+{
+    if !a.is_zero() { (b.clone() % a).is_zero() } else { true }
+}
+
+/// Returns true if `a` is divisible by `b`. Otherwise returns false.
+pub fn is_divisible_by<T: BigInteger>(a: &T, b: &T) -> bool
+{
+    divides(b, a)
+}
+
+/// Returns true if `d` is common divisor of `a` and `b`. Otherwise returns false.
+pub fn is_common_divisor<T: BigInteger>(d: &T, a: &T, b: &T) -> bool
+{
+    divides(d, a) && divides(d, b)
+}
+
+/// Returns true if `d` is common multiple of `a` and `b`. Otherwise returns false.
+pub fn is_common_multiple<T: BigInteger>(d: &T, a: &T, b: &T) -> bool
+{
+    divides(a, d) && divides(b, d)
+}
+
+/// Computes greatest common divisor of `a` and `b`.
+/// Go to [crate::gcd] for further information.
+pub fn gcd<T: BigInteger>(a: &T, b: &T) -> T
+{
+    gcd_euclid(a.abs(), b.abs())
+}
+
+/// Computes least common multiple of `a` and `b`.
+/// Go to [crate::lcm] for further information.
+pub fn lcm<T: BigInteger>(a: &T, b: &T) -> T
+{
+    if a.is_zero() || b.is_zero() {
+        return T::zero();
+    }
+    let (a, b) = (a.abs(), b.abs());
+    let g = gcd_euclid(a.clone(), b.clone());
+    (a / &g) * &b
+}
+
+fn gcd_euclid<T: BigInteger>(mut a: T, mut b: T) -> T
+{
+    while !b.is_zero() {
+        let r = a % &b;
+        a = b;
+        b = r;
+    }
+    a
+}
+
+/// Computes greatest common divisor of `a` and `b` together with the coefficients
+/// `x0`, `y0`, `x1`, `y1`, satisfying `gcd(a, b) = x0*a + y0*b` and `0 = x1*a + y1*b`.
+/// Go to [crate::gcd_extended] for further information.
+pub fn gcd_extended(a: &BigInt, b: &BigInt) -> GcdExtendedResult<BigInt>
+{
+    let (mut r0, mut r1) = (BigInteger::abs(a), BigInteger::abs(b));
+    let (mut a0, mut a1) = (BigInt::from(1), BigInt::from(0));
+    let (mut b0, mut b1) = (BigInt::from(0), BigInt::from(1));
+    while !r1.is_zero() {
+        let q = &r0 / &r1;
+        let r = &r0 - &q * &r1;
+        (r0, r1) = (r1, r);
+        let a2 = &a0 - &q * &a1;
+        (a0, a1) = (a1, a2);
+        let b2 = &b0 - &q * &b1;
+        (b0, b1) = (b1, b2);
+    }
+    if a.is_negative() {
+        a0 = -a0;
+        a1 = -a1;
+    }
+    if b.is_negative() {
+        b0 = -b0;
+        b1 = -b1;
+    }
+    GcdExtendedResult { gcd: r0, x0: a0, y0: b0, x1: a1, y1: b1 }
+}
+
+/// Returns true if `n` is a probable prime according to the Baillie-PSW test. Otherwise returns false.
+/// Go to [crate::is_probable_prime] for further information.
+pub fn is_probable_prime(n: &BigUint) -> bool
+{
+    if let Some(n) = n.to_u128() {
+        return crate::is_probable_prime(n);
+    }
+    if SMALL_PRIMES.iter().any(|&p| (n % p).is_zero()) {
+        return false;
+    }
+    is_strong_probable_prime_base_2(n) && is_strong_lucas_probable_prime(n)
+}
+
+fn is_strong_probable_prime_base_2(n: &BigUint) -> bool
+{
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let mut x = BigUint::from(2u32).modpow(&(&n_minus_one >> s), n);
+    if x.is_one() || x == n_minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = &x * &x % n;
+        if x == n_minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+/// Strong Lucas test with `P = 1` and `D`, `Q = (1 - D)/4` chosen by Selfridge's method.
+fn is_strong_lucas_probable_prime(n: &BigUint) -> bool
+{
+    let root = n.sqrt();
+    if &root * &root == *n {
+        return false;
+    }
+    let d = match selfridge_parameter(|d| jacobi_small(d, n)) {
+        Some(d) => d,
+        None => return false
+    };
+    let signed_mod = |x: i64| {
+        let r = n - (BigUint::from(x.unsigned_abs()) % n);
+        if x >= 0 { (n - r) % n } else { r }
+    };
+    let half = |x: BigUint| if x.bit(0) { (x + n) >> 1 } else { x >> 1 };
+    let (d_mod, q) = (signed_mod(d), signed_mod((1 - d) / 4));
+    let n_plus_one = n + 1u32;
+    let s = n_plus_one.trailing_zeros().unwrap_or(0);
+    let k = &n_plus_one >> s;
+    let (mut u, mut v, mut q_k) = (BigUint::one(), BigUint::one(), q.clone());
+    for bit in (0..k.bits() - 1).rev() {
+        u = &u * &v % n;
+        v = (&v * &v + n + n - &q_k - &q_k) % n;
+        q_k = &q_k * &q_k % n;
+        if k.bit(bit) {
+            let u_next = half((&u + &v) % n);
+            v = half((&d_mod * &u + &v) % n);
+            u = u_next;
+            q_k = &q_k * &q % n;
+        }
+    }
+    if u.is_zero() || v.is_zero() {
+        return true;
+    }
+    for _ in 1..s {
+        v = (&v * &v + n + n - &q_k - &q_k) % n;
+        q_k = &q_k * &q_k % n;
+        if v.is_zero() {
+            return true;
+        }
+    }
+    false
+}
+
+/// Computes the Jacobi symbol `(d/n)` for small odd `d` and large odd `n` using quadratic reciprocity.
+fn jacobi_small(d: i64, n: &BigUint) -> i8
+{
+    let n_mod_4 = (n % 4u32).to_u32().unwrap();
+    let mut res = if d < 0 && n_mod_4 == 3 { -1 } else { 1 };
+    let a = d.unsigned_abs();
+    if a % 4 == 3 && n_mod_4 == 3 {
+        res = -res;
+    }
+    let r = (n % a).to_u64().unwrap();
+    res * jacobi_u128(r as u128, a as u128)
+}
+
+/// Computes the divisor function `σ_k(n)`, the sum of `k`-th powers of all divisors of `n`, without overflow.
+/// Go to [crate::sigma] for further information.
+pub fn sigma(k: u32, n: u64) -> BigUint
+{
+    crate::factorize(n).factors().iter().map(|&(p, e)| {
+        let pk = BigUint::from(p).pow(k);
+        (0..e).fold(BigUint::one(), |acc, _| acc * &pk + 1u32)
+    }).product()
+}
+
+/// Computes the fundamental solution of Pell's equation `x^2 - d*y^2 = 1`, the one with the smallest positive `x` and `y`.
+///
+/// Takes the convergent before the end of the first period of [crate::sqrt_continued_fraction], which solves
+/// `x^2 - d*y^2 = (-1)^r` for the period length `r`, and squares it in `Z[√d]` if `r` is odd.
+/// Returns `None` if `d` is a perfect square, since then only the trivial solution `(1, 0)` exists.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(solve_pell(7), Some((BigUint::from(8u32), BigUint::from(3u32))));
+/// assert_eq!(solve_pell(61), Some((BigUint::from(1766319049u32), BigUint::from(226153980u32))));
+/// assert_eq!(solve_pell(9), None);
+/// ```
+pub fn solve_pell(d: u64) -> Option<(BigUint, BigUint)>
+{
+    let (x, y, negative) = pell_unit(d)?;
+    if negative {
+        // (x + y√d)^2 solves the equation with 1
+        let (x2, y2) = (&x * &x, &y * &y);
+        return Some((x2 + y2 * d, x * y * 2u32));
+    }
+    Some((x, y))
+}
+
+/// Computes the smallest positive solution of `x^2 - d*y^2 = ±1` from the first period of [crate::sqrt_continued_fraction],
+/// together with a flag telling whether the right-hand side is `-1`. Returns `None` if `d` is a perfect square.
+fn pell_unit(d: u64) -> Option<(BigUint, BigUint, bool)>
+{
+    let (a0, period) = crate::sqrt_continued_fraction(d);
+    let (&_, terms) = period.split_last()?;
+    let (mut p, mut p_prev) = (BigUint::from(a0), BigUint::one());
+    let (mut q, mut q_prev) = (BigUint::one(), BigUint::zero());
+    for &a in terms {
+        let p_next = &p * a + &p_prev;
+        let q_next = &q * a + &q_prev;
+        p_prev = std::mem::replace(&mut p, p_next);
+        q_prev = std::mem::replace(&mut q, q_next);
+    }
+    // the convergent before the end of a period of length r solves the equation with (-1)^r
+    Some((p, q, period.len() % 2 == 1))
+}
+
+/// Finds the fundamental solutions of the generalized Pell equation `x^2 - d*y^2 = n`, one for each class of solutions.
+///
+/// Two solutions belong to the same class if they differ by a factor `(t + u√d)^k` for a solution `(t, u)`
+/// of Pell's equation [solve_pell], so every solution is obtained from exactly one returned solution and one power.
+/// Uses the Lagrange-Matthews-Mollin method: for each `f` with `f^2 | n` and each square root `z` of `d` modulo `m = n/f^2`,
+/// the continued fraction of `(z + √d)/|m|` is expanded until its denominator becomes `±1`,
+/// where the convergent solves the equation with `±m`. The solutions for `-m` are turned into ones for `m`
+/// by the solution of `x^2 - d*y^2 = -1` if it exists.
+///
+/// Returns an empty vector if there are no solutions, and also if `n = 0` or `d` is a perfect square,
+/// where the equation factors and has no classes in this sense.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigInt;
+///
+/// let solutions = solve_generalized_pell(13, 27);
+/// assert_eq!(solutions.len(), 4);
+/// for (x, y) in solutions {
+///     assert_eq!(&x * &x - 13 * &y * &y, BigInt::from(27));
+/// }
+/// assert!(solve_generalized_pell(5, 2).is_empty());
+/// ```
+pub fn solve_generalized_pell(d: u64, n: i64) -> Vec<(BigInt, BigInt)>
+{
+    let unit = match pell_unit(d) {
+        Some(unit) if n != 0 => unit,
+        _ => return Vec::new()
+    };
+    let negative_unit = if unit.2 { Some((BigInt::from(unit.0), BigInt::from(unit.1))) } else { None };
+    let (d_big, sqrt_d) = (BigInt::from(d), d.isqrt() as i128);
+    let mut res = Vec::new();
+    for f in (1..=n.unsigned_abs().isqrt()).filter(|f| n.unsigned_abs().is_multiple_of(f * f)) {
+        let m = n / (f * f) as i64;
+        let modulus = m.unsigned_abs();
+        for z in crate::sqrt_mod(d % modulus, modulus) {
+            // representative of z in (-|m|/2, |m|/2]
+            let z = if z > modulus / 2 { z as i128 - modulus as i128 } else { z as i128 };
+            let (r, s, sign) = match generalized_pell_convergent(d as i128, sqrt_d, z, modulus as i128) {
+                Some(found) => found,
+                None => continue
+            };
+            let (x, y) = if sign == m.signum() {
+                (r, s)
+            } else {
+                match &negative_unit {
+                    Some((t, u)) => (&r * t + &s * u * &d_big, &r * u + &s * t),
+                    None => continue
+                }
+            };
+            res.push((x * f, y * f));
+        }
+    }
+    res
+}
+
+/// Expands `(p + √d)/q` into a continued fraction until the first `Q_i = ±1` with `i >= 1` within its period
+/// and returns the previous convergent `(G, B)` along with the sign of `G^2 - d*B^2`. Expects `q | p^2 - d`.
+fn generalized_pell_convergent(d: i128, sqrt_d: i128, mut p: i128, mut q: i128) -> Option<(BigInt, BigInt, i64)>
+{
+    let (mut g, mut g_prev) = (BigInt::from(q), BigInt::from(-p));
+    let (mut b, mut b_prev) = (BigInt::zero(), BigInt::one());
+    let mut seen = std::collections::HashSet::new();
+    for i in 0.. {
+        if i > 0 && (q == 1 || q == -1) {
+            let sign = if (&g * &g - &b * &b * d).is_positive() { 1 } else { -1 };
+            return Some((g, b, sign));
+        }
+        if !seen.insert((p, q)) {
+            return None;
+        }
+        // floor((p + √d) / q) for irrational √d
+        let a = if q > 0 { (p + sqrt_d).div_euclid(q) } else { -(p + sqrt_d).div_euclid(-q) - 1 };
+        let g_next = &g * a + &g_prev;
+        let b_next = &b * a + &b_prev;
+        g_prev = std::mem::replace(&mut g, g_next);
+        b_prev = std::mem::replace(&mut b, b_next);
+        p = a * q - p;
+        q = (d - p * p) / q;
+    }
+    unreachable!()
+}
+
+/// Creates an iterator through all positive solutions of Pell's equation `x^2 - d*y^2 = 1` in increasing order.
+///
+/// Starts with the fundamental solution `(x1, y1)` from [solve_pell] and obtains the following ones
+/// from `x_k + y_k √d = (x1 + y1 √d)^k`. Yields nothing if `d` is a perfect square.
+pub fn pell_solutions(d: u64) -> PellSolutions
+{
+    let fundamental = solve_pell(d);
+    PellSolutions { d: BigUint::from(d), current: fundamental.clone(), fundamental }
+}
+
+/// Iterator through the positive solutions of Pell's equation. Go to [pell_solutions] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PellSolutions
+{
+    d: BigUint,
+    fundamental: Option<(BigUint, BigUint)>,
+    current: Option<(BigUint, BigUint)>
+}
+
+impl Iterator for PellSolutions {
+    type Item = (BigUint, BigUint);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (x1, y1) = self.fundamental.as_ref()?;
+        let (x, y) = self.current.take()?;
+        let next_x = x1 * &x + &self.d * y1 * &y;
+        let next_y = x1 * &y + y1 * &x;
+        self.current = Some((next_x, next_y));
+        Some((x, y))
+    }
+}
+
+/// Computes `n!` exactly.
+///
+/// Factorizes `n!` by Legendre's formula, the exponent of a prime `p` being `floor(n/p) + floor(n/p^2) + ...`,
+/// and multiplies the prime powers in a balanced product tree, so the multiplications are done on numbers
+/// of similar size and values like `10000!` are computed quickly.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(factorial_exact(20), BigUint::from(2432902008176640000u64));
+/// assert_eq!(factorial_exact(0), BigUint::from(1u32));
+/// assert_eq!(factorial_exact(10000).to_string().len(), 35660);
+/// ```
+pub fn factorial_exact(n: u64) -> BigUint
+{
+    let powers: Vec<BigUint> = crate::primes_in_range(2, n).into_iter().map(|p| {
+        BigUint::from(p).pow(factorial_valuation(n, p) as u32)
+    }).collect();
+    balanced_product(&powers)
+}
+
+/// Computes the binomial coefficient `C(n, k) = n!/(k!(n-k)!)` exactly, which is `0` for `k > n`.
+///
+/// If `n` is not much larger than `min(k, n-k)`, factorizes the coefficient by Legendre's formula like [factorial_exact].
+/// Otherwise divides the balanced product of `n-k+1, ..., n` by `k!`, which avoids sieving up to `n`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(binomial_exact(10, 3), BigUint::from(120u32));
+/// assert_eq!(binomial_exact(100, 50).to_string(), "100891344545564193334812497256");
+/// assert_eq!(binomial_exact(u64::MAX, 1), BigUint::from(u64::MAX));
+/// assert_eq!(binomial_exact(3, 4), BigUint::from(0u32));
+/// ```
+pub fn binomial_exact(n: u64, k: u64) -> BigUint
+{
+    if k > n {
+        return BigUint::zero();
+    }
+    let k = k.min(n - k);
+    if n / BINOMIAL_SIEVE_RATIO <= k {
+        let powers: Vec<BigUint> = crate::primes_in_range(2, n).into_iter().filter_map(|p| {
+            let e = factorial_valuation(n, p) - factorial_valuation(k, p) - factorial_valuation(n - k, p);
+            if e > 0 { Some(BigUint::from(p).pow(e as u32)) } else { None }
+        }).collect();
+        return balanced_product(&powers);
+    }
+    let numer: Vec<BigUint> = (n - k + 1..=n).map(BigUint::from).collect();
+    balanced_product(&numer) / factorial_exact(k)
+}
+
+/// Computes the multinomial coefficient `(k_1 + ... + k_m)!/(k_1! ... k_m!)` exactly
+/// as the product of the binomial coefficients `C(k_1 + ... + k_i, k_i)`. Go to [crate::multinomial] for further information.
+///
+/// Panics if the sum of `ks` does not fit in `u64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(multinomial_exact(&[2, 3, 1]), BigUint::from(60u32));
+/// assert_eq!(multinomial_exact(&[1; 35]), factorial_exact(35));
+/// ```
+pub fn multinomial_exact(ks: &[u64]) -> BigUint
+{
+    let mut sum: u64 = 0;
+    let binomials: Vec<BigUint> = ks.iter().map(|&k| {
+        sum = sum.checked_add(k).expect("sum must fit in u64");
+        binomial_exact(sum, k)
+    }).collect();
+    balanced_product(&binomials)
+}
+
+/// Largest ratio `n / min(k, n-k)` for which [binomial_exact] sieves the primes up to `n`.
+const BINOMIAL_SIEVE_RATIO: u64 = 16;
+
+/// Multiplies the values by splitting them into halves recursively. The product of no values is `1`.
+fn balanced_product(values: &[BigUint]) -> BigUint
+{
+    match values.len() {
+        0 => BigUint::one(),
+        1 => values[0].clone(),
+        len => balanced_product(&values[..len / 2]) * balanced_product(&values[len / 2..])
+    }
+}
+
+/// Computes the central binomial coefficient `C(2n, n)` exactly.
+/// Go to [binomial_exact] for further information.
+///
+/// Panics if `2n` does not fit in `u64`.
+pub fn central_binomial_exact(n: u64) -> BigUint
+{
+    binomial_exact(n.checked_mul(2).expect("2n must fit in u64"), n)
+}
+
+/// Computes the `n`-th Catalan number `C(2n, n)/(n + 1)` exactly, the number of binary trees with `n` nodes
+/// or of balanced sequences of `n` pairs of parentheses.
+///
+/// Panics if `2n` does not fit in `u64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(catalan_exact(5), BigUint::from(42u32));
+/// assert_eq!(catalan_exact(100).to_string(), "896519947090131496687170070074100632420837521538745909320");
+/// ```
+pub fn catalan_exact(n: u64) -> BigUint
+{
+    central_binomial_exact(n) / (n + 1)
+}
+
+/// Computes the `n`-th Motzkin number exactly, the number of ways to draw non-crossing chords
+/// between `n` points on a circle, by the recurrence `(n + 2) M_n = (2n + 1) M_(n-1) + (3n - 3) M_(n-2)`.
+/// Takes `O(n)` multiplications of numbers with `O(n)` bits.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(motzkin_exact(6), BigUint::from(51u32));
+/// ```
+pub fn motzkin_exact(n: u64) -> BigUint
+{
+    motzkin_numbers().nth(n as usize).unwrap()
+}
+
+/// Creates an infinite iterator through the central binomial coefficients `C(2n, n)` starting with `n = 0`,
+/// using `C(2n + 2, n + 1) = C(2n, n) (4n + 2)/(n + 1)`.
+pub fn central_binomials() -> CentralBinomials
+{
+    CentralBinomials { n: 0, current: BigUint::one() }
+}
+
+/// Iterator through the central binomial coefficients. Go to [central_binomials] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CentralBinomials
+{
+    n: u64,
+    current: BigUint
+}
+
+impl Iterator for CentralBinomials {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = &self.current * (4 * self.n + 2) / (self.n + 1);
+        self.n += 1;
+        Some(std::mem::replace(&mut self.current, next))
+    }
+}
+
+/// Creates an infinite iterator through the Catalan numbers starting with `C_0 = 1`,
+/// using `C_(n+1) = C_n (4n + 2)/(n + 2)`. Go to [catalan_exact] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// let catalan: Vec<BigUint> = catalan_numbers().take(6).collect();
+/// assert_eq!(catalan, [1u32, 1, 2, 5, 14, 42].iter().map(|&c| BigUint::from(c)).collect::<Vec<_>>());
+/// ```
+pub fn catalan_numbers() -> CatalanNumbers
+{
+    CatalanNumbers { n: 0, current: BigUint::one() }
+}
+
+/// Iterator through the Catalan numbers. Go to [catalan_numbers] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CatalanNumbers
+{
+    n: u64,
+    current: BigUint
+}
+
+impl Iterator for CatalanNumbers {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = &self.current * (4 * self.n + 2) / (self.n + 2);
+        self.n += 1;
+        Some(std::mem::replace(&mut self.current, next))
+    }
+}
+
+/// Creates an infinite iterator through the Motzkin numbers starting with `M_0 = 1`.
+/// Go to [motzkin_exact] for further information.
+pub fn motzkin_numbers() -> MotzkinNumbers
+{
+    MotzkinNumbers { n: 0, current: BigUint::one(), next: BigUint::one() }
+}
+
+/// Iterator through the Motzkin numbers. Go to [motzkin_numbers] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct MotzkinNumbers
+{
+    n: u64,
+    current: BigUint,
+    next: BigUint
+}
+
+impl Iterator for MotzkinNumbers {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // M_(n+2) from M_(n+1) and M_n
+        let m = self.n + 2;
+        let after = (&self.next * (2 * m + 1) + &self.current * (3 * m - 3)) / (m + 2);
+        self.n += 1;
+        let next = std::mem::replace(&mut self.next, after);
+        Some(std::mem::replace(&mut self.current, next))
+    }
+}
+
+/// Computes the `n`-th Bell number exactly, the number of partitions of an `n`-element set.
+///
+/// Builds `n` rows of the Bell triangle, where each row starts with the last entry of the previous one
+/// and every other entry is the sum of its left neighbor and the entry above it, in `O(n^2)` additions.
+/// Go to [crate::bell_mod] for Bell numbers modulo a prime.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(bell(5), BigUint::from(52u32));
+/// assert_eq!(bell(30).to_string(), "846749014511809332450147");
+/// ```
+pub fn bell(n: u64) -> BigUint
+{
+    bell_numbers().nth(n as usize).unwrap()
+}
+
+/// Creates an infinite iterator through the Bell numbers starting with `B_0 = 1`.
+/// Go to [bell] for further information.
+pub fn bell_numbers() -> BellNumbers
+{
+    BellNumbers { row: vec![BigUint::one()] }
+}
+
+/// Iterator through the Bell numbers. Go to [bell_numbers] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BellNumbers
+{
+    // the current row of the Bell triangle, starting with the current Bell number
+    row: Vec<BigUint>
+}
+
+impl Iterator for BellNumbers {
+    type Item = BigUint;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut next = Vec::with_capacity(self.row.len() + 1);
+        next.push(self.row[self.row.len() - 1].clone());
+        for x in &self.row {
+            let sum = &next[next.len() - 1] + x;
+            next.push(sum);
+        }
+        let row = std::mem::replace(&mut self.row, next);
+        row.into_iter().next()
+    }
+}
+
+/// Computes the number of partitions `p(n)` of `n` into positive parts exactly.
+///
+/// Uses the recurrence from Euler's pentagonal number theorem in `O(n^1.5)` additions.
+/// Go to [crate::partition_counts_mod] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+/// use num_bigint::BigUint;
+///
+/// assert_eq!(partition_count(100), BigUint::from(190569292u32));
+/// assert_eq!(partition_count(1000).to_string(), "24061467864032622473692149727991");
+/// ```
+pub fn partition_count(n: u64) -> BigUint
+{
+    let len = n as usize + 1;
+    let mut res: Vec<BigUint> = Vec::with_capacity(len);
+    res.push(BigUint::one());
+    for i in 1..len {
+        let (mut plus, mut minus) = (BigUint::zero(), BigUint::zero());
+        for (j, pentagonal) in generalized_pentagonal_numbers().take_while(|&g| g <= i).enumerate() {
+            if j % 4 < 2 {
+                plus += &res[i - pentagonal];
+            } else {
+                minus += &res[i - pentagonal];
+            }
+        }
+        res.push(plus - minus);
+    }
+    res.pop().unwrap()
+}
+
+/// Computes the number of derangements `D_n` exactly. Go to [crate::derangements] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::bigint::*;
+///
+/// assert_eq!(derangements(40).to_string(), "300158458444475693321518926221316715906770469041");
+/// ```
+pub fn derangements(n: u64) -> BigUint
+{
+    let mut res = BigUint::one();
+    for i in 1..=n {
+        res *= i;
+        if i % 2 == 0 {
+            res += 1u32;
+        } else {
+            res -= 1u32;
+        }
+    }
+    res
+}
+
+/// Computes the ménage number `U_n` exactly. Go to [crate::menage] for further information.
+pub fn menage(n: u64) -> BigUint
+{
+    match n {
+        0 => return BigUint::one(),
+        1 | 2 => return BigUint::zero(),
+        _ => {}
+    }
+    let (mut prev, mut res) = (-BigInt::one(), BigInt::zero());
+    for i in 3..=n {
+        let sign = if i % 2 == 0 { -4 } else { 4 };
+        let next = &res * i + (&prev * i + sign) / (i - 2);
+        prev = std::mem::replace(&mut res, next);
+    }
+    res.to_biguint().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn divides_bigint() {
+        let a = big("123456789012345678901234567890");
+        assert!(divides(&a, &(&a * big("-98765432109876543210"))));
+        assert!(!divides(&a, &(&a + BigInt::from(1))));
+        assert!(divides(&BigInt::from(0), &BigInt::from(0)));
+        assert!(is_divisible_by(&(&a * &a), &a));
+    }
+
+    #[test]
+    fn divides_biguint() {
+        let a = BigUint::from(3u32).pow(100);
+        assert!(divides(&BigUint::from(3u32), &a));
+        assert!(!divides(&BigUint::from(2u32), &a));
+    }
+
+    #[test]
+    fn gcd_bigint() {
+        let p = big("170141183460469231731687303715884105727");
+        let a = &p * big("12");
+        let b = &p * big("-18");
+        assert_eq!(gcd(&a, &b), &p * big("6"));
+        assert_eq!(gcd(&b, &BigInt::from(0)), big("18") * &p);
+        assert_eq!(gcd(&BigInt::from(0), &BigInt::from(0)), BigInt::from(0));
+    }
+
+    #[test]
+    fn gcd_lcm_biguint() {
+        let a = BigUint::from(2u32).pow(200) * BigUint::from(3u32);
+        let b = BigUint::from(2u32).pow(150) * BigUint::from(5u32);
+        assert_eq!(gcd(&a, &b), BigUint::from(2u32).pow(150));
+        assert_eq!(lcm(&a, &b), BigUint::from(2u32).pow(200) * BigUint::from(15u32));
+    }
+
+    #[test]
+    fn lcm_bigint() {
+        assert_eq!(lcm(&big("-21"), &big("6")), big("42"));
+        assert_eq!(lcm(&big("0"), &big("6")), big("0"));
+        assert!(is_common_multiple(&big("42"), &big("-21"), &big("6")));
+        assert!(is_common_divisor(&big("-3"), &big("-21"), &big("6")));
+    }
+
+    fn test_gcd_extended(a: BigInt, b: BigInt) {
+        let res = gcd_extended(&a, &b);
+        assert_eq!(res.gcd, gcd(&a, &b));
+        assert_eq!(&res.x0 * &a + &res.y0 * &b, res.gcd);
+        assert_eq!(&res.x1 * &a + &res.y1 * &b, BigInt::from(0));
+    }
+
+    #[test]
+    fn gcd_extended_bigint() {
+        let a = big("340282366920938463463374607431768211455");
+        let b = big("18446744073709551617");
+        test_gcd_extended(a.clone(), b.clone());
+        test_gcd_extended(-a.clone(), b.clone());
+        test_gcd_extended(a.clone(), -b.clone());
+        test_gcd_extended(-a, -b);
+        test_gcd_extended(big("12"), big("0"));
+    }
+
+    #[test]
+    fn gcd_extended_bigint_matches_i64() {
+        for (a, b) in [(6i64, 10i64), (-9, 12), (18, -19), (0, 7)] {
+            let res = gcd_extended(&BigInt::from(a), &BigInt::from(b));
+            let expected = crate::gcd_extended(a, b);
+            assert_eq!(res.gcd, BigInt::from(expected.gcd));
+            assert_eq!(res.x0, BigInt::from(expected.x0));
+            assert_eq!(res.y0, BigInt::from(expected.y0));
+        }
+    }
+
+    fn pow2_minus_1(e: u32) -> BigUint {
+        (BigUint::one() << e) - 1u32
+    }
+
+    #[test]
+    fn is_probable_prime_mersenne() {
+        for e in 2..200u32 {
+            let expected = [2, 3, 5, 7, 13, 17, 19, 31, 61, 89, 107, 127].contains(&e);
+            assert_eq!(is_probable_prime(&pow2_minus_1(e)), expected, "{}", e);
+        }
+        assert!(is_probable_prime(&pow2_minus_1(521)));
+        assert!(is_probable_prime(&pow2_minus_1(607)));
+    }
+    #[test]
+    fn is_probable_prime_matches_u128() {
+        for n in 0..3000u32 {
+            assert_eq!(is_probable_prime(&BigUint::from(n)), crate::is_prime(n as u64));
+        }
+        assert!(is_probable_prime(&BigUint::from(u128::MAX - 158)));
+    }
+    #[test]
+    fn is_probable_prime_big_composites() {
+        assert!(!is_probable_prime(&(pow2_minus_1(127) * pow2_minus_1(89))));
+        assert!(!is_probable_prime(&(pow2_minus_1(521) * pow2_minus_1(521))));
+        assert!(!is_probable_prime(&(pow2_minus_1(127) * BigUint::from(5459u32))));
+    }
+    #[test]
+    fn jacobi_small_matches_u128() {
+        let n = 1_000_000_007u128 * 998_244_353;
+        for d in [5i64, -7, 9, -11, 13, -15, 17, 1, -1] {
+            let expected = jacobi_u128(if d < 0 { n - d.unsigned_abs() as u128 } else { d as u128 }, n);
+            assert_eq!(jacobi_small(d, &BigUint::from(n)), expected, "{}", d);
+        }
+    }
+
+    #[test]
+    fn sigma_biguint() {
+        for n in 1..200u64 {
+            for k in 0..4 {
+                assert_eq!(sigma(k, n), BigUint::from(crate::sigma(k, n).unwrap()));
+            }
+        }
+        // σ_5(2^63) = (2^320 - 1) / 31
+        assert_eq!(sigma(5, 1 << 63), (BigUint::one() << 320u32) / 31u32);
+        assert_eq!(sigma(0, u64::MAX), BigUint::from(crate::divisor_count(u64::MAX)));
+    }
+
+    fn is_pell_solution(d: u64, x: &BigUint, y: &BigUint) -> bool {
+        x * x == BigUint::from(d) * y * y + 1u32
+    }
+
+    #[test]
+    fn solve_pell_brute_force() {
+        for d in 0..200u64 {
+            let expected = (1..100_000u64).find(|&y| (d * y * y + 1).isqrt().pow(2) == d * y * y + 1)
+                .map(|y| (BigUint::from((d * y * y + 1).isqrt()), BigUint::from(y)));
+            if d.isqrt().pow(2) == d {
+                assert_eq!(solve_pell(d), None, "{}", d);
+            } else if expected.is_some() {
+                assert_eq!(solve_pell(d), expected, "{}", d);
+            } else {
+                let (x, y) = solve_pell(d).unwrap();
+                assert!(is_pell_solution(d, &x, &y) && y >= BigUint::from(100_000u32), "{}", d);
+            }
+        }
+    }
+    #[test]
+    fn solve_pell_large() {
+        // d = 991 has a fundamental solution with 30 digits
+        let (x, y) = solve_pell(991).unwrap();
+        assert_eq!(x, "379516400906811930638014896080".parse().unwrap());
+        assert_eq!(y, "12055735790331359447442538767".parse().unwrap());
+        for d in [u64::MAX, u64::MAX - 1, (1 << 62) + 1, 1_000_000_007] {
+            let (x, y) = solve_pell(d).unwrap();
+            assert!(is_pell_solution(d, &x, &y), "{}", d);
+        }
+        assert_eq!(solve_pell(u32::MAX as u64 * u32::MAX as u64), None);
+    }
+    #[test]
+    fn pell_solutions_sequence() {
+        let expected: Vec<(BigUint, BigUint)> = [(3u32, 2u32), (17, 12), (99, 70), (577, 408), (3363, 2378)].iter()
+            .map(|&(x, y)| (BigUint::from(x), BigUint::from(y))).collect();
+        assert_eq!(pell_solutions(2).take(5).collect::<Vec<_>>(), expected);
+        for d in [3u64, 13, 61, 109, 12345] {
+            let solutions: Vec<_> = pell_solutions(d).take(6).collect();
+            assert!(solutions.iter().all(|(x, y)| is_pell_solution(d, x, y)), "{}", d);
+            assert!(solutions.windows(2).all(|w| w[0] < w[1]), "{}", d);
+        }
+        assert_eq!(pell_solutions(16).next(), None);
+    }
+
+    fn pell_equivalent(d: i128, n: i128, (x, y): (i128, i128), (u, v): (i128, i128)) -> bool {
+        (x * u - d * y * v) % n == 0 && (x * v - y * u) % n == 0
+    }
+
+    #[test]
+    fn solve_generalized_pell_brute_force() {
+        for d in (2..30u64).filter(|&d| d.isqrt().pow(2) != d) {
+            for n in (-30..=30i64).filter(|&n| n != 0) {
+                let res: Vec<(i128, i128)> = solve_generalized_pell(d, n).iter()
+                    .map(|(x, y)| (x.to_i128().unwrap(), y.to_i128().unwrap())).collect();
+                let (di, ni) = (d as i128, n as i128);
+                for (i, &s) in res.iter().enumerate() {
+                    assert_eq!(s.0 * s.0 - di * s.1 * s.1, ni, "{} {} {:?}", d, n, s);
+                    assert!(res[..i].iter().all(|&t| !pell_equivalent(di, ni, s, t)), "{} {} {:?}", d, n, res);
+                }
+                for y in 0..2000i128 {
+                    let x2 = ni + di * y * y;
+                    if x2 < 0 || (x2 as u128).isqrt().pow(2) != x2 as u128 {
+                        continue;
+                    }
+                    let x = (x2 as u128).isqrt() as i128;
+                    for &s in [(x, y), (-x, y)].iter() {
+                        assert!(res.iter().any(|&t| pell_equivalent(di, ni, s, t)), "{} {} {:?} {:?}", d, n, s, res);
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn solve_generalized_pell_large() {
+        assert_eq!(solve_generalized_pell(2, 0), []);
+        assert_eq!(solve_generalized_pell(16, 9), []);
+        assert_eq!(solve_generalized_pell(1, 1), []);
+        // x^2 - 3y^2 = -1 has no solution, while 61 has an odd period
+        assert!(solve_generalized_pell(3, -1).is_empty());
+        assert_eq!(solve_generalized_pell(61, -1), [(BigInt::from(29718), BigInt::from(3805))]);
+        for (d, n) in [(991u64, 1_000_000i64), (u32::MAX as u64, -(u32::MAX as i64) + 1), (1_000_000_007, 1 << 40)] {
+            let res = solve_generalized_pell(d, n);
+            assert!(res.iter().all(|(x, y)| x * x - BigInt::from(d) * y * y == BigInt::from(n)), "{} {}", d, n);
+            assert!(!res.is_empty(), "{} {}", d, n);
+        }
+    }
+    #[test]
+    fn factorial_exact_matches_naive() {
+        let mut naive = BigUint::one();
+        for n in 0..300u64 {
+            if n > 0 {
+                naive *= n;
+            }
+            assert_eq!(factorial_exact(n), naive);
+        }
+        let f = factorial_exact(3000);
+        assert_eq!(f, (1..=3000u32).fold(BigUint::one(), |acc, i| acc * i));
+    }
+    #[test]
+    fn binomial_exact_matches_pascal() {
+        let mut row = vec![BigUint::one()];
+        for n in 0..200u64 {
+            for k in 0..=n + 1 {
+                let expected = row.get(k as usize).cloned().unwrap_or_else(BigUint::zero);
+                assert_eq!(binomial_exact(n, k), expected, "{} {}", n, k);
+            }
+            let mut next = vec![BigUint::one(); row.len() + 1];
+            for i in 1..row.len() {
+                next[i] = &row[i - 1] + &row[i];
+            }
+            row = next;
+        }
+        // both branches agree
+        for &(n, k) in [(1000u64, 62u64), (1000, 63), (1000, 70), (5000, 2500)].iter() {
+            let naive = factorial_exact(n) / (factorial_exact(k) * factorial_exact(n - k));
+            assert_eq!(binomial_exact(n, k), naive);
+        }
+        assert_eq!(binomial_exact(1 << 40, 2), BigUint::from((1u128 << 39) * ((1 << 40) - 1)));
+    }
+    #[test]
+    fn catalan_motzkin_central() {
+        let motzkin = [1u32, 1, 2, 4, 9, 21, 51, 127, 323, 835, 2188, 5798, 15511, 41835];
+        for (n, (m, c)) in motzkin_numbers().zip(catalan_numbers()).take(300).enumerate() {
+            let n = n as u64;
+            assert_eq!(c, catalan_exact(n));
+            assert_eq!(c, binomial_exact(2 * n, n) - binomial_exact(2 * n, n + 1));
+            // M_n is the sum of C(n, 2k) C_k
+            let sum = (0..=n / 2).map(|k| binomial_exact(n, 2 * k) * catalan_exact(k)).fold(BigUint::zero(), |a, b| a + b);
+            assert_eq!(m, sum);
+            if let Some(&expected) = motzkin.get(n as usize) {
+                assert_eq!(m, BigUint::from(expected));
+            }
+        }
+        for (n, c) in central_binomials().take(300).enumerate() {
+            assert_eq!(c, central_binomial_exact(n as u64));
+        }
+        assert_eq!(motzkin_exact(0), BigUint::one());
+        assert_eq!(motzkin_exact(13), BigUint::from(41835u32));
+    }
+    #[test]
+    fn bell_numbers_match_stirling() {
+        // B_n is the sum of Stirling numbers of the second kind S(n, k)
+        let mut stirling = vec![BigUint::one()];
+        for (n, b) in bell_numbers().take(150).enumerate() {
+            assert_eq!(b, stirling.iter().fold(BigUint::zero(), |acc, s| acc + s));
+            for &p in [2u64, 3, 101].iter() {
+                assert_eq!(&b % p, BigUint::from(crate::bell_mod(n as u64, p)));
+            }
+            let mut next = vec![BigUint::zero(); stirling.len() + 1];
+            for k in 1..next.len() {
+                next[k] = stirling.get(k).map_or(BigUint::zero(), |s| s * k) + &stirling[k - 1];
+            }
+            stirling = next;
+        }
+        assert_eq!(bell(0), BigUint::one());
+        assert_eq!(bell(15), BigUint::from(1382958545u64));
+    }
+    #[test]
+    fn partition_count_matches_mod() {
+        let m = 1_000_000_007u64;
+        let expected = crate::partition_counts_mod(3000, m);
+        for n in (0..3000).step_by(97).chain(2999..3000) {
+            assert_eq!(partition_count(n) % m, BigUint::from(expected[n as usize]));
+        }
+        assert_eq!(partition_count(0), BigUint::one());
+        assert_eq!(partition_count(30), BigUint::from(5604u32));
+    }
+    #[test]
+    fn derangements_and_menage_exact() {
+        for n in 0..34 {
+            assert_eq!(derangements(n), BigUint::from(crate::derangements(n).unwrap()));
+            assert_eq!(menage(n), BigUint::from(crate::menage(n).unwrap()));
+        }
+        let p = 1_000_000_007u64;
+        let f = crate::FactorialsModP::new(1000, p);
+        for n in (0..500).step_by(7) {
+            assert_eq!(derangements(n) % p, BigUint::from(crate::derangements_mod(n, p)));
+            assert_eq!(menage(n) % p, BigUint::from(f.menage(n)));
+        }
+    }
+    #[test]
+    fn multinomial_exact_matches_factorials() {
+        for ks in [vec![], vec![0], vec![5, 0, 5], vec![10, 20, 30], vec![1; 40], vec![100, 200, 300, 400]].iter() {
+            let sum: u64 = ks.iter().sum();
+            let expected = factorial_exact(sum) / ks.iter().map(|&k| factorial_exact(k)).fold(BigUint::one(), |a, b| a * b);
+            assert_eq!(multinomial_exact(ks), expected);
+            if let Some(m) = crate::multinomial(ks) {
+                assert_eq!(BigUint::from(m), expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,610 @@
+use crate::residues::inverse;
+use crate::{factorize, gcd, mod_mul, mod_pow};
+
+/// Table of factorials and inverse factorials modulo a prime `p` answering binomial coefficient
+/// and related queries in `O(1)`.
+///
+/// Factorials are computed up to a limit `n < p`, since `p` divides all larger factorials.
+/// The inverse of `n!` is computed by Fermat's little theorem and the other inverses by `1/(k-1)! = k/k!`.
+/// Primality of `p` is not checked, for composite `p` the results are meaningless.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let f = FactorialsModP::new(1000, 1_000_000_007);
+/// assert_eq!(f.binomial(10, 3), 120);
+/// assert_eq!(f.permutations(10, 3), 720);
+/// assert_eq!(f.catalan(5), 42);
+/// assert_eq!(f.multinomial(&[2, 3, 1]), 60);
+/// assert_eq!(f.binomial(1000, 500), 159835829);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct FactorialsModP
+{
+    p: u64,
+    factorials: Vec<u64>,
+    inverse_factorials: Vec<u64>
+}
+
+impl FactorialsModP {
+    /// Precomputes the factorials of `0, ..., n` modulo `p`.
+    ///
+    /// Panics if `n >= p` or `p < 2`.
+    pub fn new(n: u64, p: u64) -> Self {
+        assert!(p >= 2, "modulus must be at least 2");
+        assert!(n < p, "limit {} must be smaller than the modulus {}", n, p);
+        let len = n as usize + 1;
+        let mut factorials = Vec::with_capacity(len);
+        factorials.push(1);
+        for k in 1..=n {
+            factorials.push(mod_mul(factorials[k as usize - 1], k, p));
+        }
+        let mut inverse_factorials = vec![0; len];
+        inverse_factorials[n as usize] = mod_pow(factorials[n as usize], p - 2, p);
+        for k in (1..=n).rev() {
+            inverse_factorials[k as usize - 1] = mod_mul(inverse_factorials[k as usize], k, p);
+        }
+        FactorialsModP { p, factorials, inverse_factorials }
+    }
+
+    /// Largest number whose factorial is stored.
+    pub fn limit(&self) -> u64 {
+        self.factorials.len() as u64 - 1
+    }
+
+    /// The prime modulus.
+    pub fn modulus(&self) -> u64 {
+        self.p
+    }
+
+    /// Computes `n! mod p`.
+    ///
+    /// Panics if `n` is greater than the limit.
+    pub fn factorial(&self, n: u64) -> u64 {
+        self.factorials[self.index(n)]
+    }
+
+    /// Computes the inverse of `n!` modulo `p`.
+    ///
+    /// Panics if `n` is greater than the limit.
+    pub fn inverse_factorial(&self, n: u64) -> u64 {
+        self.inverse_factorials[self.index(n)]
+    }
+
+    /// Computes the binomial coefficient `C(n, k) = n!/(k!(n-k)!) mod p`, which is `0` for `k > n`.
+    ///
+    /// Panics if `n` is greater than the limit.
+    pub fn binomial(&self, n: u64, k: u64) -> u64 {
+        if k > n {
+            self.index(n);
+            return 0;
+        }
+        let p = self.p;
+        mod_mul(mod_mul(self.factorial(n), self.inverse_factorial(k), p), self.inverse_factorial(n - k), p)
+    }
+
+    /// Computes the number of `k`-permutations of `n` elements `n!/(n-k)! mod p`, which is `0` for `k > n`.
+    ///
+    /// Panics if `n` is greater than the limit.
+    pub fn permutations(&self, n: u64, k: u64) -> u64 {
+        if k > n {
+            self.index(n);
+            return 0;
+        }
+        mod_mul(self.factorial(n), self.inverse_factorial(n - k), self.p)
+    }
+
+    /// Computes the `n`-th Catalan number `(2n)!/(n!(n+1)!) mod p`.
+    ///
+    /// Panics if `2n` is greater than the limit.
+    pub fn catalan(&self, n: u64) -> u64 {
+        let p = self.p;
+        let double = n.checked_mul(2).expect("2n must not overflow");
+        mod_mul(mod_mul(self.factorial(double), self.inverse_factorial(n), p), self.inverse_factorial(n + 1), p)
+    }
+
+    /// Computes the central binomial coefficient `C(2n, n) mod p`.
+    ///
+    /// Panics if `2n` is greater than the limit.
+    pub fn central_binomial(&self, n: u64) -> u64 {
+        self.binomial(n.checked_mul(2).expect("2n must not overflow"), n)
+    }
+
+    /// Computes the `n`-th Motzkin number modulo `p` as the sum of `C(n, 2k) C_k` in `O(n)`.
+    ///
+    /// Panics if `n` is greater than the limit.
+    pub fn motzkin(&self, n: u64) -> u64 {
+        let p = self.p;
+        (0..=n / 2).fold(0, |res, k| {
+            // C(n, 2k) C_k = n!/((n - 2k)! k! (k + 1)!)
+            let term = mod_mul(mod_mul(self.factorial(n), self.inverse_factorial(n - 2 * k), p), self.inverse_factorial(k), p);
+            let term = mod_mul(term, self.inverse_factorial(k + 1), p);
+            add_mod(res, term, p)
+        })
+    }
+
+    /// Computes the ménage number `U_n` modulo `p` in `O(n)` by the inclusion-exclusion formula
+    /// `U_n = sum of (-1)^k (C(2n - k, k) + C(2n - k - 1, k - 1)) (n - k)!`, where the sum of binomials counts
+    /// the ways to choose `k` disjoint pairs of neighbors around a table with `2n` seats.
+    /// Go to [crate::menage] for further information.
+    ///
+    /// Panics if `2n` is greater than the limit.
+    pub fn menage(&self, n: u64) -> u64 {
+        let p = self.p;
+        let double = n.checked_mul(2).expect("2n must not overflow");
+        self.index(double);
+        if n == 1 {
+            return 0;
+        }
+        (0..=n).fold(0, |res, k| {
+            let pairs = if k == 0 { 1 % p } else { add_mod(self.binomial(double - k, k), self.binomial(double - k - 1, k - 1), p) };
+            let term = mod_mul(pairs, self.factorial(n - k), p);
+            if k % 2 == 0 { add_mod(res, term, p) } else { add_mod(res, p - term, p) }
+        })
+    }
+
+    /// Computes the multinomial coefficient `(k_1 + ... + k_m)!/(k_1! ... k_m!) mod p`,
+    /// the number of ways to split `k_1 + ... + k_m` elements into groups of sizes `k_1, ..., k_m`.
+    ///
+    /// Panics if the sum of `ks` is greater than the limit.
+    pub fn multinomial(&self, ks: &[u64]) -> u64 {
+        let sum = ks.iter().try_fold(0u64, |s, &k| s.checked_add(k)).expect("sum must not overflow");
+        ks.iter().fold(self.factorial(sum), |res, &k| mod_mul(res, self.inverse_factorial(k), self.p))
+    }
+
+    fn index(&self, n: u64) -> usize {
+        assert!(n <= self.limit(), "{} is out of the table range [0, {}]", n, self.limit());
+        n as usize
+    }
+}
+
+/// Computes the binomial coefficient `C(n, k)`, which is `0` for `k > n`.
+///
+/// Uses `C(n, i + 1) = C(n, i) (n - i)/(i + 1)` for `i < min(k, n - k)` and cancels the common factor
+/// of `C(n, i)` and `i + 1` first, so the intermediate values never exceed the result.
+/// Returns `None` if the result does not fit in `u128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(binomial(10, 3), Some(120));
+/// assert_eq!(binomial(3, 4), Some(0));
+/// assert_eq!(binomial(130, 65), Some(95067625827960698145584333020095113100));
+/// assert_eq!(binomial(132, 66), None);
+/// ```
+pub fn binomial(n: u64, k: u64) -> Option<u128>
+{
+    if k > n {
+        return Some(0);
+    }
+    let k = k.min(n - k);
+    let mut res: u128 = 1;
+    for i in 0..k as u128 {
+        // (i + 1) divides res (n - i), so (i + 1)/g divides n - i
+        let g = gcd(res, i + 1);
+        res = (res / g).checked_mul((n as u128 - i) / ((i + 1) / g))?;
+    }
+    Some(res)
+}
+
+/// Computes the multinomial coefficient `(k_1 + ... + k_m)!/(k_1! ... k_m!)`, the number of ways to split
+/// `k_1 + ... + k_m` elements into groups of sizes `k_1, ..., k_m`.
+///
+/// Multiplies the binomial coefficients `C(k_1 + ... + k_i, k_i)` with checked arithmetic, so the intermediate values
+/// never exceed the result. Returns `None` if the result or the sum of `ks` does not fit.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(multinomial(&[2, 3, 1]), Some(60));
+/// assert_eq!(multinomial(&[]), Some(1));
+/// assert_eq!(multinomial(&[1; 35]), None);
+/// ```
+pub fn multinomial(ks: &[u64]) -> Option<u128>
+{
+    let mut sum: u64 = 0;
+    let mut res: u128 = 1;
+    for &k in ks {
+        sum = sum.checked_add(k)?;
+        res = res.checked_mul(binomial(sum, k)?)?;
+    }
+    Some(res)
+}
+
+/// Computes the binomial coefficient `C(n, k) mod p` for a prime `p` using Lucas' theorem.
+///
+/// Writing `n` and `k` in base `p` with digits `n_i` and `k_i`, `C(n, k) ≡ C(n_0, k_0) C(n_1, k_1) ... (mod p)`,
+/// where `C(n_i, k_i) = 0` for `k_i > n_i`. Each digit coefficient is computed directly, so the whole computation
+/// takes `O(min(k, p) log_p n)` multiplications and works for any `n` without precomputing a table.
+/// Returns `0` for `k > n`. Primality of `p` is not checked, for composite `p` the result is meaningless.
+///
+/// Panics if `p < 2`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(binomial_mod_prime_lucas(10, 3, 7), 1);
+/// assert_eq!(binomial_mod_prime_lucas(1_000_000_000_000_000_000, 12345, 1_000_003), 920007);
+/// assert_eq!(binomial_mod_prime_lucas(u64::MAX, 1 << 32, 2), 1);
+/// ```
+pub fn binomial_mod_prime_lucas(mut n: u64, mut k: u64, p: u64) -> u64
+{
+    assert!(p >= 2, "modulus must be at least 2");
+    if k > n {
+        return 0;
+    }
+    let mut res = 1 % p;
+    while k > 0 {
+        let (ni, ki) = (n % p, k % p);
+        if ki > ni {
+            return 0;
+        }
+        res = mod_mul(res, binomial_small(ni, ki, p), p);
+        n /= p;
+        k /= p;
+    }
+    res
+}
+
+/// Computes `C(n, k) mod p` for `k <= n < p` as a product of `min(k, n - k)` fractions.
+fn binomial_small(n: u64, k: u64, p: u64) -> u64
+{
+    let k = k.min(n - k);
+    let (mut numer, mut denom) = (1 % p, 1 % p);
+    for i in 0..k {
+        numer = mod_mul(numer, n - i, p);
+        denom = mod_mul(denom, i + 1, p);
+    }
+    mod_mul(numer, mod_pow(denom, p - 2, p), p)
+}
+
+/// Computes the central binomial coefficient `C(2n, n) mod p` for a prime `p` and any `n`.
+/// Go to [binomial_mod_prime_lucas] for further information.
+///
+/// Panics if `p < 2` or `2n` does not fit in `u64`.
+pub fn central_binomial_mod_prime(n: u64, p: u64) -> u64
+{
+    binomial_mod_prime_lucas(n.checked_mul(2).expect("2n must fit in u64"), n, p)
+}
+
+/// Computes the `n`-th Catalan number modulo a prime `p` for any `n` as `C(2n, n) - C(2n, n + 1)`,
+/// which avoids dividing by `n + 1`. Go to [binomial_mod_prime_lucas] for further information.
+///
+/// Panics if `p < 2` or `2n` does not fit in `u64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(catalan_mod_prime(5, 1_000_000_007), 42);
+/// assert_eq!(catalan_mod_prime(5, 7), 0);
+/// assert_eq!(catalan_mod_prime(1 << 40, 2), 0);
+/// ```
+pub fn catalan_mod_prime(n: u64, p: u64) -> u64
+{
+    let double = n.checked_mul(2).expect("2n must fit in u64");
+    let (a, b) = (binomial_mod_prime_lucas(double, n, p), binomial_mod_prime_lucas(double, n + 1, p));
+    ((a as u128 + p as u128 - b as u128) % p as u128) as u64
+}
+
+/// Computes the binomial coefficient `C(n, k) mod p^e` for a prime `p` using Granville's generalization of Lucas' theorem.
+///
+/// Removing the factors of `p` from `n!` leaves the product of `(floor(n/p^j))!_p` over all `j`, where `m!_p` is
+/// the product of the numbers up to `m` coprime to `p`. These products are periodic modulo `p^e`, so they are looked up
+/// in a table of size `p^e`, while the power of `p` dividing `C(n, k)` is counted by Legendre's formula.
+/// For `e = 1` dispatches to [binomial_mod_prime_lucas] instead. Otherwise takes `O(p^e + log n)` time and `O(p^e)` memory.
+/// Returns `0` for `k > n`. Primality of `p` is not checked, for composite `p` the result is meaningless.
+///
+/// Panics if `p < 2`, `e` is zero or `p^e` does not fit in `u64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(binomial_mod_prime_power(10, 3, 2, 3), 0);
+/// assert_eq!(binomial_mod_prime_power(10, 4, 2, 3), 2);
+/// assert_eq!(binomial_mod_prime_power(1_000_000_000_000_000_000, 12345, 3, 10), 26973);
+/// ```
+pub fn binomial_mod_prime_power(n: u64, k: u64, p: u64, e: u32) -> u64
+{
+    assert!(p >= 2, "modulus must be at least 2");
+    assert!(e > 0, "exponent must be positive");
+    let q = p.checked_pow(e).expect("p^e must fit in u64");
+    if k > n {
+        return 0;
+    }
+    if e == 1 {
+        return binomial_mod_prime_lucas(n, k, p);
+    }
+    let v = factorial_valuation(n, p) - factorial_valuation(k, p) - factorial_valuation(n - k, p);
+    if v >= e as u64 {
+        return 0;
+    }
+    // units[m] is the product of the numbers in [1, m] coprime to p modulo q
+    let mut units = vec![1 % q; q as usize];
+    for m in 1..q {
+        let factor = if m % p == 0 { 1 } else { m };
+        units[m as usize] = mod_mul(units[m as usize - 1], factor, q);
+    }
+    let unit_factorial = |mut m: u64| {
+        let mut res = 1 % q;
+        while m > 0 {
+            res = mod_mul(res, mod_pow(units[q as usize - 1], m / q, q), q);
+            res = mod_mul(res, units[(m % q) as usize], q);
+            m /= p;
+        }
+        res
+    };
+    let denom = mod_mul(unit_factorial(k), unit_factorial(n - k), q);
+    let res = mod_mul(unit_factorial(n), inverse(denom, q), q);
+    mod_mul(res, p.pow(v as u32), q)
+}
+
+/// Computes the binomial coefficient `C(n, k) mod m` for any positive `m`.
+///
+/// Factorizes `m`, computes the coefficient modulo each prime power with [binomial_mod_prime_power]
+/// and combines the results by the Chinese remainder theorem. The running time is dominated by the largest
+/// prime power dividing `m` with exponent greater than one and by the largest prime factor otherwise.
+/// Returns `0` for `k > n`.
+///
+/// Panics if `m` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(binomial_mod(10, 3, 100), 20);
+/// assert_eq!(binomial_mod(1_000_000_000_000_000_000, 12345, 899919), 706626);
+/// ```
+pub fn binomial_mod(n: u64, k: u64, m: u64) -> u64
+{
+    assert!(m != 0, "modulus must be nonzero");
+    let (mut res, mut modulus) = (0u64, 1u64);
+    for &(p, e) in factorize(m).factors() {
+        let q = p.pow(e);
+        let r = binomial_mod_prime_power(n, k, p, e);
+        // res + modulus * t ≡ r (mod q)
+        let diff = ((r as u128 + q as u128 - (res % q) as u128) % q as u128) as u64;
+        let t = mod_mul(diff, inverse(modulus % q, q), q);
+        res += modulus * t;
+        modulus *= q;
+    }
+    res % m
+}
+
+/// Computes `(a + b) mod m` without overflow.
+pub(crate) fn add_mod(a: u64, b: u64, m: u64) -> u64
+{
+    ((a as u128 + b as u128) % m as u128) as u64
+}
+
+/// Computes the exponent of `p` in `n!` by Legendre's formula.
+pub(crate) fn factorial_valuation(mut n: u64, p: u64) -> u64
+{
+    let mut res = 0;
+    while n > 0 {
+        n /= p;
+        res += n;
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pascal(n: usize, p: u64) -> Vec<Vec<u64>> {
+        let mut rows = vec![vec![1 % p]];
+        for i in 1..=n {
+            let prev = &rows[i - 1];
+            let mut row = vec![1 % p; i + 1];
+            for j in 1..i {
+                row[j] = ((prev[j - 1] as u128 + prev[j] as u128) % p as u128) as u64;
+            }
+            rows.push(row);
+        }
+        rows
+    }
+
+    #[test]
+    fn binomial_matches_pascal() {
+        for &p in [2, 3, 5, 13, 101, 1_000_000_007, 18446744073709551557].iter() {
+            let n = 100.min(p - 1);
+            let f = FactorialsModP::new(n, p);
+            let rows = pascal(n as usize, p);
+            for i in 0..=n {
+                for j in 0..=n {
+                    let expected = if j <= i { rows[i as usize][j as usize] } else { 0 };
+                    assert_eq!(f.binomial(i, j), expected);
+                    let perm = (i - j.min(i) + 1..=i).fold(1 % p, |acc, x| mod_mul(acc, x, p));
+                    assert_eq!(f.permutations(i, j), if j <= i { perm } else { 0 });
+                }
+                assert_eq!(mod_mul(f.factorial(i), f.inverse_factorial(i), p), 1 % p);
+            }
+        }
+    }
+
+    #[test]
+    fn catalan_and_multinomial() {
+        let p = 1_000_000_007;
+        let f = FactorialsModP::new(200, p);
+        let catalan = [1, 1, 2, 5, 14, 42, 132, 429, 1430, 4862, 16796];
+        for (n, &c) in catalan.iter().enumerate() {
+            assert_eq!(f.catalan(n as u64), c);
+        }
+        assert_eq!(f.catalan(100), mod_mul(f.binomial(200, 100), mod_pow(101, p - 2, p), p));
+        assert_eq!(f.multinomial(&[]), 1);
+        assert_eq!(f.multinomial(&[0, 5, 0]), 1);
+        assert_eq!(f.multinomial(&[1, 1, 1, 1]), 24);
+        assert_eq!(f.multinomial(&[30, 40, 50]), mod_mul(f.binomial(120, 30), f.binomial(90, 40), p));
+    }
+
+    #[test]
+    fn limits() {
+        let f = FactorialsModP::new(6, 7);
+        assert_eq!(f.limit(), 6);
+        assert_eq!(f.modulus(), 7);
+        assert_eq!(f.factorial(6), 6);
+        assert_eq!(f.catalan(3), 5);
+        let f = FactorialsModP::new(0, 2);
+        assert_eq!(f.binomial(0, 0), 1);
+        assert_eq!(f.binomial(0, 1), 0);
+    }
+
+    #[test]
+    fn lucas_matches_pascal() {
+        for &p in [2, 3, 5, 7, 11].iter() {
+            let rows = pascal(150, p);
+            for n in 0..=150u64 {
+                for k in 0..=160u64 {
+                    let expected = if k <= n { rows[n as usize][k as usize] } else { 0 };
+                    assert_eq!(binomial_mod_prime_lucas(n, k, p), expected, "{} {} {}", n, k, p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lucas_large() {
+        let p = 1_000_000_007;
+        let f = FactorialsModP::new(2000, p);
+        for n in (0..2000).step_by(37) {
+            for k in 0..=n {
+                assert_eq!(binomial_mod_prime_lucas(n, k, p), f.binomial(n, k));
+            }
+        }
+        // C(2^m - 1, k) is odd for every k < 2^m
+        assert_eq!(binomial_mod_prime_lucas(u64::MAX, 12345678987654321, 2), 1);
+        // C(2n, n) is even for n > 0
+        assert_eq!(binomial_mod_prime_lucas(1 << 62, 1 << 61, 2), 0);
+        // in base p, C(p^2, p) ≡ C(0, 1) = 0 and C(p^2 + p, p) ≡ C(1, 1) = 1
+        assert_eq!(binomial_mod_prime_lucas(p * p, p, p), 0);
+        assert_eq!(binomial_mod_prime_lucas(p * p + p, p, p), 1);
+        assert_eq!(binomial_mod_prime_lucas(5, 0, 3), 1);
+        assert_eq!(binomial_mod_prime_lucas(5, 6, 3), 0);
+    }
+
+    #[test]
+    fn composite_moduli_match_pascal() {
+        for &m in [1, 2, 4, 8, 9, 12, 27, 30, 64, 100, 125, 243, 720, 1024].iter() {
+            let rows = pascal(80, m);
+            for n in 0..=80u64 {
+                for k in 0..=82u64 {
+                    let expected = if k <= n { rows[n as usize][k as usize] } else { 0 };
+                    assert_eq!(binomial_mod(n, k, m), expected, "{} {} {}", n, k, m);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn prime_power_large() {
+        // values computed with exact arithmetic
+        assert_eq!(binomial_mod_prime_power(1_000_000_000_000_000_000, 12345, 3, 10), 26973);
+        assert_eq!(binomial_mod_prime_power(1_000_000_000_000_000_000, 12345, 2, 20), 262144);
+        assert_eq!(binomial_mod_prime_power(999_999_999_999_999_999, 1000, 2, 20), 515585);
+        // C(2n, n) is divisible exactly by 2^(number of ones in n)
+        assert_eq!(binomial_mod_prime_power(1 << 62, 1 << 61, 2, 16) % 4, 2);
+        assert_eq!(binomial_mod_prime_power(1_000_000, 500_000, 2, 20), 601216);
+        assert_eq!(binomial_mod_prime_power(123456789, 54321, 7, 7), 191737);
+        assert_eq!(binomial_mod_prime_power(5, 6, 7, 3), 0);
+        assert_eq!(binomial_mod(1_000_000_000_000_000_000, 12345, 899919), 706626);
+        assert_eq!(binomial_mod(1_000_000_000_000_000_000, 12345, 141776649), 85766121);
+        assert_eq!(binomial_mod(999_999_999_999_999_999, 1000, 141776649), 6167826);
+        assert_eq!(binomial_mod(1_000_000_000_000_000_000, 12345, 1001), 0);
+        assert_eq!(binomial_mod(100, 50, 18446744073709551557), (100891344545564193334812497256u128 % 18446744073709551557) as u64);
+    }
+
+    #[test]
+    fn catalan_motzkin_mod_prime() {
+        let motzkin = [1u64, 1, 2, 4, 9, 21, 51, 127, 323, 835, 2188, 5798, 15511, 41835];
+        let catalan = [1u64, 1, 2, 5, 14, 42, 132, 429, 1430, 4862, 16796, 58786, 208012, 742900];
+        for &p in [2, 3, 5, 7, 13, 1_000_000_007].iter() {
+            let f = FactorialsModP::new(40.min(p - 1), p);
+            for n in 0..14u64 {
+                assert_eq!(catalan_mod_prime(n, p), catalan[n as usize] % p);
+                assert_eq!(central_binomial_mod_prime(n, p), (catalan[n as usize] * (n + 1)) % p);
+                if 2 * n <= f.limit() {
+                    assert_eq!(f.motzkin(n), motzkin[n as usize] % p);
+                    assert_eq!(f.central_binomial(n), (catalan[n as usize] * (n + 1)) % p);
+                }
+            }
+        }
+        // C_n is odd iff n + 1 is a power of two
+        for n in 0..1000u64 {
+            assert_eq!(catalan_mod_prime(n, 2), ((n + 1).is_power_of_two()) as u64);
+        }
+        let p = 1_000_000_007;
+        let f = FactorialsModP::new(3000, p);
+        for n in 0..1500 {
+            assert_eq!(catalan_mod_prime(n, p), f.catalan(n));
+            assert_eq!(central_binomial_mod_prime(n, p), f.central_binomial(n));
+        }
+    }
+
+    #[test]
+    fn menage_mod_prime() {
+        for &p in [2, 3, 5, 7, 13, 1_000_000_007].iter() {
+            let f = FactorialsModP::new(60.min(p - 1), p);
+            for n in 0..=f.limit() / 2 {
+                let expected = crate::menage(n).map(|u| (u % p as u128) as u64);
+                if expected.is_some() {
+                    assert_eq!(Some(f.menage(n)), expected, "{} {}", n, p);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn checked_binomial_and_multinomial() {
+        let mut row = vec![1u128];
+        for n in 0..=131u64 {
+            for k in 0..=n + 1 {
+                assert_eq!(binomial(n, k), Some(row.get(k as usize).copied().unwrap_or(0)), "{} {}", n, k);
+            }
+            let mut next = vec![1u128; row.len() + 1];
+            for i in 1..row.len() {
+                next[i] = row[i - 1].saturating_add(row[i]);
+            }
+            row = next;
+        }
+        assert!(binomial(131, 65).is_some());
+        assert_eq!(binomial(132, 65), None);
+        assert_eq!(binomial(u64::MAX, 1), Some(u64::MAX as u128));
+        assert_eq!(binomial(u64::MAX, 2), Some(u64::MAX as u128 * (u64::MAX as u128 - 1) / 2));
+        assert_eq!(binomial(u64::MAX, 3), None);
+        let f = FactorialsModP::new(100, 1_000_000_007);
+        for ks in [vec![0], vec![5, 0, 5], vec![10, 20, 30], vec![1; 20], vec![3, 4, 5, 6, 7]].iter() {
+            let expected = multinomial(ks).unwrap();
+            assert_eq!((expected % 1_000_000_007) as u64, f.multinomial(ks));
+        }
+        // 34! fits in u128, 35! does not
+        assert_eq!(multinomial(&[1; 34]), Some((1..=34u128).product()));
+        assert_eq!(multinomial(&[1; 35]), None);
+        assert_eq!(multinomial(&[u64::MAX, 1]), None);
+        assert_eq!(multinomial(&[u64::MAX, 0]), Some(1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_range() {
+        FactorialsModP::new(10, 11).binomial(11, 12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn limit_not_below_modulus() {
+        FactorialsModP::new(7, 7);
+    }
+}
@@ -0,0 +1,348 @@
+use crate::binomial;
+
+/// Rearranges the increasing indices of a combination of `k = indices.len()` elements out of `0, ..., n-1`
+/// into the lexicographically next one, without allocating.
+///
+/// Returns true if the next combination exists. Otherwise resets `indices` to the first combination `0, ..., k-1`
+/// and returns false, like `std::next_permutation` in C++.
+///
+/// Panics if `indices` is not strictly increasing or contains a value not smaller than `n`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let mut c = [0, 3, 4];
+/// assert!(next_combination(&mut c, 5));
+/// assert_eq!(c, [1, 2, 3]);
+/// let mut c = [2, 3, 4];
+/// assert!(!next_combination(&mut c, 5));
+/// assert_eq!(c, [0, 1, 2]);
+/// ```
+pub fn next_combination(indices: &mut [usize], n: usize) -> bool
+{
+    let k = indices.len();
+    assert!(indices.windows(2).all(|w| w[0] < w[1]) && indices.last().is_none_or(|&x| x < n),
+        "indices must be increasing and smaller than {}", n);
+    // the rightmost index which is not at its maximum n - k + i
+    let i = match (0..k).rev().find(|&i| indices[i] < n - k + i) {
+        Some(i) => i,
+        None => {
+            for (i, x) in indices.iter_mut().enumerate() {
+                *x = i;
+            }
+            return false;
+        }
+    };
+    indices[i] += 1;
+    for j in i + 1..k {
+        indices[j] = indices[j - 1] + 1;
+    }
+    true
+}
+
+/// Creates an iterator through all combinations of `k` elements out of `0, ..., n-1` in lexicographic order.
+/// Each combination is an increasing vector of indices. Yields nothing if `k > n` and a single empty vector if `k = 0`.
+///
+/// The indices are advanced in place by [next_combination], only the yielded vectors are allocated.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let c: Vec<Vec<usize>> = combinations(4, 2).collect();
+/// assert_eq!(c, [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]]);
+/// ```
+pub fn combinations(n: usize, k: usize) -> Combinations
+{
+    Combinations { n, indices: (0..k).collect(), done: k > n }
+}
+
+/// Iterator through the combinations of indices. Go to [combinations] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Combinations
+{
+    n: usize,
+    indices: Vec<usize>,
+    done: bool
+}
+
+impl Iterator for Combinations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.indices.clone();
+        self.done = !next_combination(&mut self.indices, self.n);
+        Some(res)
+    }
+}
+
+/// Creates an iterator through all combinations of `k` items of a slice in lexicographic order of their positions.
+/// Go to [combinations] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let c: Vec<Vec<&char>> = combinations_of(&['a', 'b', 'c'], 2).collect();
+/// assert_eq!(c, [[&'a', &'b'], [&'a', &'c'], [&'b', &'c']]);
+/// ```
+pub fn combinations_of<T>(items: &[T], k: usize) -> CombinationsOf<'_, T>
+{
+    CombinationsOf { items, inner: combinations(items.len(), k) }
+}
+
+/// Iterator through the combinations of items of a slice. Go to [combinations_of] for further information.
+#[derive(Debug, Clone)]
+pub struct CombinationsOf<'a, T>
+{
+    items: &'a [T],
+    inner: Combinations
+}
+
+impl<'a, T> Iterator for CombinationsOf<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let items = self.items;
+        self.inner.next().map(|indices| indices.into_iter().map(|i| &items[i]).collect())
+    }
+}
+
+/// Computes the position of a combination of `k = combination.len()` elements out of `0, ..., n-1`
+/// in the lexicographic order of [combinations], starting with 0.
+///
+/// Uses the combinatorial number system: the complements `n-1-c_i` of the elements `c_0 < ... < c_(k-1)` are decreasing,
+/// which makes the rank `C(n, k) - 1 - sum of C(n-1-c_i, k-i)`. Takes `O(k^2)` time.
+/// Returns `None` if `C(n, k)` does not fit in `u128`.
+///
+/// Panics if `combination` is not strictly increasing or contains a value not smaller than `n`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(rank_combination(&[0, 1, 2], 5), Some(0));
+/// assert_eq!(rank_combination(&[1, 2, 4], 5), Some(7));
+/// assert_eq!(rank_combination(&[2, 3, 4], 5), Some(9));
+/// ```
+pub fn rank_combination(combination: &[usize], n: usize) -> Option<u128>
+{
+    let k = combination.len();
+    assert!(combination.windows(2).all(|w| w[0] < w[1]) && combination.last().is_none_or(|&x| x < n),
+        "combination must be increasing and smaller than {}", n);
+    let total = binomial(n as u64, k as u64)?;
+    let colex: u128 = combination.iter().enumerate().map(|(i, &c)| binomial((n - 1 - c) as u64, (k - i) as u64).unwrap()).sum();
+    Some(total - 1 - colex)
+}
+
+/// Computes the combination of `k` elements out of `0, ..., n-1` at position `rank` in the lexicographic order
+/// of [combinations], the inverse of [rank_combination]. Unranking a uniformly random number in `[0, C(n, k))`
+/// samples a combination uniformly without enumerating them. Takes `O(nk)` time.
+///
+/// Returns `None` if `rank >= C(n, k)` or `C(n, k)` does not fit in `u128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(unrank_combination(7, 5, 3), Some(vec![1, 2, 4]));
+/// assert_eq!(unrank_combination(10, 5, 3), None);
+/// ```
+pub fn unrank_combination(rank: u128, n: usize, k: usize) -> Option<Vec<usize>>
+{
+    let total = binomial(n as u64, k as u64)?;
+    if rank >= total {
+        return None;
+    }
+    // write the colex rank as a sum of C(d_i, k - i) with decreasing d_i chosen greedily
+    let mut colex = total - 1 - rank;
+    let mut d = n;
+    let mut res = Vec::with_capacity(k);
+    for i in 0..k {
+        d -= 1;
+        loop {
+            match binomial(d as u64, (k - i) as u64) {
+                Some(c) if c <= colex => {
+                    colex -= c;
+                    break;
+                }
+                _ => d -= 1
+            }
+        }
+        res.push(n - 1 - d);
+    }
+    Some(res)
+}
+
+/// Computes the `i`-th binary reflected Gray code `i ^ (i >> 1)`. Consecutive codes differ in exactly one bit.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(gray_code(5), 0b111);
+/// assert_eq!(gray_code_rank(0b111), 5);
+/// ```
+pub fn gray_code(i: u64) -> u64
+{
+    i ^ (i >> 1)
+}
+
+/// Computes the position of a code in the binary reflected Gray code sequence, the inverse of [gray_code].
+pub fn gray_code_rank(mut code: u64) -> u64
+{
+    let mut shift = 1;
+    while shift < 64 {
+        code ^= code >> shift;
+        shift <<= 1;
+    }
+    code
+}
+
+/// Creates an iterator through all `2^n` subsets of `{0, ..., n-1}` as bitmasks in the binary reflected Gray code order,
+/// starting with the empty set. Each subset is yielded with the element added or removed in the step leading to it,
+/// which is `None` for the first one, so algorithms over all subsets can update their state incrementally.
+///
+/// The element changed in the `i`-th step is the number of trailing zeros of `i`.
+///
+/// Panics if `n > 64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let g: Vec<(u64, Option<u32>)> = gray_codes(2).collect();
+/// assert_eq!(g, [(0b00, None), (0b01, Some(0)), (0b11, Some(1)), (0b10, Some(0))]);
+/// ```
+pub fn gray_codes(n: u32) -> GrayCodes
+{
+    assert!(n <= 64, "at most 64 elements are supported, got {}", n);
+    GrayCodes { index: 0, last: if n == 64 { u64::MAX } else { (1 << n) - 1 }, done: false }
+}
+
+/// Iterator through the subsets in Gray code order. Go to [gray_codes] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct GrayCodes
+{
+    index: u64,
+    last: u64,
+    done: bool
+}
+
+impl Iterator for GrayCodes {
+    type Item = (u64, Option<u32>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let i = self.index;
+        let changed = if i == 0 { None } else { Some(i.trailing_zeros()) };
+        if i == self.last {
+            self.done = true;
+        } else {
+            self.index += 1;
+        }
+        Some((gray_code(i), changed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combinations_match_bitmasks() {
+        for n in 0..12usize {
+            for k in 0..=n + 1 {
+                // combinations as bitmasks, sorted lexicographically by their indices
+                let mut expected: Vec<Vec<usize>> = (0..1u32 << n).filter(|m| m.count_ones() as usize == k)
+                    .map(|m| (0..n).filter(|&i| m >> i & 1 == 1).collect()).collect();
+                expected.sort();
+                assert_eq!(combinations(n, k).collect::<Vec<_>>(), expected, "{} {}", n, k);
+            }
+        }
+    }
+
+    #[test]
+    fn next_combination_wraps_around() {
+        let mut c: [usize; 0] = [];
+        assert!(!next_combination(&mut c, 3));
+        let mut c = [0, 1];
+        let mut count = 1;
+        while next_combination(&mut c, 6) {
+            count += 1;
+        }
+        assert_eq!(count, 15);
+        assert_eq!(c, [0, 1]);
+        let items = [10, 20, 30, 40];
+        let c: Vec<Vec<&i32>> = combinations_of(&items, 3).collect();
+        assert_eq!(c, [[&10, &20, &30], [&10, &20, &40], [&10, &30, &40], [&20, &30, &40]]);
+        assert_eq!(combinations_of(&items, 5).count(), 0);
+    }
+
+    #[test]
+    fn gray_codes_visit_all_subsets() {
+        for n in 0..=12 {
+            let codes: Vec<(u64, Option<u32>)> = gray_codes(n).collect();
+            assert_eq!(codes.len(), 1 << n);
+            let mut seen = vec![false; 1 << n];
+            for (i, &(code, changed)) in codes.iter().enumerate() {
+                assert!(!seen[code as usize]);
+                seen[code as usize] = true;
+                assert_eq!(gray_code_rank(code), i as u64);
+                if i > 0 {
+                    assert_eq!(codes[i - 1].0 ^ code, 1 << changed.unwrap());
+                } else {
+                    assert_eq!((code, changed), (0, None));
+                }
+            }
+        }
+        for &i in [0, 1, 12345, u64::MAX, u64::MAX / 3].iter() {
+            assert_eq!(gray_code_rank(gray_code(i)), i);
+        }
+        let mut large = gray_codes(64).skip(5);
+        assert_eq!(large.next(), Some((gray_code(5), Some(0))));
+    }
+
+    #[test]
+    fn combination_ranks_match_enumeration() {
+        for n in 0..12usize {
+            for k in 0..=n {
+                for (rank, c) in combinations(n, k).enumerate() {
+                    assert_eq!(rank_combination(&c, n), Some(rank as u128));
+                    assert_eq!(unrank_combination(rank as u128, n, k), Some(c));
+                }
+                let count = binomial(n as u64, k as u64).unwrap();
+                assert_eq!(unrank_combination(count, n, k), None);
+            }
+        }
+        let n = 130;
+        let last: Vec<usize> = (65..130).collect();
+        let count = binomial(130, 65).unwrap();
+        assert_eq!(rank_combination(&last, n), Some(count - 1));
+        assert_eq!(unrank_combination(count - 1, n, 65), Some(last));
+        let c = unrank_combination(count / 3, n, 65).unwrap();
+        assert_eq!(rank_combination(&c, n), Some(count / 3));
+        assert_eq!(rank_combination(&[0], 200), Some(0));
+        assert_eq!(rank_combination(&(0..100).collect::<Vec<_>>(), 200), None);
+        assert_eq!(unrank_combination(0, 200, 100), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn next_combination_invalid() {
+        next_combination(&mut [2, 1], 3);
+    }
+}
@@ -0,0 +1,340 @@
+use std::convert::TryFrom;
+
+use crate::Rational;
+
+/// Computes the continued fraction `[a0; a1, ..., an]` of `p/q`, the quotients of the Euclidean algorithm.
+///
+/// The first term is `floor(p/q)` and may be negative, the other terms are positive.
+/// The expansion is the shorter of the two possible ones, so its last term is at least 2 unless it is the only one.
+///
+/// Panics if `q` is zero or the first term does not fit, which only happens for `i64::MIN/-1`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(continued_fraction(415, 93), vec![4, 2, 6, 7]);
+/// assert_eq!(continued_fraction(-7, 3), vec![-3, 1, 2]);
+/// assert_eq!(continued_fraction(6, 3), vec![2]);
+/// ```
+pub fn continued_fraction(p: i64, q: i64) -> Vec<i64>
+{
+    assert!(q != 0, "denominator must be nonzero");
+    let (mut p, mut q) = if q < 0 { (-(p as i128), -(q as i128)) } else { (p as i128, q as i128) };
+    let mut res = Vec::new();
+    loop {
+        let a = p.div_euclid(q);
+        res.push(i64::try_from(a).expect("continued fraction term overflow"));
+        p -= a * q;
+        if p == 0 {
+            return res;
+        }
+        std::mem::swap(&mut p, &mut q);
+    }
+}
+
+/// Computes the value of the continued fraction `[a0; a1, ..., an]` as a reduced [Rational].
+///
+/// Expects positive terms after the first one. The numerators and denominators of the convergents
+/// are computed by the usual recurrence, which keeps them coprime.
+///
+/// Panics if `terms` is empty or the value does not fit.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(from_continued_fraction(&[4, 2, 6, 7]), Rational::new(415, 93));
+/// assert_eq!(from_continued_fraction(&[-3, 1, 2]), Rational::new(-7, 3));
+/// ```
+pub fn from_continued_fraction(terms: &[i64]) -> Rational
+{
+    let (&last, rest) = terms.split_last().expect("continued fraction must have at least one term");
+    // numer/denom is the value of the tail [a_i; a_(i+1), ..., a_n]
+    let (mut numer, mut denom) = (last as i128, 1i128);
+    for &a in rest.iter().rev() {
+        let next = (a as i128).checked_mul(numer).and_then(|x| x.checked_add(denom)).expect("rational overflow");
+        denom = numer;
+        numer = next;
+    }
+    let to_i64 = |x: i128| i64::try_from(x).expect("rational overflow");
+    Rational::new(to_i64(numer), to_i64(denom))
+}
+
+/// Computes the continued fraction of `√d`, which is `[a0; a1, ..., ar, a1, ..., ar, ...]` with a periodic part
+/// of length `r` ending with `ar = 2*a0`. Returns `a0` and the period `[a1, ..., ar]`, which is empty if `d` is a perfect square.
+///
+/// The terms are `a_k = floor((a0 + m_k) / den_k)` for the complete quotients `(m_k + √d) / den_k`, which stay below `2√d`,
+/// and the period ends with the first `den_k = 1`. The convergent before the end of the period solves
+/// `x^2 - d*y^2 = (-1)^r`, which gives the solution of Pell's equation and the fundamental unit of `Q(√d)`
+/// up to the usual adjustments. The period has length `O(√d log d)`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(sqrt_continued_fraction(14), (3, vec![1, 2, 1, 6]));
+/// assert_eq!(sqrt_continued_fraction(2), (1, vec![2]));
+/// assert_eq!(sqrt_continued_fraction(16), (4, vec![]));
+/// ```
+pub fn sqrt_continued_fraction(d: u64) -> (u64, Vec<u64>)
+{
+    let a0 = d.isqrt();
+    let mut period = Vec::new();
+    if a0 * a0 == d {
+        return (a0, period);
+    }
+    let (mut m, mut den, mut a) = (0u64, 1u64, a0);
+    loop {
+        m = den * a - m;
+        den = (d - m * m) / den;
+        a = (a0 + m) / den;
+        period.push(a);
+        if den == 1 {
+            return (a0, period);
+        }
+    }
+}
+
+/// Returns an iterator through the convergents `[a0; a1, ..., ak]` of the continued fraction with the given terms.
+///
+/// The convergents `h_k/q_k` follow the recurrence `h_k = a_k h_(k-1) + h_(k-2)` and the same for `q_k`,
+/// so they are produced without reducing. The terms may come from an infinite iterator like the period
+/// of [sqrt_continued_fraction] repeated. The iterator ends with the terms or once a convergent does not fit in `i64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let (a0, period) = sqrt_continued_fraction(2);
+/// let terms = std::iter::once(a0).chain(period.into_iter().cycle()).map(|a| a as i64);
+/// let approx: Vec<String> = convergents(terms).take(5).map(|c| c.to_string()).collect();
+/// assert_eq!(approx, ["1", "3/2", "7/5", "17/12", "41/29"]);
+/// ```
+pub fn convergents<I: IntoIterator<Item = i64>>(terms: I) -> Convergents<I::IntoIter>
+{
+    Convergents { terms: terms.into_iter(), prev: (0, 1), current: (1, 0) }
+}
+
+/// Iterator through the convergents of a continued fraction. Go to [convergents] for further information.
+#[derive(Debug, Clone)]
+pub struct Convergents<I>
+{
+    terms: I,
+    prev: (i64, i64),
+    current: (i64, i64)
+}
+
+impl<I: Iterator<Item = i64>> Iterator for Convergents<I> {
+    type Item = Rational;
+
+    fn next(&mut self) -> Option<Rational> {
+        let a = self.terms.next()?;
+        let step = |x: i64, y: i64| a.checked_mul(x)?.checked_add(y);
+        let next = (step(self.current.0, self.prev.0)?, step(self.current.1, self.prev.1)?);
+        if next.1 == 0 {
+            return None;
+        }
+        self.prev = self.current;
+        self.current = next;
+        // consecutive convergents satisfy h_k q_(k-1) - h_(k-1) q_k = ±1, so they are reduced up to the sign
+        Some(Rational::new(next.0, next.1))
+    }
+}
+
+/// Finds the best rational approximation `p/q` of `x_num/x_den` with `1 <= q <= max_den`, the one with the smallest
+/// distance to `x_num/x_den`. Ties are broken by the smaller denominator and then by the smaller value.
+///
+/// Every best approximation is a convergent or a semiconvergent `(h_(k-1) + t h_k) / (q_(k-1) + t q_k)`
+/// of the continued fraction of `x_num/x_den`. The expansion stops at the first convergent whose denominator
+/// exceeds `max_den`, and the last convergent is compared with the largest semiconvergent still within the bound.
+///
+/// Panics if `x_den` is zero or `max_den` is not positive.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // π ≈ 3.14159265358979
+/// let pi = (314_159_265_358_979, 100_000_000_000_000);
+/// assert_eq!(best_rational_approx(pi.0, pi.1, 10), Rational::new(22, 7));
+/// assert_eq!(best_rational_approx(pi.0, pi.1, 200), Rational::new(355, 113));
+/// // the semiconvergent 311/99 beats the convergent 22/7
+/// assert_eq!(best_rational_approx(pi.0, pi.1, 100), Rational::new(311, 99));
+/// ```
+pub fn best_rational_approx(x_num: i64, x_den: i64, max_den: i64) -> Rational
+{
+    assert!(x_den != 0, "denominator must be nonzero");
+    assert!(max_den > 0, "maximal denominator must be positive");
+    let (mut num, mut den) = if x_den < 0 { (-(x_num as i128), -(x_den as i128)) } else { (x_num as i128, x_den as i128) };
+    let (x_num, x_den, max_den) = (num, den, max_den as i128);
+    let (mut prev, mut current) = ((0i128, 1i128), (1i128, 0i128));
+    loop {
+        let a = num.div_euclid(den);
+        if current.1 * a + prev.1 > max_den {
+            let t = (max_den - prev.1) / current.1;
+            let semi = (prev.0 + t * current.0, prev.1 + t * current.1);
+            // |x - p/q| = |x_num q - p x_den| / (x_den q), the numerators are below x_den
+            let error = |(p, q): (i128, i128)| (x_num * q - p * x_den).unsigned_abs();
+            let (semi_error, current_error) = (error(semi) * current.1 as u128, error(current) * semi.1 as u128);
+            let semi_better = semi_error < current_error || (semi_error == current_error && (semi.1, semi.0) < (current.1, current.0));
+            let best = if semi_better { semi } else { current };
+            return Rational::new(best.0 as i64, best.1 as i64);
+        }
+        let next = (current.0 * a + prev.0, current.1 * a + prev.1);
+        prev = current;
+        current = next;
+        let rem = num - a * den;
+        if rem == 0 {
+            return Rational::new(current.0 as i64, current.1 as i64);
+        }
+        num = den;
+        den = rem;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn continued_fraction_round_trip() {
+        for q in (-30..=30).filter(|&q| q != 0) {
+            for p in -100..=100 {
+                let terms = continued_fraction(p, q);
+                assert!(terms[1..].iter().all(|&a| a > 0), "{} {} {:?}", p, q, terms);
+                assert!(terms.len() == 1 || *terms.last().unwrap() >= 2, "{} {} {:?}", p, q, terms);
+                assert_eq!(from_continued_fraction(&terms), Rational::new(p, q), "{} {}", p, q);
+            }
+        }
+    }
+    #[test]
+    fn continued_fraction_large() {
+        // consecutive Fibonacci numbers give the longest expansion
+        let (f91, f92) = (4_660_046_610_375_530_309i64, 7_540_113_804_746_346_429i64);
+        let mut expected = vec![1; 90];
+        expected[89] = 2;
+        assert_eq!(continued_fraction(f92, f91), expected);
+        assert_eq!(from_continued_fraction(&expected), Rational::new(f92, f91));
+        assert_eq!(continued_fraction(i64::MIN, 1), [i64::MIN]);
+        assert_eq!(continued_fraction(i64::MIN, i64::MAX), [-2, 1, i64::MAX - 1]);
+        assert_eq!(continued_fraction(1, i64::MIN), [-1, 1, i64::MAX]);
+        assert_eq!(from_continued_fraction(&[0, i64::MAX]), Rational::new(1, i64::MAX));
+        assert_eq!(from_continued_fraction(&[5]), Rational::from(5));
+    }
+    #[test]
+    #[should_panic]
+    fn continued_fraction_overflow() {
+        continued_fraction(i64::MIN, -1);
+    }
+    #[test]
+    #[should_panic]
+    fn from_continued_fraction_overflow() {
+        from_continued_fraction(&[i64::MAX, 1, 1]);
+    }
+
+    #[test]
+    fn sqrt_continued_fraction_small() {
+        // periods of √d for d = 2, ..., 23 (OEIS A013943)
+        let lengths = [1, 2, 0, 1, 2, 4, 2, 0, 1, 2, 2, 5, 4, 2, 0, 1, 2, 6, 2, 6, 6, 4];
+        for (d, &r) in (2..).zip(lengths.iter()) {
+            let (a0, period) = sqrt_continued_fraction(d);
+            assert_eq!(period.len(), r, "{}", d);
+            assert_eq!(a0, d.isqrt());
+            if r > 0 {
+                // the period ends with 2*a0 and is symmetric apart from it
+                assert_eq!(period[r - 1], 2 * a0);
+                assert!(period[..r - 1].iter().eq(period[..r - 1].iter().rev()), "{}", d);
+            }
+        }
+        assert_eq!(sqrt_continued_fraction(0), (0, vec![]));
+        assert_eq!(sqrt_continued_fraction(1), (1, vec![]));
+        assert_eq!(sqrt_continued_fraction(94), (9, vec![1, 2, 3, 1, 1, 5, 1, 8, 1, 5, 1, 1, 3, 2, 1, 18]));
+    }
+    #[test]
+    fn sqrt_continued_fraction_convergent_solves_pell() {
+        for d in (2..500u64).filter(|&d| d.isqrt().pow(2) != d) {
+            let (a0, period) = sqrt_continued_fraction(d);
+            let mut terms = vec![a0 as i64];
+            terms.extend(period[..period.len() - 1].iter().map(|&a| a as i64));
+            let (mut p, mut p_prev, mut q, mut q_prev) = (a0 as i128, 1i128, 1i128, 0i128);
+            for &a in &terms[1..] {
+                (p, p_prev) = (a as i128 * p + p_prev, p);
+                (q, q_prev) = (a as i128 * q + q_prev, q);
+                if p > i64::MAX as i128 {
+                    break;
+                }
+            }
+            if p <= i64::MAX as i128 {
+                let expected = if period.len() % 2 == 0 { 1 } else { -1 };
+                assert_eq!(p * p - d as i128 * q * q, expected, "{}", d);
+            }
+        }
+    }
+    #[test]
+    fn sqrt_continued_fraction_large() {
+        for d in [u64::MAX, u64::MAX - 1, (1 << 62) + 1, 1_000_000_007] {
+            let (a0, period) = sqrt_continued_fraction(d);
+            assert_eq!(*period.last().unwrap(), 2 * a0);
+            assert!(period.iter().all(|&a| a <= 2 * a0));
+        }
+        // √(n^2 + 1) = [n; 2n]
+        assert_eq!(sqrt_continued_fraction((1 << 62) + 1), (1 << 31, vec![1 << 32]));
+        // √(n^2 + 2n) = [n; 1, 2n]
+        assert_eq!(sqrt_continued_fraction(u64::MAX), (u32::MAX as u64, vec![1, 2 * u32::MAX as u64]));
+    }
+
+    #[test]
+    fn convergents_match_prefixes() {
+        for (p, q) in [(415, 93), (-7, 3), (1, 1), (7_540_113_804_746_346_429, 4_660_046_610_375_530_309)] {
+            let terms = continued_fraction(p, q);
+            let expected: Vec<Rational> = (1..=terms.len()).map(|k| from_continued_fraction(&terms[..k])).collect();
+            assert_eq!(convergents(terms.iter().cloned()).collect::<Vec<_>>(), expected);
+        }
+        assert_eq!(convergents(std::iter::repeat(1)).count(), 91);
+        assert_eq!(convergents(vec![]).next(), None);
+    }
+
+    fn best_rational_approx_naive(x_num: i64, x_den: i64, max_den: i64) -> Rational {
+        // for each q the closest numerators are floor and ceil of x*q, ties go to the smaller denominator
+        let error = |p: i64, q: i64| ((x_num * q - p * x_den).abs(), q);
+        let mut best = (x_num.div_euclid(x_den), 1);
+        for q in 1..=max_den {
+            let p = (x_num * q).div_euclid(x_den);
+            for p in [p, p + 1] {
+                if error(p, q).0 * best.1 < error(best.0, best.1).0 * q {
+                    best = (p, q);
+                }
+            }
+        }
+        Rational::new(best.0, best.1)
+    }
+
+    #[test]
+    fn best_rational_approx_brute_force() {
+        for x_den in 1..40 {
+            for x_num in -60..60 {
+                for max_den in 1..45 {
+                    let expected = best_rational_approx_naive(x_num, x_den, max_den);
+                    assert_eq!(best_rational_approx(x_num, x_den, max_den), expected, "{} {} {}", x_num, x_den, max_den);
+                }
+            }
+        }
+    }
+    #[test]
+    fn best_rational_approx_large() {
+        assert_eq!(best_rational_approx(i64::MAX, i64::MAX - 1, i64::MAX), Rational::new(i64::MAX, i64::MAX - 1));
+        assert_eq!(best_rational_approx(i64::MAX, i64::MAX - 1, 1000), Rational::from(1));
+        assert_eq!(best_rational_approx(i64::MIN, i64::MAX, i64::MAX - 1), Rational::new(-i64::MAX, i64::MAX - 1));
+        assert_eq!(best_rational_approx(i64::MIN, i64::MAX, 1 << 40), Rational::from(-1));
+        assert_eq!(best_rational_approx(1, -3, 2), Rational::new(-1, 2));
+        // e ≈ 2.718281828459045 has the convergents 1264/465 and 2721/1001
+        assert_eq!(best_rational_approx(2_718_281_828_459_045, 1_000_000_000_000_000, 1000), Rational::new(1457, 536));
+    }
+}
@@ -0,0 +1,317 @@
+use crate::binomial::add_mod;
+use crate::mod_mul;
+
+/// Computes the `n`-th Bell number, the number of partitions of an `n`-element set, modulo a prime `p`.
+///
+/// The first `p` Bell numbers are computed by the Bell triangle in `O(p^2)`. Larger ones follow from
+/// Touchard's congruence `B_(n+p) ≡ B_n + B_(n+1) (mod p)`, so `B_n` is a combination of the first `p` numbers
+/// with the coefficients of `x^n mod (x^p - x - 1)`, which is computed in `O(p^2 log n)`.
+/// Primality of `p` is not checked, for composite `p` the result is meaningless.
+///
+/// Panics if `p < 2`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(bell_mod(5, 1_000_000_007), 52);
+/// assert_eq!(bell_mod(10, 7), 115975 % 7);
+/// assert_eq!(bell_mod(1_000_000_000_000_000_000, 2), 1);
+/// ```
+pub fn bell_mod(n: u64, p: u64) -> u64
+{
+    assert!(p >= 2, "modulus must be at least 2");
+    let len = p.min(n.saturating_add(1)) as usize;
+    let bell = bell_triangle_mod(len, p);
+    if n < p {
+        return bell[n as usize];
+    }
+    let coefficients = touchard_power(n, p);
+    coefficients.iter().zip(&bell).fold(0, |res, (&c, &b)| add_mod(res, mod_mul(c, b, p), p))
+}
+
+/// Computes the first `len` Bell numbers modulo `p` by the Bell triangle.
+fn bell_triangle_mod(len: usize, p: u64) -> Vec<u64>
+{
+    let mut res = Vec::with_capacity(len);
+    let mut row = vec![1 % p];
+    for _ in 0..len {
+        res.push(row[0]);
+        let mut next = Vec::with_capacity(row.len() + 1);
+        next.push(row[row.len() - 1]);
+        for &x in &row {
+            let last = next[next.len() - 1];
+            next.push(add_mod(last, x, p));
+        }
+        row = next;
+    }
+    res
+}
+
+/// Computes the coefficients of `x^n mod (x^p - x - 1)` modulo `p`.
+fn touchard_power(n: u64, p: u64) -> Vec<u64>
+{
+    let len = p as usize;
+    let multiply = |a: &[u64], b: &[u64]| {
+        let mut product = vec![0; 2 * len - 1];
+        for (i, &x) in a.iter().enumerate().filter(|&(_, &x)| x != 0) {
+            for (j, &y) in b.iter().enumerate() {
+                product[i + j] = add_mod(product[i + j], mod_mul(x, y, p), p);
+            }
+        }
+        // x^k = x^(k-p+1) + x^(k-p)
+        for k in (len..product.len()).rev() {
+            let c = std::mem::replace(&mut product[k], 0);
+            product[k - len + 1] = add_mod(product[k - len + 1], c, p);
+            product[k - len] = add_mod(product[k - len], c, p);
+        }
+        product.truncate(len);
+        product
+    };
+    let mut res = vec![0; len];
+    res[0] = 1;
+    let mut base = vec![0; len];
+    base[1] = 1;
+    let mut n = n;
+    while n > 0 {
+        if n & 1 == 1 {
+            res = multiply(&res, &base);
+        }
+        base = multiply(&base, &base);
+        n >>= 1;
+    }
+    res
+}
+
+/// Computes the number of partitions `p(n)` of `n` into positive parts modulo `m`.
+/// Go to [partition_counts_mod] for further information.
+///
+/// Panics if `m` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(partition_count_mod(5, 1_000_000_007), 7);
+/// assert_eq!(partition_count_mod(100, 1_000_000_007), 190569292);
+/// assert_eq!(partition_count_mod(10000, 1_000_000_007), 17783467);
+/// ```
+pub fn partition_count_mod(n: u64, m: u64) -> u64
+{
+    partition_counts_mod(n, m)[n as usize]
+}
+
+/// Computes the numbers of partitions `p(0), ..., p(n)` modulo `m`.
+///
+/// Uses Euler's pentagonal number theorem, which gives the recurrence
+/// `p(n) = p(n-1) + p(n-2) - p(n-5) - p(n-7) + ...` over the generalized pentagonal numbers `k(3k-1)/2`
+/// for `k = 1, -1, 2, -2, ...` with signs `+, +, -, -, ...`, so all values take `O(n^1.5)` time.
+///
+/// Panics if `m` is zero.
+pub fn partition_counts_mod(n: u64, m: u64) -> Vec<u64>
+{
+    assert!(m != 0, "modulus must be nonzero");
+    let len = n as usize + 1;
+    let mut res = vec![0; len];
+    res[0] = 1 % m;
+    for i in 1..len {
+        let mut sum = 0;
+        for (j, pentagonal) in generalized_pentagonal_numbers().take_while(|&g| g <= i).enumerate() {
+            let term = res[i - pentagonal];
+            sum = if j % 4 < 2 { add_mod(sum, term, m) } else { add_mod(sum, m - term, m) };
+        }
+        res[i] = sum;
+    }
+    res
+}
+
+/// Iterates through the generalized pentagonal numbers `k(3k-1)/2` for `k = 1, -1, 2, -2, ...`.
+pub(crate) fn generalized_pentagonal_numbers() -> impl Iterator<Item = usize>
+{
+    (1..).flat_map(|k: usize| [k * (3 * k - 1) / 2, k * (3 * k + 1) / 2])
+}
+
+/// Computes the number of derangements `D_n`, the permutations of `n` elements without a fixed point.
+///
+/// Uses the recurrence `D_n = n D_(n-1) + (-1)^n` with checked arithmetic.
+/// Returns `None` if the result does not fit in `u128`, which happens for `n > 34`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(derangements(4), Some(9));
+/// assert_eq!(derangements(0), Some(1));
+/// assert_eq!(derangements(35), None);
+/// ```
+pub fn derangements(n: u64) -> Option<u128>
+{
+    let mut res: u128 = 1;
+    for i in 1..=n {
+        res = res.checked_mul(i as u128)?;
+        res = if i % 2 == 0 { res.checked_add(1)? } else { res - 1 };
+    }
+    Some(res)
+}
+
+/// Computes the number of derangements `D_n` modulo `m`. Go to [derangements] for further information.
+///
+/// Panics if `m` is zero.
+pub fn derangements_mod(n: u64, m: u64) -> u64
+{
+    assert!(m != 0, "modulus must be nonzero");
+    let mut res = 1 % m;
+    for i in 1..=n {
+        res = mod_mul(res, i, m);
+        res = if i % 2 == 0 { add_mod(res, 1, m) } else { add_mod(res, m - 1 % m, m) };
+    }
+    res
+}
+
+/// Computes the ménage number `U_n`, the number of ways to seat `n` couples at a round table with `2n` numbered seats,
+/// once the women are seated in alternate seats, so that men and women alternate and nobody sits next to their partner.
+///
+/// Uses the recurrence `(n - 2) U_n = n (n - 2) U_(n-1) + n U_(n-2) + 4 (-1)^(n+1)` with checked arithmetic,
+/// starting from `U_1 = -1` and `U_2 = 0`, while `U_0 = 1` and the actual count for one couple is `0`.
+/// Returns `None` if the result does not fit in `u128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(menage(3), Some(1));
+/// assert_eq!(menage(6), Some(80));
+/// assert_eq!(menage(10), Some(439792));
+/// ```
+pub fn menage(n: u64) -> Option<u128>
+{
+    match n {
+        0 => return Some(1),
+        1 | 2 => return Some(0),
+        _ => {}
+    }
+    let (mut prev, mut res): (i128, i128) = (-1, 0);
+    for i in 3..=n as i128 {
+        // U_i = i U_(i-1) + (i U_(i-2) + 4 (-1)^(i+1))/(i - 2), where the division is exact
+        let sign = if i % 2 == 0 { -4 } else { 4 };
+        let next = i.checked_mul(res)?.checked_add((i.checked_mul(prev)?.checked_add(sign)?) / (i - 2))?;
+        prev = res;
+        res = next;
+    }
+    Some(res as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bell_mod_matches_triangle() {
+        let bell: [u64; 16] = [1, 1, 2, 5, 15, 52, 203, 877, 4140, 21147, 115975, 678570, 4213597, 27644437, 190899322, 1382958545];
+        for &p in [2, 3, 5, 7, 11, 13, 1_000_000_007].iter() {
+            for (n, &b) in bell.iter().enumerate() {
+                assert_eq!(bell_mod(n as u64, p), b % p, "{} {}", n, p);
+            }
+        }
+        // long sequences computed by the triangle
+        for &p in [2, 3, 5, 7, 31, 97].iter() {
+            for (n, &b) in bell_triangle_mod(500, p).iter().enumerate() {
+                assert_eq!(bell_mod(n as u64, p), b, "{} {}", n, p);
+            }
+        }
+    }
+
+    #[test]
+    fn bell_mod_periodic() {
+        // the sequence modulo p is periodic with period dividing (p^p - 1)/(p - 1)
+        assert_eq!(bell_mod(3 + 13, 3), bell_mod(3, 3));
+        assert_eq!(bell_mod(7 + 781, 5), bell_mod(7, 5));
+        assert_eq!(bell_mod(u64::MAX, 2), bell_mod(u64::MAX % 3, 2));
+        assert_eq!(bell_mod(0, 2), 1);
+    }
+
+    #[test]
+    fn partition_counts_match_naive() {
+        // naive dynamic programming over the largest part
+        let len = 300;
+        let m = 1_000_000_007;
+        let mut naive = vec![0u64; len];
+        naive[0] = 1;
+        for part in 1..len {
+            for i in part..len {
+                naive[i] = (naive[i] + naive[i - part]) % m;
+            }
+        }
+        assert_eq!(partition_counts_mod(len as u64 - 1, m), naive);
+        for &modulus in [1, 2, 5, 7, 11, 1 << 63].iter() {
+            let expected: Vec<u64> = partition_counts_mod(200, 1 << 63).iter().map(|&x| x % modulus).collect();
+            assert_eq!(partition_counts_mod(200, modulus), expected);
+        }
+        // Ramanujan's congruences
+        let p = partition_counts_mod(2000, 5 * 7 * 11);
+        for i in 0..150 {
+            assert_eq!(p[5 * i + 4] % 5, 0);
+            assert_eq!(p[7 * i + 5] % 7, 0);
+            assert_eq!(p[11 * i + 6] % 11, 0);
+        }
+        assert_eq!(partition_count_mod(0, 1), 0);
+        assert_eq!(partition_count_mod(0, 2), 1);
+    }
+
+    #[test]
+    fn derangements_match_naive() {
+        // D_n is the number of permutations without fixed points, counted by inclusion-exclusion
+        let mut factorial = 1u128;
+        for n in 0..=33u64 {
+            if n > 0 {
+                factorial *= n as u128;
+            }
+            let (mut plus, mut minus, mut term) = (0u128, 0u128, factorial);
+            for k in 0..=n as u128 {
+                if k % 2 == 0 { plus += term } else { minus += term }
+                term /= k + 1;
+            }
+            assert_eq!(derangements(n), Some(plus - minus), "{}", n);
+            for &m in [1u64, 2, 7, 1_000_000_007, u64::MAX].iter() {
+                assert_eq!(derangements_mod(n, m) as u128, (plus - minus) % m as u128);
+            }
+        }
+        assert_eq!(derangements(34), derangements(33).map(|d| 34 * d + 1));
+        assert_eq!(derangements(35), None);
+        assert_eq!(derangements_mod(1_000_000, 1_000_000_007), derangements_mod(1_000_000, 1_000_000_007 * 3) % 1_000_000_007);
+    }
+
+    #[test]
+    fn menage_matches_brute_force() {
+        // seat the men into the gaps between the seated women, man i must avoid gaps i and i + 1
+        fn brute(n: usize) -> u128 {
+            let mut perm: Vec<usize> = (0..n).collect();
+            let mut count = 0;
+            loop {
+                if perm.iter().enumerate().all(|(i, &g)| g != i && g != (i + 1) % n) {
+                    count += 1;
+                }
+                let i = match (1..n).rev().find(|&i| perm[i - 1] < perm[i]) {
+                    Some(i) => i,
+                    None => return count
+                };
+                let j = (i..n).rev().find(|&j| perm[j] > perm[i - 1]).unwrap();
+                perm.swap(i - 1, j);
+                perm[i..].reverse();
+            }
+        }
+        for n in 1..=8 {
+            assert_eq!(menage(n as u64), Some(brute(n)), "{}", n);
+        }
+        let known = [1u128, 0, 0, 1, 2, 13, 80, 579, 4738, 43387, 439792, 4890741, 59216642, 775596313, 10927434464];
+        for (n, &u) in known.iter().enumerate() {
+            assert_eq!(menage(n as u64), Some(u));
+        }
+        assert!(menage(33).is_some());
+        assert_eq!(menage(40), None);
+    }
+}
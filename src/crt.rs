@@ -0,0 +1,215 @@
+use crate::{mod_inverse, mod_mul, solve_linear_congruence};
+
+/// Solves the system of congruences `x ≡ r_i (mod m_i)` given as pairs `(r_i, m_i)`
+/// using the Chinese remainder theorem.
+///
+/// The moduli must be positive and pairwise coprime. Returns `(x, M)`, where `M` is the product
+/// of the moduli and `x` is the unique solution in `[0, M)`. An empty system gives `(0, 1)`.
+///
+/// Returns `None` if some modulus is not positive, the moduli are not pairwise coprime
+/// or `M` does not fit in `i64`. Intermediate values are computed in `i128`.
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)>
+{
+    let (mut x, mut modulus) = (0i64, 1i64);
+    for &(r, m) in congruences {
+        if m <= 0 {
+            return None;
+        }
+        let inv = mod_inverse(modulus % m, m)?;
+        let diff = (r as i128 - x as i128).rem_euclid(m as i128);
+        let t = diff * inv as i128 % m as i128;
+        modulus = modulus.checked_mul(m)?;
+        x = (x as i128 + t * (modulus / m) as i128) as i64;
+    }
+    Some((x, modulus))
+}
+
+/// Solves the system of congruences `x ≡ r_i (mod m_i)` given as pairs `(r_i, m_i)`,
+/// where the moduli may share common factors.
+///
+/// Returns `(x, L)`, where `L` is the least common multiple of the moduli and `x` is the unique solution in `[0, L)`.
+/// An empty system gives `(0, 1)`.
+///
+/// Returns `None` if the system is inconsistent, some modulus is not positive or `L` does not fit in `i64`.
+pub fn crt_general(congruences: &[(i64, i64)]) -> Option<(i64, i64)>
+{
+    let (mut x, mut modulus) = (0i64, 1i64);
+    for &(r, m) in congruences {
+        if m <= 0 {
+            return None;
+        }
+        let diff = (r as i128 - x as i128).rem_euclid(m as i128) as i64;
+        let t = solve_linear_congruence(modulus % m, diff, m)?;
+        let new_modulus = modulus.checked_mul(t.modulus)?;
+        x = (x as i128 + t.x as i128 * modulus as i128) as i64;
+        modulus = new_modulus;
+    }
+    Some((x, modulus))
+}
+
+/// Computes `x mod modulus`, where `x` is the solution in `[0, M)` of the system of congruences
+/// `x ≡ r_i (mod m_i)` with pairwise coprime moduli and `M` is the product of the moduli.
+///
+/// Uses Garner's algorithm, which never forms `M` itself, so it works for many small moduli
+/// whose product is far beyond `i64`. Runs in `O(n^2)` for `n` congruences.
+///
+/// Returns `None` if some modulus or `modulus` is not positive, or the moduli are not pairwise coprime.
+pub fn crt_garner(congruences: &[(i64, i64)], modulus: i64) -> Option<i64>
+{
+    if modulus <= 0 || congruences.iter().any(|&(_, m)| m <= 0) {
+        return None;
+    }
+    let n = congruences.len();
+    let mut moduli: Vec<u64> = congruences.iter().map(|&(_, m)| m as u64).collect();
+    moduli.push(modulus as u64);
+    // prod[j] = m_0 * ... * m_(i-1) mod moduli[j] and value[j] = x_i mod moduli[j],
+    // where x_i is the solution of the first i congruences.
+    let mut prod: Vec<u64> = moduli.iter().map(|&m| 1 % m).collect();
+    let mut value = vec![0u64; n + 1];
+    for (i, &(r, m)) in congruences.iter().enumerate() {
+        let inv = mod_inverse(prod[i] as i64, m)? as u64;
+        let m = m as u64;
+        let r = r.rem_euclid(m as i64) as u64;
+        let digit = mod_mul((r + m - value[i]) % m, inv, m);
+        for j in i + 1..=n {
+            value[j] = (value[j] + mod_mul(digit, prod[j], moduli[j])) % moduli[j];
+            prod[j] = mod_mul(prod[j], m, moduli[j]);
+        }
+    }
+    Some(value[n] as i64)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn test_crt(congruences: &[(i64, i64)], res: Option<(i64, i64)>) {
+        assert_eq!(crt(congruences), res);
+        if let Some((x, m)) = res {
+            for &(r, mi) in congruences {
+                assert_eq!(x.rem_euclid(mi), r.rem_euclid(mi));
+            }
+            assert!(0 <= x && x < m);
+        }
+    }
+
+    #[test]
+    fn crt_empty() {
+        test_crt(&[], Some((0, 1)));
+    }
+    #[test]
+    fn crt_single() {
+        test_crt(&[(-3, 7)], Some((4, 7)));
+        test_crt(&[(5, 1)], Some((0, 1)));
+    }
+    #[test]
+    fn crt_sun_tzu() {
+        test_crt(&[(2, 3), (3, 5), (2, 7)], Some((23, 105)));
+    }
+    #[test]
+    fn crt_negative_residues() {
+        test_crt(&[(-1, 4), (-1, 9), (-1, 25)], Some((899, 900)));
+    }
+    #[test]
+    fn crt_not_coprime() {
+        test_crt(&[(1, 4), (3, 6)], None);
+        test_crt(&[(1, 4), (1, 4)], None);
+    }
+    #[test]
+    fn crt_invalid_modulus() {
+        test_crt(&[(1, 0)], None);
+        test_crt(&[(1, 3), (1, -5)], None);
+    }
+    #[test]
+    fn crt_large_moduli() {
+        let (p, q) = (3_037_000_493i64, 3_037_000_453i64);
+        let x = 9_000_000_000_000_000_123i64;
+        test_crt(&[(x % p, p), (x % q, q)], Some((x, p * q)));
+    }
+    #[test]
+    fn crt_overflow() {
+        test_crt(&[(1, 3_037_000_493), (2, 3_037_000_453), (3, 7)], None);
+    }
+    #[test]
+    fn crt_brute_force() {
+        let moduli = [4i64, 9, 5, 7];
+        for x in 0..4 * 9 * 5 * 7 {
+            let congruences: Vec<(i64, i64)> = moduli.iter().map(|&m| (x % m, m)).collect();
+            test_crt(&congruences, Some((x, 1260)));
+        }
+    }
+
+    fn test_crt_general(congruences: &[(i64, i64)], res: Option<(i64, i64)>) {
+        assert_eq!(crt_general(congruences), res);
+        if let Some((x, m)) = res {
+            for &(r, mi) in congruences {
+                assert_eq!(x.rem_euclid(mi), r.rem_euclid(mi));
+            }
+            assert!(0 <= x && x < m);
+        }
+    }
+
+    #[test]
+    fn crt_general_coprime() {
+        test_crt_general(&[], Some((0, 1)));
+        test_crt_general(&[(2, 3), (3, 5), (2, 7)], Some((23, 105)));
+    }
+    #[test]
+    fn crt_general_shared_factors() {
+        test_crt_general(&[(1, 4), (3, 6)], Some((9, 12)));
+        test_crt_general(&[(5, 12), (11, 18), (2, 9)], Some((29, 36)));
+        test_crt_general(&[(5, 12), (11, 18), (3, 9)], None);
+        test_crt_general(&[(5, 12), (11, 18), (5, 8)], Some((29, 72)));
+        test_crt_general(&[(3, 10), (3, 10)], Some((3, 10)));
+    }
+    #[test]
+    fn crt_general_inconsistent() {
+        test_crt_general(&[(1, 4), (2, 6)], None);
+        test_crt_general(&[(0, 2), (1, 4)], None);
+    }
+    #[test]
+    fn crt_general_invalid() {
+        test_crt_general(&[(1, 4), (2, 0)], None);
+        test_crt_general(&[(1, 3_037_000_493), (2, 3_037_000_453), (3, 7)], None);
+    }
+    #[test]
+    fn crt_general_brute_force() {
+        for m1 in 1..13i64 {
+            for m2 in 1..13i64 {
+                let l = crate::lcm(m1, m2);
+                for r1 in 0..m1 {
+                    for r2 in 0..m2 {
+                        let expected = (0..l).find(|x| x % m1 == r1 && x % m2 == r2).map(|x| (x, l));
+                        test_crt_general(&[(r1, m1), (r2, m2)], expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crt_garner_matches_crt() {
+        let congruences = [(2, 3), (3, 5), (2, 7), (-1, 11)];
+        let (x, m) = crt(&congruences).unwrap();
+        assert_eq!(crt_garner(&congruences, m), Some(x));
+        assert_eq!(crt_garner(&congruences, 1_000_000_007), Some(x % 1_000_000_007));
+        assert_eq!(crt_garner(&congruences, 1), Some(0));
+        assert_eq!(crt_garner(&[], 17), Some(0));
+    }
+    #[test]
+    fn crt_garner_beyond_i64() {
+        let x: u128 = 123_456_789_012_345_678_901_234_567_890;
+        let moduli = [998_244_353i64, 1_004_535_809, 469_762_049, 167_772_161];
+        let congruences: Vec<(i64, i64)> = moduli.iter().map(|&m| ((x % m as u128) as i64, m)).collect();
+        let target = 1_000_000_007;
+        assert_eq!(crt_garner(&congruences, target), Some((x % target as u128) as i64));
+        assert_eq!(crt_garner(&congruences, i64::MAX), Some((x % i64::MAX as u128) as i64));
+    }
+    #[test]
+    fn crt_garner_invalid() {
+        assert_eq!(crt_garner(&[(1, 4), (3, 6)], 100), None);
+        assert_eq!(crt_garner(&[(1, 4)], 0), None);
+        assert_eq!(crt_garner(&[(1, -4)], 10), None);
+    }
+}
@@ -0,0 +1,541 @@
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::{gcd, multiplicative_order};
+
+/// Expansion of a fraction in a positional base, `integer.prefix(repetend)` with the repetend repeated forever.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct RadixExpansion
+{
+    pub integer: u64,
+    /// Digits after the point before the repeating part.
+    pub prefix: Vec<u64>,
+    /// Repeating digits, empty if the expansion terminates.
+    pub repetend: Vec<u64>
+}
+
+/// Computes the expansion of `p/q` in the given base as the integer part, the non-repeating prefix and the repetend.
+///
+/// For `p/q` in lowest terms write `q = q1 * q2` where every prime of `q1` divides the base and `q2` is coprime with it.
+/// The prefix is as long as it takes for powers of the base to absorb `q1` and the repetend has
+/// the length of the multiplicative order of the base modulo `q2`, which can be up to `q2 - 1` digits.
+/// The digits are then produced by long division.
+///
+/// Panics if `q` is zero or `base < 2`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // 1/7 = 0.(142857)
+/// let res = decimal_expansion(1, 7, 10);
+/// assert_eq!((res.integer, res.prefix, res.repetend), (0, vec![], vec![1, 4, 2, 8, 5, 7]));
+/// // 19/12 = 1.58(3)
+/// let res = decimal_expansion(19, 12, 10);
+/// assert_eq!((res.integer, res.prefix, res.repetend), (1, vec![5, 8], vec![3]));
+/// // 5/8 = 0.101 in binary
+/// assert_eq!(decimal_expansion(5, 8, 2).prefix, vec![1, 0, 1]);
+/// ```
+pub fn decimal_expansion(p: u64, q: u64, base: u64) -> RadixExpansion
+{
+    assert!(q != 0, "denominator must be nonzero");
+    assert!(base >= 2, "base must be at least 2");
+    let g = gcd(p, q);
+    let (p, q) = (p / g, q / g);
+    // strip the primes shared with the base, each round takes one more digit of the prefix
+    let (mut coprime, mut prefix_len) = (q, 0);
+    loop {
+        let g = gcd(coprime, base);
+        if g == 1 {
+            break;
+        }
+        coprime /= g;
+        prefix_len += 1;
+    }
+    let period = if coprime == 1 { 0 } else { multiplicative_order(base % coprime, coprime).unwrap() };
+    let mut remainder = p % q;
+    let mut next_digit = || {
+        let x = remainder as u128 * base as u128;
+        remainder = (x % q as u128) as u64;
+        (x / q as u128) as u64
+    };
+    let prefix = (0..prefix_len).map(|_| next_digit()).collect();
+    let repetend = (0..period).map(|_| next_digit()).collect();
+    RadixExpansion { integer: p / q, prefix, repetend }
+}
+
+/// Error returned when converting digits to a number fails.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum DigitError
+{
+    /// The digit at `index` is not smaller than its radix.
+    InvalidDigit { index: usize, digit: u64, radix: u64 },
+    /// The value does not fit in the result type.
+    Overflow
+}
+
+impl fmt::Display for DigitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigitError::InvalidDigit { index, digit, radix } => write!(f, "digit {} at index {} is not smaller than radix {}", digit, index, radix),
+            DigitError::Overflow => f.write_str("number does not fit in the result type")
+        }
+    }
+}
+
+impl std::error::Error for DigitError {}
+
+/// Computes the digits of `n` in the given base, the most significant first. Zero has the single digit 0.
+///
+/// Panics if `base < 2`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(to_digits(1234, 10), vec![1, 2, 3, 4]);
+/// assert_eq!(to_digits(10, 2), vec![1, 0, 1, 0]);
+/// assert_eq!(to_digits(0, 7), vec![0]);
+/// ```
+pub fn to_digits(mut n: u64, base: u64) -> Vec<u64>
+{
+    assert!(base >= 2, "base must be at least 2");
+    let mut res = vec![n % base];
+    n /= base;
+    while n > 0 {
+        res.push(n % base);
+        n /= base;
+    }
+    res.reverse();
+    res
+}
+
+/// Computes the number with the given digits in the given base, the most significant first.
+/// The empty slice gives 0 and leading zeros are allowed.
+///
+/// Returns an error if a digit is not smaller than the base or the number does not fit. Panics if `base < 2`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(from_digits(&[1, 2, 3, 4], 10), Ok(1234));
+/// assert_eq!(from_digits(&[1, 2], 2), Err(DigitError::InvalidDigit { index: 1, digit: 2, radix: 2 }));
+/// ```
+pub fn from_digits(digits: &[u64], base: u64) -> Result<u64, DigitError>
+{
+    assert!(base >= 2, "base must be at least 2");
+    from_mixed_radix_by(digits, |_| base)
+}
+
+/// Computes the digits of `n` in the mixed-radix system with the given radices, the most significant first,
+/// so that the digit `d[i]` lies in `[0, radices[i])` and has the weight `radices[i+1] * ... * radices[k-1]`.
+///
+/// Returns `None` if `n` is not smaller than the product of the radices. Panics if a radix is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // 100000 seconds are 1 day, 3 hours, 46 minutes and 40 seconds
+/// assert_eq!(to_mixed_radix(100_000, &[7, 24, 60, 60]), Some(vec![1, 3, 46, 40]));
+/// assert_eq!(to_mixed_radix(100, &[3, 3]), None);
+/// ```
+pub fn to_mixed_radix(mut n: u64, radices: &[u64]) -> Option<Vec<u64>>
+{
+    assert!(radices.iter().all(|&r| r > 0), "radix must be positive");
+    let mut res = vec![0; radices.len()];
+    for (d, &r) in res.iter_mut().zip(radices).rev() {
+        *d = n % r;
+        n /= r;
+    }
+    if n == 0 { Some(res) } else { None }
+}
+
+/// Computes the number with the given digits in the mixed-radix system with the given radices, the most significant first.
+/// Go to [to_mixed_radix] for further information.
+///
+/// Returns an error if a digit is not smaller than its radix or the number does not fit.
+/// Panics if the lengths of `digits` and `radices` differ.
+pub fn from_mixed_radix(digits: &[u64], radices: &[u64]) -> Result<u64, DigitError>
+{
+    assert_eq!(digits.len(), radices.len(), "every digit must have a radix");
+    from_mixed_radix_by(digits, |i| radices[i])
+}
+
+/// Computes the digits of `n` in the factorial number system, the most significant first, ending with the digit for `0!`,
+/// which is always 0. The digit for `k!` is at most `k`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // 463 = 3*5! + 4*4! + 1*3! + 0*2! + 1*1! + 0*0!
+/// assert_eq!(to_factorial_base(463), vec![3, 4, 1, 0, 1, 0]);
+/// assert_eq!(to_factorial_base(0), vec![0]);
+/// ```
+pub fn to_factorial_base(mut n: u64) -> Vec<u64>
+{
+    let mut res = vec![0];
+    let mut radix = 2;
+    while n > 0 {
+        res.push(n % radix);
+        n /= radix;
+        radix += 1;
+    }
+    res.reverse();
+    res
+}
+
+/// Computes the number with the given digits in the factorial number system, the most significant first,
+/// where the last digit stands for `0!`. Go to [to_factorial_base] for further information.
+///
+/// Returns an error if the digit for `k!` exceeds `k` or the number does not fit.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(from_factorial_base(&[3, 4, 1, 0, 1, 0]), Ok(463));
+/// assert_eq!(from_factorial_base(&[2, 0]), Err(DigitError::InvalidDigit { index: 0, digit: 2, radix: 2 }));
+/// ```
+pub fn from_factorial_base(digits: &[u64]) -> Result<u64, DigitError>
+{
+    let len = digits.len() as u64;
+    from_mixed_radix_by(digits, |i| len - i as u64)
+}
+
+/// Evaluates the digits with the radix of the `i`-th digit given by `radix(i)`.
+fn from_mixed_radix_by<F: Fn(usize) -> u64>(digits: &[u64], radix: F) -> Result<u64, DigitError>
+{
+    let mut res: u64 = 0;
+    for (index, &digit) in digits.iter().enumerate() {
+        let r = radix(index);
+        if digit >= r {
+            return Err(DigitError::InvalidDigit { index, digit, radix: r });
+        }
+        res = res.checked_mul(r).and_then(|x| x.checked_add(digit)).ok_or(DigitError::Overflow)?;
+    }
+    Ok(res)
+}
+
+/// Computes the balanced ternary digits of `n`, which are -1, 0 and 1 with weights `3^k`, the most significant first.
+/// Zero has the single digit 0.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // 8 = 9 - 1 and -8 = -9 + 1
+/// assert_eq!(to_balanced_ternary(8), vec![1, 0, -1]);
+/// assert_eq!(to_balanced_ternary(-8), vec![-1, 0, 1]);
+/// ```
+pub fn to_balanced_ternary(n: i64) -> Vec<i8>
+{
+    normalize_balanced_ternary(&[n])
+}
+
+/// Computes the value of base 3 digits, the most significant first. The digits may be any integers,
+/// so sums and products of balanced ternary numbers can be evaluated digit-wise before normalizing.
+/// Returns `None` if the value does not fit.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(from_balanced_ternary(&[1, 0, -1]), Some(8));
+/// assert_eq!(from_balanced_ternary(&[2, -5]), Some(1));
+/// ```
+pub fn from_balanced_ternary<T: Copy + Into<i64>>(digits: &[T]) -> Option<i64>
+{
+    evaluate(digits.iter().map(|&d| d.into() as i128), 3)
+}
+
+/// Converts base 3 digits with arbitrary integer values, the most significant first, to proper balanced ternary digits
+/// of the same value by carrying from the least significant digit.
+///
+/// Panics if the value does not fit in `i64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // digit-wise sum of 5 = [1, -1, -1] and 7 = [1, -1, 1]
+/// assert_eq!(normalize_balanced_ternary(&[2, -2, 0]), vec![1, 1, 0]);
+/// ```
+pub fn normalize_balanced_ternary(digits: &[i64]) -> Vec<i8>
+{
+    normalize(digits, 3, |v| (v + 1).rem_euclid(3) - 1).into_iter().map(|d| d as i8).collect()
+}
+
+/// Computes the digits of `n` in base `-base`, which lie in `[0, base)` and have weights `(-base)^k`,
+/// the most significant first. Every integer has such a representation without a sign, base 2 gives negabinary
+/// and base 10 negadecimal. Zero has the single digit 0.
+///
+/// Panics if `base < 2`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // -3 = 1 * 4 + 1 * (-2) + 1
+/// assert_eq!(to_negabase(-3, 2), vec![1, 1, 0, 1]);
+/// assert_eq!(to_negabase(15, 10), vec![1, 9, 5]);
+/// ```
+pub fn to_negabase(n: i64, base: u64) -> Vec<u64>
+{
+    normalize_negabase(&[n], base)
+}
+
+/// Computes the number with the given digits in base `-base`, the most significant first.
+/// Go to [to_negabase] for further information.
+///
+/// Returns an error if a digit is not smaller than `base` or the number does not fit. Panics if `base < 2`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(from_negabase(&[1, 1, 0, 1], 2), Ok(-3));
+/// assert_eq!(from_negabase(&[1, 9, 5], 10), Ok(15));
+/// ```
+pub fn from_negabase(digits: &[u64], base: u64) -> Result<i64, DigitError>
+{
+    assert!(base >= 2, "base must be at least 2");
+    if let Some((index, &digit)) = digits.iter().enumerate().find(|&(_, &d)| d >= base) {
+        return Err(DigitError::InvalidDigit { index, digit, radix: base });
+    }
+    evaluate(digits.iter().map(|&d| d as i128), -(base as i128)).ok_or(DigitError::Overflow)
+}
+
+/// Converts base `-base` digits with arbitrary integer values, the most significant first, to proper digits
+/// in `[0, base)` of the same value by carrying from the least significant digit.
+///
+/// Panics if `base < 2` or the value does not fit in `i64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // digit-wise sum of 3 = [1, 1, 1] and 1 = [1] in negabinary
+/// assert_eq!(normalize_negabase(&[1, 1, 2], 2), vec![1, 0, 0]);
+/// assert_eq!(normalize_negabase(&[2], 2), vec![1, 1, 0]);
+/// ```
+pub fn normalize_negabase(digits: &[i64], base: u64) -> Vec<u64>
+{
+    assert!(base >= 2, "base must be at least 2");
+    let base = base as i128;
+    normalize(digits, -base, |v| v.rem_euclid(base)).into_iter().map(|d| d as u64).collect()
+}
+
+/// Evaluates the digits, the most significant first, in the given radix. Returns `None` if the value does not fit.
+fn evaluate<I: Iterator<Item = i128>>(digits: I, radix: i128) -> Option<i64>
+{
+    let mut res: i128 = 0;
+    for d in digits {
+        res = res.checked_mul(radix)?.checked_add(d)?;
+        // digits of u64 size cannot bring a value this large back into range
+        if res.unsigned_abs() > 1 << 100 {
+            return None;
+        }
+    }
+    i64::try_from(res).ok()
+}
+
+/// Carries the digits, the most significant first, into the digits chosen by `digit(v)` for the value `v` at each position.
+fn normalize<F: Fn(i128) -> i128>(digits: &[i64], radix: i128, digit: F) -> Vec<i128>
+{
+    assert!(evaluate(digits.iter().map(|&d| d as i128), radix).is_some(), "value must fit in i64");
+    let mut res = Vec::new();
+    let mut carry: i128 = 0;
+    let mut iter = digits.iter().rev();
+    loop {
+        let v = carry + iter.next().map_or(0, |&d| d as i128);
+        let d = digit(v);
+        res.push(d);
+        carry = (v - d) / radix;
+        if carry == 0 && iter.len() == 0 {
+            break;
+        }
+    }
+    while res.len() > 1 && res[res.len() - 1] == 0 {
+        res.pop();
+    }
+    res.reverse();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // finds the expansion by long division with remainder cycle detection
+    fn expansion_naive(p: u64, q: u64, base: u64) -> RadixExpansion {
+        let mut seen = std::collections::HashMap::new();
+        let (mut digits, mut remainder) = (Vec::new(), p % q);
+        while remainder != 0 && !seen.contains_key(&remainder) {
+            seen.insert(remainder, digits.len());
+            digits.push(remainder * base / q);
+            remainder = remainder * base % q;
+        }
+        let start = if remainder == 0 { digits.len() } else { seen[&remainder] };
+        RadixExpansion { integer: p / q, prefix: digits[..start].to_vec(), repetend: digits[start..].to_vec() }
+    }
+
+    #[test]
+    fn decimal_expansion_brute_force() {
+        for base in 2..13 {
+            for q in 1..80 {
+                for p in 0..2 * q {
+                    assert_eq!(decimal_expansion(p, q, base), expansion_naive(p, q, base), "{} {} {}", p, q, base);
+                }
+            }
+        }
+    }
+    #[test]
+    fn decimal_expansion_large() {
+        // 1 - 2^-63 = 0.(2^32 - 1)(2^32 - 2) in base 2^32
+        let res = decimal_expansion(u64::MAX, 1 << 63, 1 << 32);
+        assert_eq!(res, RadixExpansion { integer: 1, prefix: vec![u32::MAX as u64, u32::MAX as u64 - 1], repetend: vec![] });
+        let res = decimal_expansion(1, 1_000_003, 1 << 32);
+        assert_eq!(res.repetend.len() as u64, multiplicative_order((1 << 32) % 1_000_003, 1_000_003).unwrap());
+        let res = decimal_expansion(u64::MAX - 1, u64::MAX, u64::MAX);
+        assert_eq!((res.integer, res.prefix, res.repetend), (0, vec![u64::MAX - 1], vec![]));
+        // base ≡ 1 (mod 3) gives 1/3 = 0.((base - 1)/3)
+        let res = decimal_expansion(1, 3, u64::MAX - 2);
+        assert_eq!(res.repetend, [(u64::MAX - 3) / 3]);
+    }
+    #[test]
+    #[should_panic]
+    fn decimal_expansion_base_one() {
+        decimal_expansion(1, 3, 1);
+    }
+
+    #[test]
+    fn digits_round_trip() {
+        for base in 2..40 {
+            for n in (0..2000).chain(u64::MAX - 100..=u64::MAX) {
+                let digits = to_digits(n, base);
+                assert!(digits[0] != 0 || digits.len() == 1);
+                assert!(digits.iter().all(|&d| d < base));
+                assert_eq!(from_digits(&digits, base), Ok(n));
+            }
+        }
+        assert_eq!(to_digits(u64::MAX, u64::MAX), [1, 0]);
+        assert_eq!(to_digits(u64::MAX, 16), [15; 16]);
+        assert_eq!(from_digits(&[], 10), Ok(0));
+        assert_eq!(from_digits(&[0, 0, 4, 2], 10), Ok(42));
+        assert_eq!(from_digits(&[1, 0, 0], 1 << 32), Err(DigitError::Overflow));
+        assert_eq!(from_digits(&[1, 8, 4, 4, 6, 7, 4, 4, 0, 7, 3, 7, 0, 9, 5, 5, 1, 6, 1, 6], 10), Err(DigitError::Overflow));
+        assert_eq!(from_digits(&[1, 8, 4, 4, 6, 7, 4, 4, 0, 7, 3, 7, 0, 9, 5, 5, 1, 6, 1, 5], 10), Ok(u64::MAX));
+    }
+    #[test]
+    fn mixed_radix_round_trip() {
+        let radices = [3, 1, 4, 5, 2];
+        for n in 0..120 {
+            let digits = to_mixed_radix(n, &radices).unwrap();
+            assert!(digits.iter().zip(&radices).all(|(&d, &r)| d < r));
+            assert_eq!(from_mixed_radix(&digits, &radices), Ok(n));
+        }
+        assert_eq!(to_mixed_radix(120, &radices), None);
+        assert_eq!(to_mixed_radix(0, &[]), Some(vec![]));
+        assert_eq!(to_mixed_radix(1, &[]), None);
+        assert_eq!(from_mixed_radix(&[0, 1, 4], &[3, 1, 4]), Err(DigitError::InvalidDigit { index: 1, digit: 1, radix: 1 }));
+        assert_eq!(to_mixed_radix(u64::MAX, &[u64::MAX, 2]), Some(vec![u64::MAX / 2, 1]));
+    }
+    #[test]
+    fn factorial_base_round_trip() {
+        let factorials: Vec<u64> = (0..21u64).scan(1u64, |f, k| { *f *= k.max(1); Some(*f) }).collect();
+        for n in (0..5000).chain(u64::MAX - 100..=u64::MAX) {
+            let digits = to_factorial_base(n);
+            let len = digits.len();
+            assert_eq!(digits[len - 1], 0);
+            assert!(digits.iter().enumerate().all(|(i, &d)| d <= (len - 1 - i) as u64));
+            let value: u128 = digits.iter().rev().zip(&factorials).map(|(&d, &f)| d as u128 * f as u128).sum();
+            assert_eq!(value, n as u128);
+            assert_eq!(from_factorial_base(&digits), Ok(n));
+        }
+        // 20 * 20! does not fit
+        let mut digits = vec![0; 21];
+        digits[0] = 20;
+        assert_eq!(from_factorial_base(&digits), Err(DigitError::Overflow));
+        assert_eq!(from_factorial_base(&[0, 1]), Err(DigitError::InvalidDigit { index: 1, digit: 1, radix: 1 }));
+        assert_eq!(from_factorial_base(&[]), Ok(0));
+    }
+    #[test]
+    fn balanced_ternary_round_trip() {
+        for n in (-5000..5000).chain(i64::MIN..i64::MIN + 100).chain(i64::MAX - 100..=i64::MAX) {
+            let digits = to_balanced_ternary(n);
+            assert!(digits.iter().all(|&d| (-1..=1).contains(&d)));
+            assert!(digits[0] != 0 || digits == [0]);
+            assert_eq!(from_balanced_ternary(&digits), Some(n));
+            if n != i64::MIN {
+                assert_eq!(to_balanced_ternary(-n), digits.iter().map(|&d| -d).collect::<Vec<_>>());
+            }
+        }
+        assert_eq!(to_balanced_ternary(0), vec![0]);
+        assert_eq!(from_balanced_ternary::<i8>(&[]), Some(0));
+        assert_eq!(from_balanced_ternary(&[1i8; 41]), None);
+    }
+    #[test]
+    fn balanced_ternary_arithmetic() {
+        for a in -300..300i64 {
+            for b in -30..30i64 {
+                let (x, y) = (to_balanced_ternary(a), to_balanced_ternary(b));
+                let len = x.len().max(y.len());
+                let mut sum = vec![0i64; len];
+                for (i, &d) in x.iter().rev().enumerate() {
+                    sum[len - 1 - i] += d as i64;
+                }
+                for (i, &d) in y.iter().rev().enumerate() {
+                    sum[len - 1 - i] += d as i64;
+                }
+                assert_eq!(normalize_balanced_ternary(&sum), to_balanced_ternary(a + b));
+                let product: Vec<i64> = x.iter().map(|&d| d as i64 * b).collect();
+                assert_eq!(normalize_balanced_ternary(&product), to_balanced_ternary(a * b));
+            }
+        }
+    }
+    #[test]
+    fn negabase_round_trip() {
+        for base in [2, 3, 10, 1 << 40, u64::MAX].iter().copied() {
+            for n in (-3000..3000).chain(i64::MIN..i64::MIN + 100).chain(i64::MAX - 100..=i64::MAX) {
+                let digits = to_negabase(n, base);
+                assert!(digits.iter().all(|&d| d < base));
+                assert!(digits[0] != 0 || digits == [0]);
+                assert_eq!(from_negabase(&digits, base), Ok(n));
+            }
+        }
+        for n in -1000..1000i64 {
+            // binary digits of n with the signs adjusted to the weights (-2)^k
+            let bits = to_digits(n.unsigned_abs(), 2);
+            let len = bits.len();
+            let digits: Vec<i64> = bits.iter().enumerate().map(|(i, &d)| if (len - 1 - i) % 2 == 1 { -n.signum() * d as i64 } else { n.signum() * d as i64 }).collect();
+            assert_eq!(normalize_negabase(&digits, 2), to_negabase(n, 2));
+        }
+        assert_eq!(to_negabase(2, 2), vec![1, 1, 0]);
+        assert_eq!(to_negabase(-1, 10), vec![1, 9]);
+        assert_eq!(from_negabase(&[1, 2, 1], 2), Err(DigitError::InvalidDigit { index: 1, digit: 2, radix: 2 }));
+        assert_eq!(from_negabase(&[1; 65], 2), Err(DigitError::Overflow));
+        assert_eq!(from_negabase(&[1, 0, 0], u64::MAX), Err(DigitError::Overflow));
+        assert_eq!(from_negabase(&[], 2), Ok(0));
+    }
+    #[test]
+    fn digit_error_display() {
+        assert_eq!(DigitError::InvalidDigit { index: 1, digit: 2, radix: 2 }.to_string(), "digit 2 at index 1 is not smaller than radix 2");
+        assert_eq!(DigitError::Overflow.to_string(), "number does not fit in the result type");
+    }
+}
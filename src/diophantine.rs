@@ -0,0 +1,436 @@
+use std::convert::TryFrom;
+
+use crate::{gcd, gcd_extended};
+
+/// All solutions of a linear Diophantine equation `a*x + b*y = c`,
+/// which are `x = x0 + t*dx` and `y = y0 - t*dy` for every integer `t`.
+///
+/// [solve_linear_diophantine] returns `dx >= 0` and the smallest non-negative `x0` if `dx > 0`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct LinearDiophantineSolution
+{
+    pub x0: i64,
+    pub y0: i64,
+    pub dx: i64,
+    pub dy: i64
+}
+
+impl LinearDiophantineSolution {
+    /// Returns the solution for parameter `t`.
+    pub fn solution(&self, t: i64) -> (i64, i64) {
+        (self.x0 + t * self.dx, self.y0 - t * self.dy)
+    }
+
+    /// Returns the solution with the smallest non-negative `x`. Returns `None` if `x` is negative for all solutions.
+    pub fn min_nonnegative_x(&self) -> Option<(i64, i64)> {
+        if self.dx == 0 {
+            return if self.x0 >= 0 { Some((self.x0, self.y0)) } else { None };
+        }
+        let t = -self.x0.div_euclid(self.dx.abs()) * self.dx.signum();
+        Some(self.solution(t))
+    }
+}
+
+/// Solves the linear Diophantine equation `a*x + b*y = c` in integers.
+///
+/// A solution exists iff `g = gcd(a, b)` divides `c`, one is obtained by scaling the Bézout coefficients
+/// of [gcd_extended] by `c/g` and all others differ by multiples of `(b/g, -a/g)`.
+/// Intermediate values are computed in `i128`.
+///
+/// Returns `None` if there is no solution, if `a = b = 0` (then every pair solves `0 = 0`)
+/// or if the normalized solution does not fit in `i64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let res = solve_linear_diophantine(6, 10, 8).unwrap();
+/// assert_eq!(res, LinearDiophantineSolution { x0: 3, y0: -1, dx: 5, dy: 3 });
+/// assert_eq!(res.solution(1), (8, -4));
+/// assert_eq!(solve_linear_diophantine(6, 10, 7), None);
+/// ```
+pub fn solve_linear_diophantine(a: i64, b: i64, c: i64) -> Option<LinearDiophantineSolution>
+{
+    let (a, b, c) = (a as i128, b as i128, c as i128);
+    let res = gcd_extended(a, b);
+    let g = res.gcd;
+    if g == 0 || c % g != 0 {
+        return None;
+    }
+    let (mut dx, mut dy) = (b / g, a / g);
+    if dx < 0 {
+        dx = -dx;
+        dy = -dy;
+    }
+    let mut x0 = res.x0 * (c / g);
+    if dx > 0 {
+        x0 = x0.rem_euclid(dx);
+    }
+    // b = 0 gives x0 = c/a directly, otherwise y0 follows from x0
+    let y0 = if b == 0 { res.y0 * (c / g) } else { (c - a * x0) / b };
+    Some(LinearDiophantineSolution {
+        x0: i64::try_from(x0).ok()?,
+        y0: i64::try_from(y0).ok()?,
+        dx: i64::try_from(dx).ok()?,
+        dy: i64::try_from(dy).ok()?
+    })
+}
+
+/// All solutions of a linear Diophantine equation `a[0]*x[0] + ... + a[n-1]*x[n-1] = c`,
+/// which are `x + t[0]*basis[0] + ... + t[k-1]*basis[k-1]` for all integers `t`.
+///
+/// `basis` is a basis of the lattice of solutions of the homogeneous equation with `c = 0`,
+/// it has `n - 1` vectors if some coefficient is nonzero and `n` otherwise.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct LinearDiophantineManySolution
+{
+    pub x: Vec<i64>,
+    pub basis: Vec<Vec<i64>>
+}
+
+impl LinearDiophantineManySolution {
+    /// Returns the solution for parameters `t`, one for every basis vector.
+    pub fn solution(&self, t: &[i64]) -> Vec<i64> {
+        let mut res = self.x.clone();
+        for (v, &ti) in self.basis.iter().zip(t) {
+            for (r, &vi) in res.iter_mut().zip(v) {
+                *r += ti * vi;
+            }
+        }
+        res
+    }
+}
+
+/// Solves the linear Diophantine equation `a[0]*x[0] + ... + a[n-1]*x[n-1] = c` in integers.
+///
+/// Like [crate::gcd_extended_many], the coefficients are combined one at a time. Combining `g = gcd(a[0], ..., a[i-1])`
+/// with `a[i]` by [gcd_extended] gives a unimodular transformation, whose second row yields one new vector
+/// of the homogeneous lattice, so the vectors together form its basis rather than just independent solutions.
+/// Intermediate values are computed in `i128`.
+///
+/// Returns `None` if there is no solution or a value does not fit in `i64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let res = solve_linear_diophantine_many(&[6, 10, 15], 7).unwrap();
+/// assert_eq!(res.basis.len(), 2);
+/// let x = res.solution(&[3, -2]);
+/// assert_eq!(6 * x[0] + 10 * x[1] + 15 * x[2], 7);
+/// assert_eq!(solve_linear_diophantine_many(&[6, 10], 7), None);
+/// ```
+pub fn solve_linear_diophantine_many(a: &[i64], c: i64) -> Option<LinearDiophantineManySolution>
+{
+    let n = a.len();
+    let mut g: i128 = 0;
+    // g = Σ coefs[j] * a[j] over the processed coefficients
+    let mut coefs: Vec<i128> = Vec::with_capacity(n);
+    let mut basis: Vec<Vec<i128>> = Vec::new();
+    for (i, &ai) in a.iter().enumerate() {
+        let ai = ai as i128;
+        if g == 0 {
+            // all previous coefficients are zero, each of them contributed a unit vector
+            if ai == 0 {
+                let mut v = vec![0; n];
+                v[i] = 1;
+                basis.push(v);
+            }
+            coefs.push(ai.signum());
+            g = ai.abs();
+            continue;
+        }
+        let res = gcd_extended(g, ai);
+        let mut v: Vec<i128> = coefs.iter().map(|&cj| ai / res.gcd * cj).collect();
+        v.push(-(g / res.gcd));
+        v.resize(n, 0);
+        basis.push(v);
+        for cj in coefs.iter_mut() {
+            *cj *= res.x0;
+        }
+        coefs.push(res.y0);
+        g = res.gcd;
+    }
+    let c = c as i128;
+    let scale = match g {
+        0 if c == 0 => 0,
+        0 => return None,
+        _ if c % g != 0 => return None,
+        _ => c / g
+    };
+    let to_i64 = |v: &[i128]| v.iter().map(|&vi| i64::try_from(vi).ok()).collect::<Option<Vec<i64>>>();
+    let x: Vec<i128> = coefs.iter().map(|&cj| cj.checked_mul(scale)).collect::<Option<_>>()?;
+    Some(LinearDiophantineManySolution {
+        x: to_i64(&x)?,
+        basis: basis.iter().map(|v| to_i64(v)).collect::<Option<_>>()?
+    })
+}
+
+/// Computes the Frobenius number of two coin denominations, the largest amount not expressible as
+/// `a*x + b*y` with non-negative `x, y`.
+///
+/// For coprime `a, b` this is `ab - a - b` by Sylvester's formula. The result is `-1` if `a` or `b` is 1,
+/// since every non-negative amount is representable. Returns `None` if `gcd(a, b) != 1`,
+/// in which case infinitely many amounts are not representable, or if the result does not fit in `i128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(frobenius(3, 5), Some(7));
+/// assert_eq!(frobenius(1, 5), Some(-1));
+/// assert_eq!(frobenius(4, 6), None);
+/// ```
+pub fn frobenius(a: u64, b: u64) -> Option<i128>
+{
+    if gcd(a, b) != 1 {
+        return None;
+    }
+    // ab - a - b = (a - 1)(b - 1) - 1
+    (a as i128 - 1).checked_mul(b as i128 - 1).map(|x| x - 1)
+}
+
+/// Computes the Frobenius number of a set of coin denominations, the largest amount not expressible as
+/// their non-negative integer combination. Zero denominations are ignored.
+///
+/// Uses the round-robin algorithm of Böcker and Lipták, which finds the smallest representable amount
+/// in each residue class modulo the smallest denomination `m` in `O(k m)` time and `O(m)` memory for `k` denominations,
+/// so it is intended for sets whose smallest denomination is at most a few millions.
+/// The result is `-1` if the set contains 1. Returns `None` if the denominations are not coprime or there are none.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(frobenius_set(&[6, 9, 20]), Some(43));
+/// assert_eq!(frobenius_set(&[6, 9, 21]), None);
+/// ```
+pub fn frobenius_set(coins: &[u64]) -> Option<i128>
+{
+    let table = residue_table(coins);
+    if table.is_empty() || table.contains(&u128::MAX) {
+        return None;
+    }
+    Some(*table.iter().max().unwrap() as i128 - table.len() as i128)
+}
+
+/// Returns true if `n` is a non-negative integer combination of `coins`. Otherwise returns false.
+///
+/// Builds the same table of smallest representable amounts per residue class as [frobenius_set],
+/// so the cost is dominated by the smallest non-zero denomination rather than by `n`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert!(is_representable(44, &[6, 9, 20]));
+/// assert!(!is_representable(43, &[6, 9, 20]));
+/// assert!(is_representable(0, &[]));
+/// ```
+pub fn is_representable(n: u64, coins: &[u64]) -> bool
+{
+    let table = residue_table(coins);
+    if table.is_empty() {
+        return n == 0;
+    }
+    n as u128 >= table[(n % table.len() as u64) as usize]
+}
+
+/// Computes the smallest non-negative combination of `coins` in each residue class modulo the smallest non-zero coin,
+/// with `u128::MAX` for the unreachable classes. Returns an empty table if there is no non-zero coin.
+fn residue_table(coins: &[u64]) -> Vec<u128>
+{
+    let m = match coins.iter().filter(|&&c| c > 0).min() {
+        Some(&m) => m,
+        None => return Vec::new()
+    };
+    let mut table = vec![u128::MAX; m as usize];
+    table[0] = 0;
+    for &c in coins.iter().filter(|&&c| c > 0 && c != m) {
+        // adding c walks the residues in gcd(m, c) cycles of length m / gcd(m, c),
+        // one pass from the smallest value of each cycle suffices
+        let g = gcd(m, c) as usize;
+        for r in 0..g {
+            let mut best = match (r..m as usize).step_by(g).map(|i| table[i]).min() {
+                Some(best) if best != u128::MAX => best,
+                _ => continue
+            };
+            for _ in 0..m as usize / g {
+                best += c as u128;
+                let i = (best % m as u128) as usize;
+                best = best.min(table[i]);
+                table[i] = best;
+            }
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn test_solve_linear_diophantine(a: i64, b: i64, c: i64) {
+        let res = match solve_linear_diophantine(a, b, c) {
+            Some(res) => res,
+            None => {
+                let g = crate::gcd(a, b);
+                assert!(g == 0 || c % g != 0, "{} {} {}", a, b, c);
+                return;
+            }
+        };
+        for t in -1..=1i128 {
+            let (x, y) = (res.x0 as i128 + t * res.dx as i128, res.y0 as i128 - t * res.dy as i128);
+            assert_eq!(a as i128 * x + b as i128 * y, c as i128, "{} {} {} {:?}", a, b, c, res);
+        }
+        assert!(res.dx >= 0);
+        let g = crate::gcd(a, b);
+        assert_eq!((res.dx, res.dy), ((b / g).abs(), if b < 0 { -a / g } else { a / g }));
+        if res.dx > 0 {
+            assert!(res.x0 >= 0 && res.x0 < res.dx);
+            assert_eq!(res.min_nonnegative_x(), Some((res.x0, res.y0)));
+        }
+    }
+
+    #[test]
+    fn solve_linear_diophantine_brute_force() {
+        for a in -12..=12 {
+            for b in -12..=12 {
+                for c in -30..=30 {
+                    test_solve_linear_diophantine(a, b, c);
+                }
+            }
+        }
+        test_solve_linear_diophantine(1_000_000_007, 998_244_353, i64::MAX);
+        test_solve_linear_diophantine(i64::MAX, i64::MAX - 1, i64::MIN);
+        test_solve_linear_diophantine(-6, 0, 12);
+    }
+    #[test]
+    fn solve_linear_diophantine_degenerate() {
+        assert_eq!(solve_linear_diophantine(0, 0, 0), None);
+        assert_eq!(solve_linear_diophantine(0, 5, 10), Some(LinearDiophantineSolution { x0: 0, y0: 2, dx: 1, dy: 0 }));
+        assert_eq!(solve_linear_diophantine(-3, 0, 12), Some(LinearDiophantineSolution { x0: -4, y0: 0, dx: 0, dy: -1 }));
+        let res = solve_linear_diophantine(-3, 0, 12).unwrap();
+        assert_eq!(res.min_nonnegative_x(), None);
+        assert_eq!(res.solution(2), (-4, 2));
+    }
+    #[test]
+    fn min_nonnegative_x_general() {
+        let res = LinearDiophantineSolution { x0: -17, y0: 10, dx: -5, dy: 2 };
+        assert_eq!(res.min_nonnegative_x(), Some((3, 18)));
+        let res = LinearDiophantineSolution { x0: 17, y0: 10, dx: 5, dy: 2 };
+        assert_eq!(res.min_nonnegative_x(), Some((2, 16)));
+    }
+
+    fn dot(a: &[i64], x: &[i64]) -> i128 {
+        a.iter().zip(x).map(|(&ai, &xi)| ai as i128 * xi as i128).sum()
+    }
+
+    fn test_solve_linear_diophantine_many(a: &[i64], c: i64) {
+        let g = crate::gcd_all(a);
+        let res = match solve_linear_diophantine_many(a, c) {
+            Some(res) => res,
+            None => {
+                assert!(if g == 0 { c != 0 } else { c % g != 0 }, "{:?} {}", a, c);
+                return;
+            }
+        };
+        assert_eq!(dot(a, &res.x), c as i128, "{:?} {}", a, c);
+        assert_eq!(res.basis.len(), if g == 0 { a.len() } else { a.len() - 1 });
+        for v in &res.basis {
+            assert_eq!(v.len(), a.len());
+            assert_eq!(dot(a, v), 0, "{:?} {:?}", a, v);
+        }
+        let x = res.solution(&vec![2; res.basis.len()]);
+        assert_eq!(dot(a, &x), c as i128);
+    }
+
+    #[test]
+    fn solve_linear_diophantine_many_brute_force() {
+        for a0 in -6..=6 {
+            for a1 in -6..=6 {
+                for a2 in -6..=6 {
+                    for c in -10..=10 {
+                        test_solve_linear_diophantine_many(&[a0, a1, a2], c);
+                    }
+                }
+            }
+        }
+        test_solve_linear_diophantine_many(&[], 0);
+        test_solve_linear_diophantine_many(&[0, 0, 5, 0, 7, 0], 3);
+        test_solve_linear_diophantine_many(&[1_000_000_007, 998_244_353, 1_000_000_009, 12], 1_000_000);
+        assert_eq!(solve_linear_diophantine_many(&[], 1), None);
+    }
+    #[test]
+    fn solve_linear_diophantine_many_lattice_basis() {
+        // the 2x2 minors of a basis of the homogeneous lattice are ±a/gcd(a)
+        for a0 in -5..=5i64 {
+            for a1 in -5..=5 {
+                for a2 in 1..=5 {
+                    let res = solve_linear_diophantine_many(&[a0, a1, a2], 0).unwrap();
+                    let (u, v) = (&res.basis[0], &res.basis[1]);
+                    let minors = [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]];
+                    let g = crate::gcd_all(&[a0, a1, a2]);
+                    let sign = minors[2].signum() * a2.signum();
+                    assert_eq!(minors, [sign * a0 / g, sign * a1 / g, sign * a2 / g], "{} {} {}", a0, a1, a2);
+                }
+            }
+        }
+    }
+
+    fn representable_naive(limit: usize, coins: &[u64]) -> Vec<bool> {
+        let mut res = vec![false; limit];
+        res[0] = true;
+        for n in 1..limit {
+            res[n] = coins.iter().any(|&c| c > 0 && c as usize <= n && res[n - c as usize]);
+        }
+        res
+    }
+
+    #[test]
+    fn frobenius_brute_force() {
+        for a in 0..30 {
+            for b in 0..30 {
+                let naive = representable_naive(1000, &[a, b]);
+                let expected = if naive[900..].iter().all(|&r| r) {
+                    Some(naive.iter().rposition(|&r| !r).map_or(-1, |n| n as i128))
+                } else {
+                    None
+                };
+                assert_eq!(frobenius(a, b), expected, "{} {}", a, b);
+                assert_eq!(frobenius_set(&[a, b]), expected, "{} {}", a, b);
+            }
+        }
+        assert_eq!(frobenius(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(frobenius(1 << 63, u64::MAX), Some(((1 << 63) - 1) * (u64::MAX as i128 - 1) - 1));
+    }
+    #[test]
+    fn frobenius_set_brute_force() {
+        let sets: [&[u64]; 8] = [&[6, 9, 20], &[12, 16, 20, 27], &[5, 7, 11, 0], &[31, 41, 59, 26], &[10, 15, 6], &[4, 6, 10], &[], &[0]];
+        for coins in sets.iter() {
+            let naive = representable_naive(3000, coins);
+            for (n, &r) in naive.iter().enumerate() {
+                assert_eq!(is_representable(n as u64, coins), r, "{} {:?}", n, coins);
+            }
+            let expected = if naive[2000..].iter().all(|&r| r) { naive.iter().rposition(|&r| !r).map(|n| n as i128) } else { None };
+            assert_eq!(frobenius_set(coins), expected, "{:?}", coins);
+        }
+        assert_eq!(frobenius_set(&[1, 1000]), Some(-1));
+    }
+    #[test]
+    fn frobenius_set_large() {
+        // the Frobenius number of an arithmetic sequence a, a + d, ..., a + s*d is (floor((a - 2) / s) + 1) a + (d - 1)(a - 1) - 1
+        let (a, d, s) = (100_003u64, 7u64, 4u64);
+        let coins: Vec<u64> = (0..=s).map(|i| a + i * d).collect();
+        let expected = ((a - 2) / s + 1) * a + (d - 1) * (a - 1) - 1;
+        assert_eq!(frobenius_set(&coins), Some(expected as i128));
+        assert!(is_representable(u64::MAX, &coins));
+        assert!(!is_representable(expected, &coins));
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use crate::residues::inverse;
+use crate::{factorize, gcd, mod_mul, mod_pow, multiplicative_order};
+
+/// Computes the discrete logarithm, the smallest `k >= 0` satisfying `base^k ≡ target (mod modulus)`.
+///
+/// If `base` is not coprime with `modulus`, common divisors are factored out first, which handles solutions
+/// before `base^k` becomes periodic. The rest is solved in the group of units.
+///
+/// Returns `None` if there is no solution. Panics if `modulus` is zero.
+///
+/// Dispatches to [discrete_log_pohlig_hellman], which is never asymptotically slower than [discrete_log_bsgs]
+/// and much faster when the order of `base` has only small prime factors.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(discrete_log(3, 13, 17), Some(4));
+/// assert_eq!(discrete_log(2, 0, 16), Some(4));
+/// assert_eq!(discrete_log(2, 3, 7), None);
+/// ```
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Option<u64>
+{
+    discrete_log_pohlig_hellman(base, target, modulus)
+}
+
+/// Computes the discrete logarithm using the baby-step giant-step algorithm with a hash map
+/// of `⌈sqrt(modulus)⌉` baby steps, so it takes `O(sqrt(modulus))` time and memory.
+/// Go to [discrete_log] for further information.
+pub fn discrete_log_bsgs(base: u64, target: u64, modulus: u64) -> Option<u64>
+{
+    match reduce_to_units(base, target, modulus) {
+        Reduced::Solved(k) => Some(k),
+        Reduced::Unsolvable => None,
+        Reduced::Units { base, coef, target, modulus, offset } => {
+            baby_step_giant_step(base, coef, target, modulus, modulus).map(|k| k + offset)
+        }
+    }
+}
+
+/// Computes the discrete logarithm using the Pohlig–Hellman algorithm.
+///
+/// The order of `base` is factorized and the logarithm is found modulo every prime power `q^e` of the order
+/// digit by digit, each digit by [discrete_log_bsgs] in the subgroup of order `q`, and combined by the Chinese
+/// remainder theorem. It takes `O(Σ e * sqrt(q))` group operations besides factorizing `λ(modulus)`.
+/// Go to [discrete_log] for further information.
+pub fn discrete_log_pohlig_hellman(base: u64, target: u64, modulus: u64) -> Option<u64>
+{
+    match reduce_to_units(base, target, modulus) {
+        Reduced::Solved(k) => Some(k),
+        Reduced::Unsolvable => None,
+        Reduced::Units { base, coef, target, modulus, offset } => {
+            let target = mod_mul(target, inverse(coef, modulus), modulus);
+            pohlig_hellman(base, target, modulus).map(|k| k + offset)
+        }
+    }
+}
+
+/// Discrete logarithm problem after factoring out common divisors of the base and the modulus.
+enum Reduced
+{
+    Solved(u64),
+    Unsolvable,
+    /// Solution is `offset + k` for the smallest `k >= 1` with `coef * base^k ≡ target (mod modulus)`,
+    /// where `base` and `coef` are coprime with `modulus`.
+    Units { base: u64, coef: u64, target: u64, modulus: u64, offset: u64 }
+}
+
+/// Factors out common divisors of `base` and `modulus` while checking the small exponents.
+fn reduce_to_units(base: u64, target: u64, modulus: u64) -> Reduced
+{
+    assert!(modulus != 0, "modulus must be positive");
+    let (mut base, mut target, mut modulus) = (base % modulus, target % modulus, modulus);
+    // invariant: coef * base^(k - offset) ≡ target (mod modulus)
+    let mut coef = 1 % modulus;
+    let mut offset = 0;
+    loop {
+        if coef == target {
+            return Reduced::Solved(offset);
+        }
+        let g = gcd(base, modulus);
+        if g == 1 {
+            return Reduced::Units { base, coef, target, modulus, offset };
+        }
+        if !target.is_multiple_of(g) {
+            return Reduced::Unsolvable;
+        }
+        // coef * (base/g) * base^(k - offset - 1) ≡ target/g (mod modulus/g)
+        modulus /= g;
+        target /= g;
+        coef = mod_mul(coef, base / g, modulus);
+        base %= modulus;
+        offset += 1;
+    }
+}
+
+/// Finds the smallest `k >= 1` with `coef * base^k ≡ target (mod modulus)` for `base` coprime with `modulus`,
+/// searching `k` up to `s^2` for `s = ⌊sqrt(bound)⌋ + 1`, where `bound` is at least the order of `base`.
+fn baby_step_giant_step(base: u64, coef: u64, target: u64, modulus: u64, bound: u64) -> Option<u64>
+{
+    let steps = bound.isqrt() + 1;
+    // target * base^j for j < steps, keeping the largest j for each value
+    let mut baby = HashMap::with_capacity(steps as usize);
+    let mut value = target;
+    for j in 0..steps {
+        baby.insert(value, j);
+        value = mod_mul(value, base, modulus);
+    }
+    let giant = mod_pow(base, steps, modulus);
+    let mut value = coef;
+    for i in 1..=steps {
+        value = mod_mul(value, giant, modulus);
+        if let Some(&j) = baby.get(&value) {
+            return Some(i * steps - j);
+        }
+    }
+    None
+}
+
+/// Finds the smallest `k >= 0` with `base^k ≡ target (mod modulus)` for `base` and `target` coprime with `modulus`.
+fn pohlig_hellman(base: u64, target: u64, modulus: u64) -> Option<u64>
+{
+    let order = multiplicative_order(base, modulus)?;
+    let (mut x, mut x_modulus) = (0u64, 1u64);
+    for &(q, e) in factorize(order).factors() {
+        let qe = q.pow(e);
+        let g = mod_pow(base, order / qe, modulus);
+        let h = mod_pow(target, order / qe, modulus);
+        let digit = discrete_log_prime_power(g, h, q, e, modulus)?;
+        // combine x mod x_modulus with digit mod q^e
+        let t = (digit as u128 + qe as u128 - (x % qe) as u128) * inverse(x_modulus % qe, qe) as u128 % qe as u128;
+        x += x_modulus * t as u64;
+        x_modulus *= qe;
+    }
+    if mod_pow(base, x, modulus) == target % modulus { Some(x) } else { None }
+}
+
+/// Finds `k` in `[0, q^e)` with `g^k ≡ h (mod modulus)` for `g` of order `q^e`, one base `q` digit at a time.
+fn discrete_log_prime_power(g: u64, h: u64, q: u64, e: u32, modulus: u64) -> Option<u64>
+{
+    // gamma has order q, the digit d_i solves gamma^d_i = (g^-x * h)^(q^(e-1-i))
+    let gamma = mod_pow(g, q.pow(e - 1), modulus);
+    let g_inv = inverse(g, modulus);
+    let mut x = 0;
+    let mut q_power = 1;
+    for i in 0..e {
+        let rest = mod_mul(mod_pow(g_inv, x, modulus), h, modulus);
+        let value = mod_pow(rest, q.pow(e - 1 - i), modulus);
+        let digit = if value == 1 % modulus { 0 } else { baby_step_giant_step(gamma, 1, value, modulus, q)? };
+        x += digit * q_power;
+        q_power *= q;
+    }
+    Some(x)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn discrete_log_naive(base: u64, target: u64, modulus: u64) -> Option<u64> {
+        let mut value = 1 % modulus;
+        for k in 0..=2 * modulus {
+            if value == target % modulus {
+                return Some(k);
+            }
+            value = mod_mul(value, base, modulus);
+        }
+        None
+    }
+
+    #[test]
+    fn discrete_log_brute_force() {
+        for modulus in 1..60u64 {
+            for base in 0..modulus {
+                for target in 0..modulus {
+                    let expected = discrete_log_naive(base, target, modulus);
+                    assert_eq!(discrete_log_bsgs(base, target, modulus), expected, "{} {} {}", base, target, modulus);
+                    assert_eq!(discrete_log_pohlig_hellman(base, target, modulus), expected, "{} {} {}", base, target, modulus);
+                }
+            }
+        }
+    }
+    #[test]
+    fn discrete_log_large() {
+        let p = 1_000_000_007;
+        for &k in &[0u64, 1, 12_345, 999_999_999, 500_000_003] {
+            assert_eq!(discrete_log_bsgs(5, mod_pow(5, k, p), p), Some(k));
+            assert_eq!(discrete_log(5, mod_pow(5, k, p), p), Some(k));
+        }
+        let m = 1 << 40;
+        assert_eq!(discrete_log_bsgs(3, mod_pow(3, 123_456_789, m), m).map(|k| mod_pow(3, k, m)), Some(mod_pow(3, 123_456_789, m)));
+        assert_eq!(discrete_log(6, mod_pow(6, 30, 1 << 40), 1 << 40), Some(30));
+        assert_eq!(discrete_log(6, 0, 1 << 40), Some(40));
+        assert_eq!(discrete_log(2, 1, 1), Some(0));
+        assert_eq!(discrete_log(0, 0, 10), Some(1));
+        assert_eq!(discrete_log(10, 1, 25), Some(0));
+        assert_eq!(discrete_log(10, 2, 25), None);
+    }
+    #[test]
+    fn pohlig_hellman_smooth_order() {
+        // p - 1 = 2^32 * 3 * 5 * 17 * 257 * 65537, far beyond the reach of BSGS
+        let p = 18_446_744_069_414_584_321u64;
+        for &k in &[0u64, 1, 123_456_789_123_456_789, p - 2] {
+            assert_eq!(discrete_log_pohlig_hellman(7, mod_pow(7, k, p), p), Some(k));
+        }
+        // the order of 3 modulo 2^63 is 2^61
+        let m = 1 << 63;
+        assert_eq!(discrete_log(3, mod_pow(3, (1 << 61) - 5, m), m), Some((1 << 61) - 5));
+        assert_eq!(discrete_log(3, 5, m), None);
+        assert_eq!(discrete_log(2, mod_pow(2, 600, p), p), Some(600 % 192));
+    }
+}
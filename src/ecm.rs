@@ -0,0 +1,193 @@
+use crate::wide::Montgomery;
+use crate::{gcd, primes_in_range, Sieve};
+
+/// Giant step of the second stage, primes are written as `m*D ± j` with `j < D/2`.
+const STAGE_2_STEP: u64 = 210;
+
+/// Point on a Montgomery curve in projective `(X : Z)` coordinates, both in Montgomery form.
+#[derive(Clone, Copy)]
+struct Point
+{
+    x: u128,
+    z: u128
+}
+
+/// Montgomery curve `By^2 = x^3 + Ax^2 + x` given by `A + 2C` and `4C` for `A/C = A`.
+struct Curve<'a>
+{
+    mont: &'a Montgomery,
+    a24_plus: u128,
+    c24: u128
+}
+
+impl<'a> Curve<'a> {
+    fn double(&self, p: Point) -> Point {
+        let m = self.mont;
+        let minus = m.mul(m.sub(p.x, p.z), m.sub(p.x, p.z));
+        let plus = m.mul(m.add(p.x, p.z), m.add(p.x, p.z));
+        let z = m.mul(self.c24, minus);
+        let x = m.mul(z, plus);
+        let diff = m.sub(plus, minus);
+        let z = m.mul(m.add(z, m.mul(self.a24_plus, diff)), diff);
+        Point { x, z }
+    }
+
+    /// Computes `p + q` given `p - q`.
+    fn add(&self, p: Point, q: Point, diff: Point) -> Point {
+        let m = self.mont;
+        let u = m.mul(m.sub(p.x, p.z), m.add(q.x, q.z));
+        let v = m.mul(m.add(p.x, p.z), m.sub(q.x, q.z));
+        let (sum, dif) = (m.add(u, v), m.sub(u, v));
+        Point { x: m.mul(diff.z, m.mul(sum, sum)), z: m.mul(diff.x, m.mul(dif, dif)) }
+    }
+
+    /// Computes `k*p` for `k >= 1` using the Montgomery ladder.
+    fn mul(&self, p: Point, k: u64) -> Point {
+        let (mut r0, mut r1) = (p, self.double(p));
+        for bit in (0..63 - k.leading_zeros()).rev() {
+            if (k >> bit) & 1 == 1 {
+                r0 = self.add(r1, r0, p);
+                r1 = self.double(r1);
+            } else {
+                r1 = self.add(r0, r1, p);
+                r0 = self.double(r0);
+            }
+        }
+        r0
+    }
+}
+
+/// Tries to find a nontrivial factor of `n` by Lenstra's elliptic curve method.
+///
+/// Runs up to `curves` Montgomery curves given by Suyama's parametrization with `σ = 6, 7, ...`.
+/// Each curve finds a prime factor `p` of `n` if the order of the curve modulo `p` is a product
+/// of prime powers not exceeding `b1` (stage 1) and at most one additional prime in `(b1, b2]` (stage 2).
+/// Unlike Pollard's `p - 1`, a failing curve can be replaced by another one, so factors of 30 to 40 bits
+/// are found with `b1` of a few thousands and tens of curves.
+///
+/// Returns `None` if no nontrivial factor was found, in particular for primes and `n < 4`.
+pub fn ecm(n: u128, b1: u64, b2: u64, curves: u32) -> Option<u128>
+{
+    if n < 4 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let mont = Montgomery::new(n);
+    let stage_1_primes: Vec<u64> = Sieve::new(b1.max(STAGE_2_STEP.min(b2))).primes().collect();
+    let stage_2_primes = primes_in_range(stage_1_primes.last().map_or(0, |&p| p + 1), b2);
+    for sigma in (6..).take(curves as usize) {
+        if let Some(d) = ecm_curve(&mont, sigma, b1, &stage_1_primes, &stage_2_primes) {
+            return Some(d);
+        }
+    }
+    None
+}
+
+/// Runs both stages on the curve given by `sigma`.
+fn ecm_curve(mont: &Montgomery, sigma: u64, b1: u64, stage_1_primes: &[u64], stage_2_primes: &[u64]) -> Option<u128>
+{
+    let n = mont.modulus();
+    let check = |g: u128| if g > 1 && g < n { Some(g) } else { None };
+    // Suyama: u = σ^2 - 5, v = 4σ, starting point (u^3 : v^3) and (A + 2)/4 = (v - u)^3 (3u + v) / (16 u^3 v)
+    let m = mont;
+    let s = m.encode(sigma as u128);
+    let u = m.sub(m.mul(s, s), m.encode(5));
+    let v = m.add(m.add(s, s), m.add(s, s));
+    let u3 = m.mul(m.mul(u, u), u);
+    let v3 = m.mul(m.mul(v, v), v);
+    let vu = m.sub(v, u);
+    let a24_plus = m.mul(m.mul(m.mul(vu, vu), vu), m.add(m.add(m.add(u, u), u), v));
+    let c24 = m.mul(m.mul(m.encode(16), u3), v);
+    let g = gcd(c24, n);
+    if g > 1 {
+        return check(g);
+    }
+    let curve = Curve { mont, a24_plus, c24 };
+    let mut q = Point { x: u3, z: v3 };
+    for &p in stage_1_primes {
+        let mut power = p;
+        while power <= b1 / p {
+            power *= p;
+        }
+        q = curve.mul(q, power);
+    }
+    let g = gcd(q.z, n);
+    if g > 1 || stage_2_primes.is_empty() {
+        return check(g);
+    }
+    // baby steps j*q for odd j < D/2
+    let half_step = (STAGE_2_STEP / 2) as usize;
+    let mut baby = vec![q; half_step + 1];
+    let q2 = curve.double(q);
+    baby[3] = curve.add(q2, q, q);
+    for j in (5..=half_step).step_by(2) {
+        baby[j] = curve.add(baby[j - 2], q2, baby[j - 4]);
+    }
+    // giant steps (m*D)*q, each prime p = m*D ± j contributes X_giant*Z_j - X_j*Z_giant
+    let giant_step = curve.mul(q, STAGE_2_STEP);
+    let mut m_current = 1;
+    let (mut giant, mut giant_prev) = (giant_step, giant_step);
+    let mut product = mont.one();
+    for &p in stage_2_primes {
+        let m_target = (p + STAGE_2_STEP / 2) / STAGE_2_STEP;
+        while m_current < m_target {
+            let next = if m_current == 1 { curve.double(giant) } else { curve.add(giant, giant_step, giant_prev) };
+            giant_prev = giant;
+            giant = next;
+            m_current += 1;
+        }
+        let j = p.abs_diff(m_target * STAGE_2_STEP) as usize;
+        let b = baby[j];
+        product = mont.mul(product, mont.sub(mont.mul(giant.x, b.z), mont.mul(b.x, giant.z)));
+    }
+    check(gcd(product, n))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn ecm_finds_small_factors() {
+        let p: u128 = 34_359_738_337; // 35-bit prime
+        let q: u128 = 1_237_940_039_285_380_274_899_124_191; // 90-bit prime
+        let d = ecm(p * q, 2000, 200_000, 100).unwrap();
+        assert_eq!(d, p);
+    }
+    #[test]
+    fn ecm_finds_divisors() {
+        for n in (101..3000u128).step_by(2) {
+            if let Some(d) = ecm(n, 50, 500, 3) {
+                assert!(d > 1 && d < n && n.is_multiple_of(d), "{} {}", n, d);
+            }
+        }
+        let d = ecm(2_147_483_647 * 2_147_483_629, 500, 50_000, 50).unwrap();
+        assert!(d == 2_147_483_647 || d == 2_147_483_629);
+    }
+    #[test]
+    fn ecm_no_factor() {
+        assert_eq!(ecm((1 << 127) - 1, 100, 1000, 5), None);
+        assert_eq!(ecm(3, 100, 1000, 5), None);
+        assert_eq!(ecm(1 << 100, 100, 1000, 5), Some(2));
+    }
+    #[test]
+    fn curve_arithmetic_consistent() {
+        let mont = Montgomery::new(1_000_000_007);
+        let m = &mont;
+        let curve = Curve { mont: m, a24_plus: m.encode(12345), c24: m.encode(4) };
+        let p = Point { x: m.encode(2), z: m.one() };
+        let affine = |q: Point| crate::mod_mul(m.decode(q.x) as u64, crate::mod_inverse(m.decode(q.z) as i64, 1_000_000_007).unwrap() as u64, 1_000_000_007);
+        // 3p = 2p + p and 5p = 3p + 2p given the differences
+        let p2 = curve.double(p);
+        let p3 = curve.add(p2, p, p);
+        let p5 = curve.add(p3, p2, p);
+        assert_eq!(affine(curve.mul(p, 2)), affine(p2));
+        assert_eq!(affine(curve.mul(p, 3)), affine(p3));
+        assert_eq!(affine(curve.mul(p, 5)), affine(p5));
+        assert_eq!(affine(curve.mul(p, 6)), affine(curve.double(p3)));
+        assert_eq!(affine(curve.mul(curve.mul(p, 7), 11)), affine(curve.mul(p, 77)));
+    }
+}
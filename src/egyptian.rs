@@ -0,0 +1,138 @@
+use std::convert::TryFrom;
+
+use crate::gcd;
+use crate::residues::inverse;
+
+/// Decomposes `p/q` into a sum of distinct unit fractions and returns their denominators in increasing order.
+///
+/// Uses the greedy algorithm of Fibonacci and Sylvester, which repeatedly subtracts the largest unit fraction
+/// not exceeding the rest. The numerator of the rest strictly decreases, so there are at most `p` terms,
+/// but the denominators can grow doubly exponentially. Go to [egyptian_fractions_golomb] for bounded denominators.
+///
+/// Returns `None` if a denominator does not fit in `u64`. Panics if `q` is zero or `p > q`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(egyptian_fractions(4, 13), Some(vec![4, 18, 468]));
+/// assert_eq!(egyptian_fractions(6, 6), Some(vec![1]));
+/// assert_eq!(egyptian_fractions(5, 121), None);
+/// ```
+pub fn egyptian_fractions(p: u64, q: u64) -> Option<Vec<u64>>
+{
+    assert!(q > 0 && p <= q, "fraction must be in [0, 1]");
+    let (mut p, mut q) = (p as u128, q as u128);
+    let mut res = Vec::new();
+    while p > 0 {
+        let d = q.div_ceil(p);
+        res.push(u64::try_from(d).ok()?);
+        // p/q - 1/d = (p*d - q) / (q*d)
+        p = p * d - q;
+        q = q.checked_mul(d)?;
+        let g = gcd(p, q);
+        p /= g;
+        q /= g;
+    }
+    Some(res)
+}
+
+/// Decomposes `p/q` into a sum of distinct unit fractions and returns their denominators in increasing order.
+///
+/// Uses Golomb's method: for reduced `p/q` the predecessor `p'/q'` in the Farey sequence of order `q` satisfies
+/// `p/q - p'/q' = 1/(q*q')` with `p' < p` and `q' < q`, and `q'` is the inverse of `p` modulo `q`.
+/// Repeating the step gives at most `p` terms, all denominators at most `q(q - 1)`.
+///
+/// Returns `None` if a denominator does not fit in `u64`, which can only happen for `q > 2^32`.
+/// Panics if `q` is zero or `p > q`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(egyptian_fractions_golomb(4, 13), Some(vec![4, 28, 70, 130]));
+/// assert_eq!(egyptian_fractions_golomb(5, 121), Some(vec![25, 1225, 3577, 7081, 11737]));
+/// ```
+pub fn egyptian_fractions_golomb(p: u64, q: u64) -> Option<Vec<u64>>
+{
+    assert!(q > 0 && p <= q, "fraction must be in [0, 1]");
+    let g = gcd(p, q);
+    let (mut p, mut q) = (p / g, q / g);
+    let mut res = Vec::new();
+    while p > 1 {
+        // p*q' - p'*q = 1
+        let q_prev = inverse(p, q);
+        let p_prev = ((p as u128 * q_prev as u128 - 1) / q as u128) as u64;
+        res.push(q.checked_mul(q_prev)?);
+        p = p_prev;
+        q = q_prev;
+    }
+    if p == 1 {
+        res.push(q);
+    }
+    res.reverse();
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn test_decomposition(p: u64, q: u64, denominators: &[u64]) {
+        assert!(denominators.windows(2).all(|w| w[0] < w[1]), "{} {} {:?}", p, q, denominators);
+        // subtract the unit fractions one by one, skipping the check if the exact rest overflows
+        let (mut p, mut q) = (p as u128, q as u128);
+        for &d in denominators {
+            let (lhs, rhs) = match (p.checked_mul(d as u128), q.checked_mul(d as u128)) {
+                (Some(lhs), Some(rhs)) => (lhs, rhs),
+                _ => return
+            };
+            assert!(lhs >= q, "{:?}", denominators);
+            p = lhs - q;
+            q = rhs;
+            let g = crate::gcd(p, q);
+            p /= g;
+            q /= g;
+        }
+        assert_eq!(p, 0, "{:?}", denominators);
+    }
+
+    #[test]
+    fn egyptian_fractions_small() {
+        for q in 1..60 {
+            for p in 0..=q {
+                if let Some(res) = egyptian_fractions(p, q) {
+                    assert!(res.len() as u64 <= p);
+                    test_decomposition(p, q, &res);
+                }
+                let res = egyptian_fractions_golomb(p, q).unwrap();
+                assert!(res.len() as u64 <= p && res.iter().all(|&d| d <= (q * (q - 1)).max(q)), "{} {} {:?}", p, q, res);
+                test_decomposition(p, q, &res);
+            }
+        }
+        assert_eq!(egyptian_fractions(0, 5), Some(vec![]));
+        assert_eq!(egyptian_fractions(2, 3), Some(vec![2, 6]));
+        assert_eq!(egyptian_fractions(3, 7), Some(vec![3, 11, 231]));
+        assert_eq!(egyptian_fractions_golomb(0, 5), Some(vec![]));
+        assert_eq!(egyptian_fractions_golomb(7, 7), Some(vec![1]));
+    }
+    #[test]
+    fn egyptian_fractions_large() {
+        assert_eq!(egyptian_fractions(1, u64::MAX), Some(vec![u64::MAX]));
+        assert_eq!(egyptian_fractions(u64::MAX - 1, u64::MAX), None);
+        let q = 4_294_967_291; // largest prime below 2^32
+        let res = egyptian_fractions_golomb(1_000_000, q).unwrap();
+        assert!(res.len() <= 1_000_000 && res.iter().all(|&d| d <= q * (q - 1)));
+        test_decomposition(1_000_000, q, &res);
+        assert_eq!(egyptian_fractions_golomb(3, u64::MAX), Some(vec![u64::MAX / 3]));
+        assert_eq!(egyptian_fractions_golomb(2, u64::MAX), None);
+    }
+    #[test]
+    #[should_panic]
+    fn egyptian_fractions_improper() {
+        egyptian_fractions(3, 2);
+    }
+}
@@ -0,0 +1,929 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::convert::TryFrom;
+
+use crate::{ecm, gcd, is_prime, is_probable_prime, lcm, mod_mul, mod_pow, primes, primes_in_range, siqs, Sieve, Wheel};
+
+/// Prime factorization of a positive integer as `(prime, exponent)` pairs sorted by prime.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Factorization
+{
+    factors: Vec<(u64, u32)>
+}
+
+impl Factorization {
+    /// The `(prime, exponent)` pairs sorted by prime. Empty for the factorization of 1.
+    pub fn factors(&self) -> &[(u64, u32)] {
+        &self.factors
+    }
+
+    /// Computes the factorized number.
+    pub fn value(&self) -> u64 {
+        self.factors.iter().map(|&(p, k)| p.pow(k)).product()
+    }
+
+    /// Computes the number of divisors `d(n)`.
+    pub fn divisor_count(&self) -> u64 {
+        self.factors.iter().map(|&(_, k)| k as u64 + 1).product()
+    }
+
+    /// Computes the sum of divisors `σ(n)`.
+    pub fn divisor_sum(&self) -> u128 {
+        self.factors.iter().map(|&(p, k)| {
+            let p = p as u128;
+            (0..k).fold(1, |acc, _| acc * p + 1)
+        }).product()
+    }
+
+    /// Computes the divisor function `σ_k(n)`, the sum of `k`-th powers of all divisors.
+    ///
+    /// Returns `None` if the result does not fit in `u128`.
+    pub fn sigma(&self, k: u32) -> Option<u128> {
+        self.factors.iter().try_fold(1u128, |acc, &(p, e)| {
+            let pk = (p as u128).checked_pow(k)?;
+            let term = (0..e).try_fold(1u128, |t, _| t.checked_mul(pk)?.checked_add(1))?;
+            acc.checked_mul(term)
+        })
+    }
+
+    /// Lists all divisors in increasing order.
+    pub fn divisors(&self) -> Vec<u64> {
+        let mut res = vec![1];
+        for &(p, k) in &self.factors {
+            let len = res.len();
+            let mut power = 1;
+            for _ in 0..k {
+                power *= p;
+                for i in 0..len {
+                    res.push(res[i] * power);
+                }
+            }
+        }
+        res.sort_unstable();
+        res
+    }
+
+    /// Computes Euler's totient `φ(n)`, the number of integers in `[1, n]` coprime with `n`.
+    pub fn totient(&self) -> u64 {
+        self.factors.iter().map(|&(p, k)| (p - 1) * p.pow(k - 1)).product()
+    }
+
+    /// Computes the Carmichael function `λ(n)`, the exponent of the multiplicative group modulo `n`.
+    pub fn carmichael_lambda(&self) -> u64 {
+        self.factors.iter().map(|&(p, k)| {
+            if p == 2 && k >= 3 { 1 << (k - 2) } else { (p - 1) * p.pow(k - 1) }
+        }).fold(1, lcm)
+    }
+
+    /// Computes the number of prime factors counted with multiplicity `Ω(n)`.
+    pub fn big_omega(&self) -> u32 {
+        self.factors.iter().map(|&(_, k)| k).sum()
+    }
+
+    /// Computes the number of distinct prime factors `ω(n)`.
+    pub fn little_omega(&self) -> u32 {
+        self.factors.len() as u32
+    }
+
+    /// Computes the Liouville function `λ(n) = (-1)^Ω(n)`.
+    pub fn liouville(&self) -> i8 {
+        if self.big_omega().is_multiple_of(2) { 1 } else { -1 }
+    }
+
+    /// Computes the Möbius function `μ(n)`, which is `(-1)^r` for squarefree `n` with `r` prime factors, otherwise `0`.
+    pub fn mobius(&self) -> i8 {
+        if self.factors.iter().any(|&(_, k)| k > 1) {
+            0
+        } else if self.factors.len().is_multiple_of(2) {
+            1
+        } else {
+            -1
+        }
+    }
+
+    /// Returns true if no prime factor appears more than once. Otherwise returns false.
+    pub fn is_squarefree(&self) -> bool {
+        self.factors.iter().all(|&(_, k)| k == 1)
+    }
+
+    /// Computes the radical `rad(n)`, the product of distinct prime factors.
+    pub fn radical(&self) -> u64 {
+        self.factors.iter().map(|&(p, _)| p).product()
+    }
+}
+
+/// Computes the prime factorization of `n`.
+///
+/// Small factors are removed by trial division by the [Wheel] candidates,
+/// the rest is split by Brent's variant of Pollard's rho with [is_prime] deciding when to stop,
+/// so even 63-bit semiprimes are factorized in milliseconds.
+///
+/// Panics if `n` is zero.
+pub fn factorize(n: u64) -> Factorization
+{
+    assert!(n != 0, "cannot factorize zero");
+    let (mut factors, rest) = trial_division(n, TRIAL_DIVISION_BOUND);
+    let mut primes = Vec::new();
+    split_factor(rest, &mut primes);
+    primes.sort_unstable();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, k)) if *q == p => *k += 1,
+            _ => factors.push((p, 1))
+        }
+    }
+    Factorization { factors }
+}
+
+/// Trial division is used for factors below this bound.
+const TRIAL_DIVISION_BOUND: u64 = 1 << 10;
+
+/// Removes all prime factors less than `bound` from `n` by trial division by the [Wheel] candidates.
+/// Returns the found factors and the remaining cofactor.
+fn trial_division(mut n: u64, bound: u64) -> (Vec<(u64, u32)>, u64)
+{
+    let mut factors = Vec::new();
+    for p in Wheel::new().take_while(|&p| p < bound) {
+        if p > n / p {
+            break;
+        }
+        let mut k = 0;
+        while n.is_multiple_of(p) {
+            n /= p;
+            k += 1;
+        }
+        if k > 0 {
+            factors.push((p, k));
+        }
+    }
+    if n > 1 && n < bound.saturating_mul(bound) {
+        factors.push((n, 1));
+        n = 1;
+    }
+    (factors, n)
+}
+
+/// Pushes all prime factors of `n` with multiplicity to `primes`.
+fn split_factor(n: u64, primes: &mut Vec<u64>)
+{
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        primes.push(n);
+        return;
+    }
+    let d = (1..).find_map(|c| pollard_rho_brent(n, c)).unwrap();
+    split_factor(d, primes);
+    split_factor(n / d, primes);
+}
+
+/// Tries to find a nontrivial factor of composite `n` by Brent's variant of Pollard's rho
+/// with the polynomial `x^2 + c`. Returns `None` if the cycle is found without a factor.
+fn pollard_rho_brent(n: u64, c: u64) -> Option<u64>
+{
+    const BATCH: u64 = 128;
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let f = |x: u64| ((x as u128 * x as u128 + c as u128) % n as u128) as u64;
+    let (mut y, mut r, mut q, mut g) = (2 % n, 1u64, 1u64, 1u64);
+    let (mut x, mut ys) = (y, y);
+    while g == 1 {
+        x = y;
+        for _ in 0..r {
+            y = f(y);
+        }
+        let mut k = 0;
+        while k < r && g == 1 {
+            ys = y;
+            for _ in 0..BATCH.min(r - k) {
+                y = f(y);
+                q = mod_mul(q, x.abs_diff(y), n);
+            }
+            g = gcd(q, n);
+            k += BATCH;
+        }
+        r *= 2;
+    }
+    if g == n {
+        // the batch overshot, retrace it one step at a time
+        loop {
+            ys = f(ys);
+            g = gcd(x.abs_diff(ys), n);
+            if g > 1 {
+                break;
+            }
+        }
+    }
+    if g == n { None } else { Some(g) }
+}
+
+/// Computes the number of divisors `d(n)` of `n`.
+///
+/// Panics if `n` is zero.
+pub fn divisor_count(n: u64) -> u64
+{
+    factorize(n).divisor_count()
+}
+
+/// Computes the sum of divisors `σ(n)` of `n`. The result always fits in `u128`.
+///
+/// Panics if `n` is zero.
+pub fn divisor_sum(n: u64) -> u128
+{
+    factorize(n).divisor_sum()
+}
+
+/// Computes the divisor function `σ_k(n)`, the sum of `k`-th powers of all divisors of `n`,
+/// from the factorization `n = p1^e1 * ... * pr^er` as the product of `1 + pi^k + ... + pi^(ei*k)`.
+///
+/// `σ_0` is the number of divisors and `σ_1` the sum of divisors.
+/// Returns `None` if the result does not fit in `u128`, use `bigint::sigma` for exact values.
+/// Panics if `n` is zero.
+pub fn sigma(k: u32, n: u64) -> Option<u128>
+{
+    factorize(n).sigma(k)
+}
+
+/// Computes Euler's totient `φ(n)`, the number of integers in `[1, n]` coprime with `n`.
+///
+/// By Euler's theorem `a^φ(n) ≡ 1 (mod n)` for all `a` coprime with `n`, so exponents modulo `n`
+/// can be reduced modulo `φ(n)`. Computed as `n * (1 - 1/p1) * ... * (1 - 1/pr)` from the factorization.
+///
+/// Panics if `n` is zero.
+pub fn totient(n: u64) -> u64
+{
+    factorize(n).totient()
+}
+
+/// Computes the Carmichael function `λ(n)`, the smallest `m > 0` with `a^m ≡ 1 (mod n)` for all `a` coprime with `n`.
+///
+/// `λ(n)` divides [totient] `φ(n)` and is often much smaller, e.g. `λ(2^k) = 2^(k-2)` for `k >= 3` and
+/// `λ(n)` is the least common multiple of `λ(p^k)` over the prime powers of `n`,
+/// so it is the right exponent for order arguments. The multiplicative group modulo `n` is cyclic iff `λ(n) = φ(n)`.
+///
+/// Panics if `n` is zero.
+pub fn carmichael_lambda(n: u64) -> u64
+{
+    factorize(n).carmichael_lambda()
+}
+
+/// Computes `Ω(n)`, the number of prime factors of `n` counted with multiplicity, e.g. `Ω(12) = 3`.
+///
+/// Panics if `n` is zero.
+pub fn big_omega(n: u64) -> u32
+{
+    factorize(n).big_omega()
+}
+
+/// Computes `ω(n)`, the number of distinct prime factors of `n`, e.g. `ω(12) = 2`.
+///
+/// Panics if `n` is zero.
+pub fn little_omega(n: u64) -> u32
+{
+    factorize(n).little_omega()
+}
+
+/// Computes the Liouville function `λ(n) = (-1)^Ω(n)`, a completely multiplicative function
+/// which agrees with [mobius] on squarefree numbers.
+///
+/// Panics if `n` is zero.
+pub fn liouville(n: u64) -> i8
+{
+    factorize(n).liouville()
+}
+
+/// Computes the Möbius function `μ(n)`, which is `(-1)^r` if `n` is a product of `r` distinct primes
+/// and `0` if `n` is divisible by a square greater than 1.
+///
+/// The sum of `μ(d)` over the divisors `d` of `n` is `1` for `n = 1` and `0` otherwise, which is the basis
+/// of Möbius inversion and of inclusion–exclusion over squarefree divisors.
+///
+/// Panics if `n` is zero.
+pub fn mobius(n: u64) -> i8
+{
+    factorize(n).mobius()
+}
+
+/// Returns true if `n` is not divisible by any square greater than 1. Otherwise returns false.
+///
+/// Returns false as soon as trial division finds a repeated small prime factor or the cofactor is a perfect square,
+/// and a cofactor below `2^30` without small prime factors has at most two prime factors, so it is decided without
+/// further factorization. Only the remaining cases are split by Pollard's rho.
+///
+/// Panics if `n` is zero.
+pub fn is_squarefree(n: u64) -> bool
+{
+    assert!(n != 0, "cannot factorize zero");
+    let mut n = n;
+    for p in Wheel::new().take_while(|&p| p < TRIAL_DIVISION_BOUND) {
+        if p > n / p {
+            return true;
+        }
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return false;
+            }
+        }
+    }
+    let root = n.isqrt();
+    if root * root == n {
+        return n == 1;
+    }
+    if n < TRIAL_DIVISION_BOUND.pow(3) {
+        return true;
+    }
+    let mut primes = Vec::new();
+    split_factor(n, &mut primes);
+    primes.sort_unstable();
+    primes.windows(2).all(|w| w[0] != w[1])
+}
+
+/// Computes the radical `rad(n)`, the product of distinct prime factors of `n`, with `rad(1) = 1`.
+///
+/// Small prime factors are removed by trial division and a prime cofactor is recognized by [is_prime],
+/// only composite cofactors are split by Pollard's rho.
+///
+/// Panics if `n` is zero.
+pub fn radical(n: u64) -> u64
+{
+    assert!(n != 0, "cannot factorize zero");
+    let (factors, rest) = trial_division(n, TRIAL_DIVISION_BOUND);
+    let small: u64 = factors.iter().map(|&(p, _)| p).product();
+    let mut primes = Vec::new();
+    split_factor(rest, &mut primes);
+    primes.sort_unstable();
+    primes.dedup();
+    small * primes.iter().product::<u64>()
+}
+
+/// Returns an iterator through all divisors of `n` in increasing order.
+///
+/// The divisors are generated lazily from the factorization of `n` using a binary heap,
+/// so taking the smallest few divisors does not enumerate all of them.
+///
+/// Panics if `n` is zero.
+pub fn divisors(n: u64) -> Divisors
+{
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((1, 0, 0)));
+    Divisors { factors: factorize(n).factors, heap }
+}
+
+/// Iterator through the divisors of a number in increasing order, returned by [divisors].
+#[derive(Debug, Clone)]
+pub struct Divisors
+{
+    factors: Vec<(u64, u32)>,
+    // (divisor, index of its largest prime factor, exponent of that prime)
+    heap: BinaryHeap<Reverse<(u64, usize, u32)>>
+}
+
+impl Iterator for Divisors {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        let Reverse((d, last, exponent)) = self.heap.pop()?;
+        // every divisor is reached exactly once by multiplying its primes in nondecreasing order
+        for (i, &(p, k)) in self.factors.iter().enumerate().skip(last) {
+            let used = if i == last && d > 1 { exponent } else { 0 };
+            if used < k {
+                self.heap.push(Reverse((d * p, i, used + 1)));
+            }
+        }
+        Some(d)
+    }
+}
+
+/// Returns an iterator through the pairs `(d, n/d)` of complementary divisors of `n` with `d <= n/d`,
+/// in increasing order of `d`.
+///
+/// Panics if `n` is zero.
+pub fn divisor_pairs(n: u64) -> DivisorPairs
+{
+    DivisorPairs { n, divisors: divisors(n) }
+}
+
+/// Iterator through the pairs of complementary divisors, returned by [divisor_pairs].
+#[derive(Debug, Clone)]
+pub struct DivisorPairs
+{
+    n: u64,
+    divisors: Divisors
+}
+
+impl Iterator for DivisorPairs {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<(u64, u64)> {
+        let d = self.divisors.next()?;
+        if d > self.n / d {
+            self.divisors.heap.clear();
+            return None;
+        }
+        Some((d, self.n / d))
+    }
+}
+
+/// Computes the prime factorizations of all numbers in the inclusive range `[lo, hi]`.
+///
+/// The primes up to `sqrt(hi)` are generated by the segmented sieve of [primes] in memory independent of `hi`,
+/// and each of them divides only its multiples in the window,
+/// so the whole range is factorized in about `O((hi - lo) log log hi + sqrt(hi))` steps
+/// instead of factorizing every number separately. For windows narrower than `sqrt(hi) / 2^16`,
+/// the primes stop at `2^16 (hi - lo + 1)` and the composite cofactors left without smaller prime factors
+/// are factorized by [factorize], so narrow windows of huge numbers stay cheap.
+/// Returns an empty vector if `lo > hi`.
+///
+/// Panics if the range contains zero.
+pub fn factorize_range(lo: u64, hi: u64) -> Vec<Factorization>
+{
+    if lo > hi {
+        return Vec::new();
+    }
+    assert!(lo > 0, "cannot factorize zero");
+    let mut rest: Vec<u64> = (lo..=hi).collect();
+    let mut res = vec![Factorization::default(); rest.len()];
+    let bound = hi.isqrt().min((hi - lo + 1).saturating_mul(RANGE_SIEVE_RATIO));
+    for p in primes().take_while(|&p| p <= bound) {
+        let first = match lo.div_ceil(p).checked_mul(p) {
+            Some(first) if first <= hi => first,
+            _ => continue
+        };
+        for m in (first..=hi).step_by(p as usize) {
+            let i = (m - lo) as usize;
+            let mut k = 0;
+            while rest[i].is_multiple_of(p) {
+                rest[i] /= p;
+                k += 1;
+            }
+            res[i].factors.push((p, k));
+        }
+    }
+    // what remains has no prime factor up to the bound, so it is 1, a prime below (bound + 1)^2
+    // or a product of primes above the bound
+    for (f, &r) in res.iter_mut().zip(&rest) {
+        if r / (bound + 1) <= bound {
+            if r > 1 {
+                f.factors.push((r, 1));
+            }
+        } else {
+            f.factors.extend_from_slice(factorize(r).factors());
+        }
+    }
+    res
+}
+
+/// Ratio of the largest sieving prime to the window width in [factorize_range], above which
+/// factorizing the leftover cofactors one by one is cheaper than generating more primes.
+const RANGE_SIEVE_RATIO: u64 = 1 << 16;
+
+/// Computes the prime factorization of `n` as `(prime, exponent)` pairs sorted by prime.
+///
+/// Numbers fitting in `u64` are factorized by [factorize]. Larger ones are split by trial division,
+/// [is_probable_prime], a short run of [ecm] for small factors and [siqs] for the rest.
+/// Numbers of 126 bits or more are out of reach of [siqs], so [ecm] continues with growing bounds,
+/// which can take seconds when there are two prime factors around 64 bits each.
+///
+/// Panics if `n` is zero.
+pub fn factorize_u128(n: u128) -> Vec<(u128, u32)>
+{
+    assert!(n != 0, "cannot factorize zero");
+    let (n, mut primes) = match u64::try_from(n) {
+        Ok(n) => return factorize(n).factors().iter().map(|&(p, k)| (p as u128, k)).collect(),
+        Err(_) => trial_division_u128(n)
+    };
+    split_factor_u128(n, &mut primes);
+    primes.sort_unstable();
+    let mut factors: Vec<(u128, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, k)) if *q == p => *k += 1,
+            _ => factors.push((p, 1))
+        }
+    }
+    factors
+}
+
+/// Bounds and number of curves of the successive [ecm] runs used by [factorize_u128].
+const ECM_SCHEDULE: [(u64, u32); 4] = [(2000, 25), (11_000, 90), (50_000, 300), (250_000, 700)];
+
+/// Removes all prime factors less than [TRIAL_DIVISION_BOUND] from `n`.
+/// Returns the remaining cofactor and the found primes with multiplicity.
+fn trial_division_u128(mut n: u128) -> (u128, Vec<u128>)
+{
+    let mut primes = Vec::new();
+    for p in Wheel::new().take_while(|&p| p < TRIAL_DIVISION_BOUND) {
+        while n.is_multiple_of(p as u128) {
+            n /= p as u128;
+            primes.push(p as u128);
+        }
+    }
+    (n, primes)
+}
+
+/// Pushes all prime factors of `n` with multiplicity to `primes`.
+fn split_factor_u128(n: u128, primes: &mut Vec<u128>)
+{
+    if let Ok(n) = u64::try_from(n) {
+        for &(p, k) in factorize(n).factors() {
+            primes.extend(std::iter::repeat_n(p as u128, k as usize));
+        }
+        return;
+    }
+    if is_probable_prime(n) {
+        primes.push(n);
+        return;
+    }
+    let root = n.isqrt();
+    let d = if root * root == n {
+        root
+    } else if let Some(d) = ecm(n, ECM_SCHEDULE[0].0, 100 * ECM_SCHEDULE[0].0, ECM_SCHEDULE[0].1) {
+        d
+    } else if let Some(d) = siqs(n) {
+        d
+    } else {
+        let mut schedule = ECM_SCHEDULE.iter().copied().skip(1).chain((1..).map(|i| (250_000 << (2 * i), 1000)));
+        schedule.find_map(|(b1, curves)| ecm(n, b1, 100 * b1, curves)).unwrap()
+    };
+    split_factor_u128(d, primes);
+    split_factor_u128(n / d, primes);
+}
+
+/// Tries to find a nontrivial factor of `n` by Pollard's `p - 1` method.
+///
+/// Finds a prime factor `p` of `n` if `p - 1` is a product of prime powers not exceeding `b1`
+/// (stage 1) and at most one additional prime in `(b1, b2]` (stage 2). Stage 2 is skipped for `b2 <= b1`.
+/// Succeeds quickly for such factors even when they are too large for Pollard's rho.
+///
+/// Returns `None` if no nontrivial factor was found, in particular for primes and `n < 4`.
+pub fn pollard_p_minus_1(n: u64, b1: u64, b2: u64) -> Option<u64>
+{
+    if n < 4 {
+        return None;
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let check = |g: u64| if g > 1 && g < n { Some(g) } else { None };
+    let mut a = 2;
+    for q in Sieve::new(b1).primes() {
+        let mut power = q;
+        while power <= b1 / q {
+            power *= q;
+        }
+        a = mod_pow(a, power, n);
+    }
+    // a is a power of 2 modulo odd n, so it is never zero
+    let g = gcd(a - 1, n);
+    if g > 1 || b2 <= b1 {
+        return check(g);
+    }
+    // stage 2 walks the primes in (b1, b2] using precomputed a^gap for the gaps between them
+    let primes = primes_in_range(b1 + 1, b2);
+    if primes.is_empty() {
+        return None;
+    }
+    let mut gap_powers = vec![1];
+    let mut x = mod_pow(a, primes[0], n);
+    let mut product = x - 1;
+    for (i, w) in primes.windows(2).enumerate() {
+        let gap = (w[1] - w[0]) as usize;
+        while gap_powers.len() <= gap {
+            let next = mod_mul(gap_powers[gap_powers.len() - 1], a, n);
+            gap_powers.push(next);
+        }
+        x = mod_mul(x, gap_powers[gap], n);
+        product = mod_mul(product, x - 1, n);
+        if i % 128 == 127 {
+            let g = gcd(product, n);
+            if g > 1 {
+                return check(g);
+            }
+        }
+    }
+    check(gcd(product, n))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn factorize_brute_force() {
+        for n in 1..5000u64 {
+            let f = factorize(n);
+            assert_eq!(f.value(), n);
+            assert!(f.factors().iter().all(|&(p, k)| is_prime(p) && k > 0));
+            assert!(f.factors().windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+    #[test]
+    fn factorize_values() {
+        assert_eq!(factorize(1).factors(), &[]);
+        assert_eq!(factorize(360).factors(), &[(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(1 << 63).factors(), &[(2, 63)]);
+        assert_eq!(factorize(u64::MAX).factors(), &[(3, 1), (5, 1), (17, 1), (257, 1), (641, 1), (65537, 1), (6_700_417, 1)]);
+        assert_eq!(factorize(1_000_000_007).factors(), &[(1_000_000_007, 1)]);
+        assert_eq!(factorize(999_983 * 999_983 * 2).factors(), &[(2, 1), (999_983, 2)]);
+    }
+    #[test]
+    #[should_panic]
+    fn factorize_zero() {
+        factorize(0);
+    }
+    #[test]
+    fn factorization_divisors() {
+        for n in 1..2000u64 {
+            let f = factorize(n);
+            let expected: Vec<u64> = (1..=n).filter(|&d| n.is_multiple_of(d)).collect();
+            assert_eq!(f.divisor_count(), expected.len() as u64);
+            assert_eq!(f.divisor_sum(), expected.iter().map(|&d| d as u128).sum::<u128>());
+            assert_eq!(f.divisors(), expected);
+        }
+    }
+    #[test]
+    fn factorization_totient_radical() {
+        for n in 1..2000u64 {
+            let f = factorize(n);
+            assert_eq!(f.totient(), (1..=n).filter(|&k| gcd(k, n) == 1).count() as u64);
+            assert_eq!(f.radical(), (1..=n).filter(|&p| n.is_multiple_of(p) && is_prime(p)).product::<u64>());
+        }
+    }
+    #[test]
+    fn factorization_large_values() {
+        let f = factorize(963_761_198_400); // highly composite number with 6720 divisors
+        assert_eq!(f.divisor_count(), 6720);
+        assert_eq!(f.divisors().len(), 6720);
+        let f = factorize(u64::MAX);
+        assert_eq!(f.totient(), 9_208_981_628_670_443_520);
+        assert_eq!(f.radical(), u64::MAX);
+        assert_eq!(f.divisor_sum(), 4 * 6 * 18 * 258 * 642 * 65538 * 6_700_418);
+    }
+
+    #[test]
+    fn factorize_semiprimes() {
+        assert_eq!(factorize(4_294_967_291 * 4_294_967_279).factors(), &[(4_294_967_279, 1), (4_294_967_291, 1)]);
+        assert_eq!(factorize(2_147_483_647 * 4_294_967_291).factors(), &[(2_147_483_647, 1), (4_294_967_291, 1)]);
+        assert_eq!(factorize(4_294_967_291 * 4_294_967_291).factors(), &[(4_294_967_291, 2)]);
+        assert_eq!(factorize(1_000_003 * 1_000_033 * 1_000_037).factors(), &[(1_000_003, 1), (1_000_033, 1), (1_000_037, 1)]);
+        assert_eq!(factorize(18_446_744_073_709_551_557).factors(), &[(18_446_744_073_709_551_557, 1)]);
+    }
+    #[test]
+    fn factorize_large_brute_force() {
+        for n in (u64::MAX - 2000..=u64::MAX).chain(1_000_000_000_000..1_000_000_002_000) {
+            let f = factorize(n);
+            assert_eq!(f.value(), n);
+            assert!(f.factors().iter().all(|&(p, k)| is_prime(p) && k > 0));
+            assert!(f.factors().windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+    #[test]
+    fn factorize_prime_powers() {
+        assert_eq!(factorize(1_000_003u64.pow(3)).factors(), &[(1_000_003, 3)]);
+        assert_eq!(factorize(1021u64.pow(6)).factors(), &[(1021, 6)]);
+        assert_eq!(factorize(3u64.pow(40)).factors(), &[(3, 40)]);
+    }
+
+    #[test]
+    fn pollard_p_minus_1_stage_1() {
+        // 892371481 - 1 = 2^3 * 3 * 5 * 7 * 11 * 13 * 17 * 19 * 23
+        let n = 892_371_481 * 4_294_967_291;
+        assert_eq!(pollard_p_minus_1(n, 100, 0), Some(892_371_481));
+        assert_eq!(pollard_p_minus_1(n, 23, 0), Some(892_371_481));
+        assert_eq!(pollard_p_minus_1(n, 20, 0), None);
+    }
+    #[test]
+    fn pollard_p_minus_1_stage_2() {
+        // 30960931 - 1 = 2 * 3 * 5 * 7 * 11 * 13 * 1031
+        let n = 30_960_931 * 4_294_967_291;
+        assert_eq!(pollard_p_minus_1(n, 100, 1000), None);
+        assert_eq!(pollard_p_minus_1(n, 100, 1031), Some(30_960_931));
+        assert_eq!(pollard_p_minus_1(n, 100, 5000), Some(30_960_931));
+        assert_eq!(pollard_p_minus_1(892_371_481 * 4_294_967_291, 20, 30), Some(892_371_481));
+    }
+    #[test]
+    fn pollard_p_minus_1_no_factor() {
+        assert_eq!(pollard_p_minus_1(1_000_000_007, 1000, 10_000), None);
+        assert_eq!(pollard_p_minus_1(3, 10, 100), None);
+        assert_eq!(pollard_p_minus_1(1, 10, 100), None);
+        assert_eq!(pollard_p_minus_1(1 << 40, 10, 100), Some(2));
+    }
+    #[test]
+    fn pollard_p_minus_1_finds_divisors() {
+        for n in (5..3000u64).step_by(2) {
+            if let Some(d) = pollard_p_minus_1(n, 30, 300) {
+                assert!(d > 1 && d < n && n.is_multiple_of(d), "{} {}", n, d);
+            }
+        }
+    }
+
+    fn test_factorize_u128(n: u128, expected: &[(u128, u32)]) {
+        let factors = factorize_u128(n);
+        assert_eq!(factors, expected);
+        assert_eq!(factors.iter().map(|&(p, k)| p.pow(k)).product::<u128>(), n);
+    }
+
+    #[test]
+    fn factorize_u128_small() {
+        test_factorize_u128(1, &[]);
+        test_factorize_u128(360, &[(2, 3), (3, 2), (5, 1)]);
+        test_factorize_u128(u64::MAX as u128, &[(3, 1), (5, 1), (17, 1), (257, 1), (641, 1), (65537, 1), (6_700_417, 1)]);
+    }
+    #[test]
+    fn factorize_u128_values() {
+        test_factorize_u128(u128::MAX, &[(3, 1), (5, 1), (17, 1), (257, 1), (641, 1), (65537, 1), (274_177, 1), (6_700_417, 1),
+            (67_280_421_310_721, 1)]);
+        test_factorize_u128(1 << 127, &[(2, 127)]);
+        test_factorize_u128((1 << 127) - 1, &[((1 << 127) - 1, 1)]);
+        test_factorize_u128(18_446_744_073_709_551_557 * 18_446_744_073_709_551_557, &[(18_446_744_073_709_551_557, 2)]);
+    }
+    #[test]
+    fn factorize_u128_ecm() {
+        test_factorize_u128(8_589_934_583 * 1_099_511_627_689 * 1_125_899_906_842_597,
+            &[(8_589_934_583, 1), (1_099_511_627_689, 1), (1_125_899_906_842_597, 1)]);
+        test_factorize_u128(34_359_738_337 * 1_237_940_039_285_380_274_899_124_191,
+            &[(34_359_738_337, 1), (1_237_940_039_285_380_274_899_124_191, 1)]);
+    }
+    #[test]
+    fn factorize_u128_siqs() {
+        test_factorize_u128(1_125_899_906_842_597 * 985_162_418_487_253, &[(985_162_418_487_253, 1), (1_125_899_906_842_597, 1)]);
+        test_factorize_u128(9 * 35_184_372_088_777 * 30_786_325_577_723, &[(3, 2), (30_786_325_577_723, 1), (35_184_372_088_777, 1)]);
+    }
+
+    #[test]
+    fn divisors_brute_force() {
+        for n in 1..3000u64 {
+            let expected: Vec<u64> = (1..=n).filter(|&d| n.is_multiple_of(d)).collect();
+            assert_eq!(divisors(n).collect::<Vec<_>>(), expected);
+        }
+    }
+    #[test]
+    fn divisors_lazy() {
+        assert_eq!(divisors(963_761_198_400).take(10).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(divisors(963_761_198_400).count(), 6720);
+        assert_eq!(divisors(u64::MAX).last(), Some(u64::MAX));
+        assert!(divisors(963_761_198_400).eq(factorize(963_761_198_400).divisors()));
+    }
+    #[test]
+    fn divisor_pairs_values() {
+        assert_eq!(divisor_pairs(36).collect::<Vec<_>>(), vec![(1, 36), (2, 18), (3, 12), (4, 9), (6, 6)]);
+        assert_eq!(divisor_pairs(1).collect::<Vec<_>>(), vec![(1, 1)]);
+        assert_eq!(divisor_pairs(1_000_000_007).collect::<Vec<_>>(), vec![(1, 1_000_000_007)]);
+        for n in 1..1000u64 {
+            let pairs: Vec<(u64, u64)> = divisor_pairs(n).collect();
+            assert_eq!(pairs.len() as u64, factorize(n).divisor_count().div_ceil(2));
+            assert!(pairs.iter().all(|&(d, e)| d <= e && d * e == n));
+        }
+    }
+
+    #[test]
+    fn sigma_brute_force() {
+        for n in 1..500u64 {
+            let divs: Vec<u128> = (1..=n as u128).filter(|&d| (n as u128).is_multiple_of(d)).collect();
+            assert_eq!(divisor_count(n), divs.len() as u64);
+            assert_eq!(divisor_sum(n), divs.iter().sum::<u128>());
+            for k in 0..5 {
+                assert_eq!(sigma(k, n), Some(divs.iter().map(|d| d.pow(k)).sum::<u128>()), "{} {}", k, n);
+            }
+        }
+    }
+    #[test]
+    fn sigma_overflow() {
+        assert_eq!(sigma(1, u64::MAX), Some(divisor_sum(u64::MAX)));
+        assert_eq!(sigma(2, 1 << 63), Some(u128::MAX / 3));
+        assert_eq!(sigma(3, 1 << 62), None);
+        assert_eq!(sigma(100, 1), Some(1));
+        assert_eq!(sigma(100, 2), Some((1 << 100) + 1));
+        assert_eq!(sigma(128, 2), None);
+        assert_eq!(divisor_count(963_761_198_400), 6720);
+        assert_eq!(divisor_sum(1_000_000_007), 1_000_000_008);
+    }
+
+    #[test]
+    fn factorize_range_brute_force() {
+        for lo in 1..40 {
+            for hi in lo - 1..60 {
+                let expected: Vec<Factorization> = (lo..=hi).map(factorize).collect();
+                assert_eq!(factorize_range(lo, hi), expected, "{} {}", lo, hi);
+            }
+        }
+        let expected: Vec<Factorization> = (1..=20_000).map(factorize).collect();
+        assert_eq!(factorize_range(1, 20_000), expected);
+    }
+    #[test]
+    fn factorize_range_large() {
+        let lo = 1_000_000_000_000;
+        let expected: Vec<Factorization> = (lo..=lo + 1000).map(factorize).collect();
+        assert_eq!(factorize_range(lo, lo + 1000), expected);
+        let expected: Vec<Factorization> = (u64::MAX - 100..=u64::MAX).map(factorize).collect();
+        assert_eq!(factorize_range(u64::MAX - 100, u64::MAX), expected);
+        // narrow windows leave products of two primes above the sieving bound
+        let n = 1_000_003 * 1_000_033;
+        assert_eq!(factorize_range(n, n)[0].factors(), [(1_000_003, 1), (1_000_033, 1)]);
+        let expected: Vec<Factorization> = (n - 5..=n + 5).map(factorize).collect();
+        assert_eq!(factorize_range(n - 5, n + 5), expected);
+        assert!(factorize_range(10, 9).is_empty());
+    }
+    #[test]
+    #[should_panic]
+    fn factorize_range_zero() {
+        factorize_range(0, 10);
+    }
+
+    #[test]
+    fn totient_brute_force() {
+        for n in 1..1000u64 {
+            assert_eq!(totient(n), (1..=n).filter(|&a| gcd(a, n) == 1).count() as u64, "{}", n);
+        }
+    }
+    #[test]
+    fn totient_values() {
+        assert_eq!(totient(1), 1);
+        assert_eq!(totient(1_000_000_007), 1_000_000_006);
+        assert_eq!(totient(1 << 63), 1 << 62);
+        assert_eq!(totient(u64::MAX), 9_208_981_628_670_443_520);
+        for a in (2..100).filter(|&a| gcd(a, 1_000_000) == 1) {
+            assert_eq!(mod_pow(a, totient(1_000_000), 1_000_000), 1);
+        }
+    }
+
+    #[test]
+    fn mobius_values() {
+        let expected = [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0, -1, 1, 1, 0, -1, 0, -1, 0];
+        for (n, &m) in expected.iter().enumerate() {
+            assert_eq!(mobius(n as u64 + 1), m, "{}", n + 1);
+        }
+        assert_eq!(mobius(1_000_000_007), -1);
+        assert_eq!(mobius(4_294_967_291 * 4_294_967_279), 1);
+        assert_eq!(mobius(4_294_967_291 * 4_294_967_291), 0);
+        assert_eq!(mobius(u64::MAX), -1);
+    }
+    #[test]
+    fn mobius_divisor_sum() {
+        for n in 1..2000u64 {
+            let sum: i64 = divisors(n).map(|d| mobius(d) as i64).sum();
+            assert_eq!(sum, (n == 1) as i64, "{}", n);
+        }
+    }
+
+    #[test]
+    fn carmichael_lambda_brute_force() {
+        for n in 1..600u64 {
+            let units: Vec<u64> = (1..=n).filter(|&a| gcd(a, n) == 1).collect();
+            let expected = (1..).find(|&m| units.iter().all(|&a| mod_pow(a, m, n) == 1 % n)).unwrap();
+            assert_eq!(carmichael_lambda(n), expected, "{}", n);
+        }
+    }
+    #[test]
+    fn carmichael_lambda_values() {
+        assert_eq!(carmichael_lambda(1), 1);
+        assert_eq!(carmichael_lambda(561), 80);
+        assert_eq!(carmichael_lambda(1 << 63), 1 << 61);
+        assert_eq!(carmichael_lambda(1_000_000_007), 1_000_000_006);
+        assert_eq!(carmichael_lambda(u64::MAX), 17_153_064_960);
+    }
+
+    #[test]
+    fn omega_values() {
+        assert_eq!((big_omega(1), little_omega(1), liouville(1)), (0, 0, 1));
+        assert_eq!((big_omega(12), little_omega(12), liouville(12)), (3, 2, -1));
+        assert_eq!((big_omega(1 << 63), little_omega(1 << 63), liouville(1 << 63)), (63, 1, -1));
+        assert_eq!((big_omega(u64::MAX), little_omega(u64::MAX), liouville(u64::MAX)), (7, 7, -1));
+        for n in 1..1000 {
+            if mobius(n) != 0 {
+                assert_eq!(liouville(n), mobius(n));
+            }
+            assert_eq!(liouville(n) * liouville(n + 1), liouville(n * (n + 1)));
+        }
+    }
+
+    #[test]
+    fn squarefree_radical_brute_force() {
+        for n in 1..5000u64 {
+            let f = factorize(n);
+            assert_eq!(is_squarefree(n), (2..=n.isqrt()).all(|d| !n.is_multiple_of(d * d)), "{}", n);
+            assert_eq!(is_squarefree(n), f.is_squarefree());
+            assert_eq!(radical(n), f.radical(), "{}", n);
+        }
+    }
+    #[test]
+    fn squarefree_radical_large() {
+        let (p, q) = (4_294_967_291, 4_294_967_279);
+        assert!(is_squarefree(p * q));
+        assert!(!is_squarefree(p * p));
+        assert!(!is_squarefree(1_000_003 * 1_000_003 * 1021));
+        assert!(!is_squarefree(1031 * 1031 * 1033));
+        assert!(is_squarefree(1031 * 1033 * 1039 * 1049));
+        assert!(!is_squarefree(1 << 63));
+        assert!(is_squarefree(u64::MAX));
+        assert_eq!(radical(p * p), p);
+        assert_eq!(radical(1 << 63), 2);
+        assert_eq!(radical(1031 * 1031 * 1033 * 12), 1031 * 1033 * 6);
+        assert_eq!(radical(u64::MAX), u64::MAX);
+        assert_eq!(radical(1), 1);
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{gcd_extended, Rational};
+
+/// Returns an iterator through the Farey sequence of order `n`, the reduced fractions in `[0, 1]`
+/// with denominators at most `n`, in increasing order.
+///
+/// Starts with `0/1` and `1/n` and obtains every next fraction from the previous two `a/b` and `c/d`
+/// as `(kc - a)/(kd - b)` with `k = floor((n + b)/d)`, so each fraction takes constant time.
+///
+/// Panics if `n` is not positive.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let f5: Vec<String> = farey(5).map(|x| x.to_string()).collect();
+/// assert_eq!(f5, ["0", "1/5", "1/4", "1/3", "2/5", "1/2", "3/5", "2/3", "3/4", "4/5", "1"]);
+/// ```
+pub fn farey(n: i64) -> Farey
+{
+    assert!(n > 0, "order must be positive");
+    Farey { n, prev: (-1, 0), current: Some((0, 1)) }
+}
+
+/// Iterator through the Farey sequence. Go to [farey] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Farey
+{
+    n: i64,
+    prev: (i64, i64),
+    current: Option<(i64, i64)>
+}
+
+impl Iterator for Farey {
+    type Item = Rational;
+
+    fn next(&mut self) -> Option<Rational> {
+        let (c, d) = self.current?;
+        self.current = if c == d {
+            None
+        } else if self.prev.1 == 0 {
+            Some((1, self.n))
+        } else {
+            let (a, b) = self.prev;
+            let (a, b, c, d, n) = (a as i128, b as i128, c as i128, d as i128, self.n as i128);
+            let k = (n + b) / d;
+            Some(((k * c - a) as i64, (k * d - b) as i64))
+        };
+        self.prev = (c, d);
+        Some(Rational::new(c, d))
+    }
+}
+
+/// Finds the neighbors of `p/q` in the Farey sequence of order `n`, the closest fractions with denominators
+/// at most `n` to the left and to the right of it.
+///
+/// The left neighbor `a/b` satisfies `pb - qa = 1`, so `b` is the largest number up to `n` congruent to
+/// the inverse of `p` modulo `q`, obtained by [gcd_extended]. The right neighbor `c/d` satisfies `qc - pd = 1`.
+/// Returns `None` for the left neighbor of `0/1` and the right neighbor of `1/1`.
+///
+/// Panics if `q` is zero, `p/q` is not in `[0, 1]` or its reduced denominator exceeds `n`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(farey_neighbors(2, 5, 7), (Some(Rational::new(1, 3)), Some(Rational::new(3, 7))));
+/// assert_eq!(farey_neighbors(1, 3, 7), (Some(Rational::new(2, 7)), Some(Rational::new(2, 5))));
+/// assert_eq!(farey_neighbors(0, 1, 4), (None, Some(Rational::new(1, 4))));
+/// ```
+pub fn farey_neighbors(p: i64, q: i64, n: i64) -> (Option<Rational>, Option<Rational>)
+{
+    let x = Rational::new(p, q);
+    let (p, q) = (x.numer(), x.denom());
+    assert!(0 <= p && p <= q, "fraction must be in [0, 1]");
+    assert!(q <= n, "denominator must not exceed the order");
+    let (p, q, n) = (p as i128, q as i128, n as i128);
+    // inverse of p modulo q, 0 for q = 1
+    let inv = gcd_extended(p, q).x0.rem_euclid(q);
+    // the largest b <= n with b ≡ r (mod q)
+    let largest = |r: i128| r + (n - r) / q * q;
+    let left = if p == 0 {
+        None
+    } else {
+        let b = largest(inv);
+        Some(Rational::new(((p * b - 1) / q) as i64, b as i64))
+    };
+    let right = if p == q {
+        None
+    } else {
+        let d = largest((q - inv) % q);
+        Some(Rational::new(((p * d + 1) / q) as i64, d as i64))
+    };
+    (left, right)
+}
+
+/// Computes the mediant `(p + r)/(q + s)` of `p/q` and `r/s`, which lies between them.
+/// The result is reduced, so it is the mediant of the reduced forms. Panics if it does not fit.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(mediant(Rational::new(1, 3), Rational::new(1, 2)), Rational::new(2, 5));
+/// assert_eq!(mediant(Rational::new(1, 2), Rational::new(5, 6)), Rational::new(3, 4));
+/// ```
+pub fn mediant(a: Rational, b: Rational) -> Rational
+{
+    let add = |x: i64, y: i64| x.checked_add(y).expect("rational overflow");
+    Rational::new(add(a.numer(), b.numer()), add(a.denom(), b.denom()))
+}
+
+/// Computes the determinant `qr - ps` of `a = p/q` and `b = r/s` in lowest terms, which is positive if and only if `a < b`.
+///
+/// The pair is unimodular if the determinant is `±1`, in which case no fraction between them has a denominator
+/// smaller than `q + s` and their mediant splits the interval into two unimodular pairs again.
+pub fn fraction_determinant(a: Rational, b: Rational) -> i128
+{
+    a.denom() as i128 * b.numer() as i128 - a.numer() as i128 * b.denom() as i128
+}
+
+/// Returns true if `a` and `b` are unimodular, that is `|qr - ps| = 1`. Otherwise returns false.
+///
+/// Such fractions are neighbors in the Farey sequences of every order from `max(q, s)` up to `q + s - 1`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert!(are_farey_neighbors(Rational::new(2, 5), Rational::new(1, 3)));
+/// assert!(!are_farey_neighbors(Rational::new(1, 5), Rational::new(1, 3)));
+/// ```
+pub fn are_farey_neighbors(a: Rational, b: Rational) -> bool
+{
+    fraction_determinant(a, b).abs() == 1
+}
+
+/// Finds the unimodular pair `a < x < b` whose mediant is `x`, the parents of `x` in the Stern-Brocot tree
+/// shifted by the integer part of `x`. Returns `None` if `x` is an integer.
+///
+/// For `x = p/q` the parents are the neighbors of `x` in the Farey sequence of order `q`,
+/// since their denominators add up to `q`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(mediant_parents(Rational::new(3, 5)), Some((Rational::new(1, 2), Rational::new(2, 3))));
+/// assert_eq!(mediant_parents(Rational::new(-7, 3)), Some((Rational::new(-5, 2), Rational::from(-2))));
+/// assert_eq!(mediant_parents(Rational::from(4)), None);
+/// ```
+pub fn mediant_parents(x: Rational) -> Option<(Rational, Rational)>
+{
+    if x.is_integer() {
+        return None;
+    }
+    let n = x.floor();
+    let (p, q) = (x.numer().rem_euclid(x.denom()), x.denom());
+    match farey_neighbors(p, q, q) {
+        (Some(a), Some(b)) => Some((a + Rational::from(n), b + Rational::from(n))),
+        _ => unreachable!()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn farey_naive(n: i64) -> Vec<Rational> {
+        let mut res: Vec<Rational> = (1..=n).flat_map(|q| (0..=q).map(move |p| Rational::new(p, q))).collect();
+        res.sort();
+        res.dedup();
+        res
+    }
+
+    #[test]
+    fn farey_brute_force() {
+        for n in 1..60 {
+            let expected = farey_naive(n);
+            assert_eq!(farey(n).collect::<Vec<_>>(), expected, "{}", n);
+            assert_eq!(expected.len() as u64, 1 + (1..=n as u64).map(crate::totient).sum::<u64>());
+        }
+        assert_eq!(farey(1).collect::<Vec<_>>(), [Rational::from(0), Rational::from(1)]);
+    }
+    #[test]
+    fn farey_large_order() {
+        let n = i64::MAX;
+        let first: Vec<Rational> = farey(n).take(3).collect();
+        assert_eq!(first, [Rational::from(0), Rational::new(1, n), Rational::new(1, n - 1)]);
+        // the fractions around 1/2 in the middle of the sequence
+        let mut iter = farey(n);
+        let left = farey_neighbors(n / 2, n, n).0.unwrap();
+        iter.prev = (left.numer(), left.denom());
+        iter.current = Some((n / 2, n));
+        assert_eq!(iter.nth(1), Some(Rational::new(1, 2)));
+        assert_eq!(iter.next(), Some(Rational::new(n / 2 + 1, n)));
+    }
+    #[test]
+    fn farey_neighbors_brute_force() {
+        for n in 1..40 {
+            let seq = farey_naive(n);
+            for (i, x) in seq.iter().enumerate() {
+                let expected = (if i > 0 { Some(seq[i - 1]) } else { None }, seq.get(i + 1).cloned());
+                assert_eq!(farey_neighbors(x.numer(), x.denom(), n), expected, "{} {}", x, n);
+            }
+        }
+        assert_eq!(farey_neighbors(2, 4, 3), (Some(Rational::new(1, 3)), Some(Rational::new(2, 3))));
+    }
+    #[test]
+    fn farey_neighbors_large() {
+        let (n, q) = (i64::MAX, 1_000_000_007);
+        let (left, right) = farey_neighbors(12345, q, n);
+        let (left, right) = (left.unwrap(), right.unwrap());
+        assert_eq!(12345 * left.denom() as i128 - q as i128 * left.numer() as i128, 1);
+        assert_eq!(q as i128 * right.numer() as i128 - 12345 * right.denom() as i128, 1);
+        assert!(left.denom() > n - q && right.denom() > n - q);
+        assert_eq!(farey_neighbors(1, 1, n), (Some(Rational::new(n - 1, n)), None));
+    }
+    #[test]
+    #[should_panic]
+    fn farey_neighbors_outside() {
+        farey_neighbors(3, 2, 5);
+    }
+
+    #[test]
+    fn mediant_utilities_brute_force() {
+        let values: Vec<Rational> = (1..12).flat_map(|q| (-15..=15).map(move |p| Rational::new(p, q))).collect();
+        for &a in &values {
+            for &b in &values {
+                let det = fraction_determinant(a, b);
+                assert_eq!(det.signum() as i8, (a < b) as i8 - (a > b) as i8);
+                if a != b {
+                    let m = mediant(a, b);
+                    assert!(a.min(b) < m && m < a.max(b));
+                }
+                if are_farey_neighbors(a, b) {
+                    // no fraction strictly between has a denominator below q + s
+                    let bound = a.denom() + b.denom();
+                    assert_eq!(crate::SternBrocot::simplest_between(a.min(b), a.max(b)).unwrap().denom(), bound);
+                    let m = mediant(a, b);
+                    assert!(are_farey_neighbors(a, m) && are_farey_neighbors(m, b));
+                }
+            }
+        }
+        for x in values {
+            match mediant_parents(x) {
+                Some((a, b)) => {
+                    assert!(a < x && x < b && are_farey_neighbors(a, b), "{}", x);
+                    assert_eq!(mediant(a, b), x);
+                }
+                None => assert!(x.is_integer())
+            }
+        }
+    }
+    #[test]
+    fn mediant_utilities_large() {
+        let (a, b) = (Rational::new(i64::MAX - 1, i64::MAX), Rational::from(1));
+        assert_eq!(fraction_determinant(a, b), 1);
+        assert!(are_farey_neighbors(b, a));
+        assert_eq!(fraction_determinant(Rational::from(i64::MIN), Rational::from(i64::MAX)), u64::MAX as i128);
+        let x = Rational::new(i64::MAX - 1, i64::MAX);
+        let (a, b) = mediant_parents(x).unwrap();
+        assert_eq!(a.denom() + b.denom(), i64::MAX);
+        assert_eq!(b, Rational::from(1));
+    }
+}
@@ -0,0 +1,46 @@
+//! Binary indexed tree counting the present elements of `0, ..., n-1`, used to rank permutations
+//! and to simulate eliminations from a circle.
+
+/// Creates a binary indexed tree over the 1-based positions `1, ..., n`, all present.
+/// The 0-based element `x` is kept at the position `x + 1`.
+pub(crate) fn fenwick_full(n: usize) -> Vec<usize>
+{
+    // tree[i] counts the present positions in (i - lowbit(i), i]
+    (0..=n).map(|i| i & i.wrapping_neg()).collect()
+}
+
+/// Counts the present elements smaller than the 0-based element `x`.
+pub(crate) fn fenwick_prefix(tree: &[usize], x: usize) -> usize
+{
+    let (mut i, mut res) = (x, 0);
+    while i > 0 {
+        res += tree[i];
+        i -= i & i.wrapping_neg();
+    }
+    res
+}
+
+/// Removes the 0-based element `x`.
+pub(crate) fn fenwick_remove(tree: &mut [usize], x: usize)
+{
+    let mut i = x + 1;
+    while i < tree.len() {
+        tree[i] -= 1;
+        i += i & i.wrapping_neg();
+    }
+}
+
+/// Finds the 0-based present element with exactly `count` smaller present elements.
+pub(crate) fn fenwick_find(tree: &[usize], count: usize) -> usize
+{
+    let (mut pos, mut rest) = (0, count + 1);
+    let mut step = (tree.len() - 1).next_power_of_two();
+    while step > 0 {
+        if pos + step < tree.len() && tree[pos + step] < rest {
+            pos += step;
+            rest -= tree[pos];
+        }
+        step /= 2;
+    }
+    pos
+}
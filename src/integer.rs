@@ -0,0 +1,149 @@
+use std::fmt::Debug;
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg, Shl, Shr};
+
+/// Primitive integer type accepted by the generic functions of this crate.
+///
+/// Implemented for `u8`–`u128`, `usize`, `i8`–`i128` and `isize`.
+pub trait Integer:
+    Copy + Ord + Debug
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+    + Div<Output = Self> + Rem<Output = Self>
+    + Shl<u32, Output = Self> + Shr<u32, Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    /// Unsigned type of the same width, able to hold the magnitude of any value.
+    type Unsigned: Integer;
+
+    /// Absolute value of `self`. Identity for unsigned types.
+    fn abs(self) -> Self;
+
+    /// Absolute value of `self` as the unsigned type of the same width. Never overflows.
+    fn unsigned_abs(self) -> Self::Unsigned;
+
+    /// Converts an unsigned magnitude back to `Self`, wrapping if it does not fit.
+    fn wrapping_from_unsigned(u: Self::Unsigned) -> Self;
+
+    /// Converts an unsigned magnitude back to `Self`. Returns `None` if it does not fit.
+    fn checked_from_unsigned(u: Self::Unsigned) -> Option<Self>;
+
+    /// Number of trailing zeros in the binary representation of `self`.
+    fn trailing_zeros(self) -> u32;
+
+    /// Checked addition. Returns `None` if overflow occurred.
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+
+    /// Checked subtraction. Returns `None` if overflow occurred.
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+
+    /// Checked multiplication. Returns `None` if overflow occurred.
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+
+    /// Wrapping multiplication together with a flag indicating whether overflow occurred.
+    fn overflowing_mul(self, rhs: Self) -> (Self, bool);
+}
+
+/// Signed primitive integer type, required where negative results can appear.
+///
+/// Implemented for `i8`–`i128` and `isize`.
+pub trait SignedInteger: Integer + Neg<Output = Self> {}
+
+macro_rules! impl_integer_signed {
+    ($($t:ty : $u:ty)*) => {$(
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            type Unsigned = $u;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn unsigned_abs(self) -> $u {
+                <$t>::unsigned_abs(self)
+            }
+
+            fn wrapping_from_unsigned(u: $u) -> Self {
+                u as $t
+            }
+
+            fn checked_from_unsigned(u: $u) -> Option<Self> {
+                if u <= <$t>::MAX as $u { Some(u as $t) } else { None }
+            }
+
+            fn trailing_zeros(self) -> u32 {
+                <$t>::trailing_zeros(self)
+            }
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+                <$t>::overflowing_mul(self, rhs)
+            }
+        }
+
+        impl SignedInteger for $t {}
+    )*}
+}
+
+macro_rules! impl_integer_unsigned {
+    ($($t:ty)*) => {$(
+        impl Integer for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            type Unsigned = Self;
+
+            fn abs(self) -> Self {
+                self
+            }
+
+            fn unsigned_abs(self) -> Self {
+                self
+            }
+
+            fn wrapping_from_unsigned(u: Self) -> Self {
+                u
+            }
+
+            fn checked_from_unsigned(u: Self) -> Option<Self> {
+                Some(u)
+            }
+
+            fn trailing_zeros(self) -> u32 {
+                <$t>::trailing_zeros(self)
+            }
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn checked_sub(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_sub(self, rhs)
+            }
+
+            fn checked_mul(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_mul(self, rhs)
+            }
+
+            fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+                <$t>::overflowing_mul(self, rhs)
+            }
+        }
+    )*}
+}
+
+impl_integer_signed!(i8:u8 i16:u16 i32:u32 i64:u64 i128:u128 isize:usize);
+impl_integer_unsigned!(u8 u16 u32 u64 u128 usize);
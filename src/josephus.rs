@@ -0,0 +1,171 @@
+use crate::fenwick::{fenwick_find, fenwick_full, fenwick_remove};
+
+/// Solves the Josephus problem: `n` people stand in a circle at positions `0, ..., n-1`, and starting from position 0,
+/// every `k`-th remaining person is eliminated until one survives. Returns the position of the survivor.
+///
+/// Dispatches to [josephus_skipping] if `k` is small compared to `n` and to [josephus_recurrence] otherwise.
+///
+/// Panics if `n` or `k` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(josephus(7, 3), 3);
+/// assert_eq!(josephus(41, 3), 30);
+/// assert_eq!(josephus(1 << 40, 2), 0);
+/// ```
+pub fn josephus(n: u64, k: u64) -> u64
+{
+    assert!(n > 0 && k > 0, "n and k must be positive");
+    if k < n / (64 - n.leading_zeros() as u64) {
+        josephus_skipping(n, k)
+    } else {
+        josephus_recurrence(n, k)
+    }
+}
+
+/// Solves the Josephus problem in `O(n)` time using the recurrence `J(m) = (J(m - 1) + k) mod m`.
+/// Go to [josephus] for further information.
+pub fn josephus_recurrence(n: u64, k: u64) -> u64
+{
+    assert!(n > 0 && k > 0, "n and k must be positive");
+    let mut res = 0;
+    for m in 2..=n {
+        res = (res + k % m) % m;
+    }
+    res
+}
+
+/// Solves the Josephus problem in `O(k log n)` time by eliminating a whole round of `floor(n/k)` people at once.
+/// Go to [josephus] for further information.
+pub fn josephus_skipping(n: u64, k: u64) -> u64
+{
+    assert!(n > 0 && k > 0, "n and k must be positive");
+    if k == 1 {
+        return n - 1;
+    }
+    let mut sizes = Vec::new();
+    let mut m = n;
+    while m > 1 {
+        sizes.push(m);
+        m = if k > m { m - 1 } else { m - m / k };
+    }
+    let mut res = 0;
+    for &m in sizes.iter().rev() {
+        res = if k > m {
+            (res + k % m) % m
+        } else {
+            // after a round eliminating every k-th of m people, the circle restarts at position m - m mod k
+            let rest = m % k;
+            if res < rest { res + m - rest } else { res - rest + (res - rest) / (k - 1) }
+        };
+    }
+    res
+}
+
+/// Returns an iterator through the positions of the people in the Josephus problem in the order of elimination.
+/// The last position yielded is the survivor returned by [josephus].
+///
+/// Each step finds the next person in `O(log n)` time by a binary indexed tree over the remaining positions.
+///
+/// Panics if `n` or `k` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(josephus_eliminations(7, 3).collect::<Vec<_>>(), [2, 5, 1, 6, 4, 0, 3]);
+/// ```
+pub fn josephus_eliminations(n: usize, k: u64) -> JosephusEliminations
+{
+    assert!(n > 0 && k > 0, "n and k must be positive");
+    JosephusEliminations { k, tree: fenwick_full(n), remaining: n, index: 0 }
+}
+
+/// Iterator through the elimination order of the Josephus problem. Go to [josephus_eliminations] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct JosephusEliminations
+{
+    k: u64,
+    tree: Vec<usize>,
+    remaining: usize,
+    // index of the next person to count from among the remaining ones
+    index: usize
+}
+
+impl Iterator for JosephusEliminations {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let target = ((self.index as u64 + (self.k - 1) % self.remaining as u64) % self.remaining as u64) as usize;
+        let pos = fenwick_find(&self.tree, target);
+        fenwick_remove(&mut self.tree, pos);
+        self.remaining -= 1;
+        self.index = if self.remaining == 0 { 0 } else { target % self.remaining };
+        Some(pos)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for JosephusEliminations {}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn eliminations_naive(n: usize, k: u64) -> Vec<usize> {
+        let mut circle: Vec<usize> = (0..n).collect();
+        let mut res = Vec::new();
+        let mut index = 0;
+        while !circle.is_empty() {
+            index = (index + (k - 1) as usize % circle.len()) % circle.len();
+            res.push(circle.remove(index));
+        }
+        res
+    }
+
+    #[test]
+    fn josephus_brute_force() {
+        for n in 1..80 {
+            for k in 1..100 {
+                let naive = eliminations_naive(n, k);
+                let survivor = *naive.last().unwrap() as u64;
+                assert_eq!(josephus_eliminations(n, k).collect::<Vec<_>>(), naive, "{} {}", n, k);
+                assert_eq!(josephus_recurrence(n as u64, k), survivor, "{} {}", n, k);
+                assert_eq!(josephus_skipping(n as u64, k), survivor, "{} {}", n, k);
+                assert_eq!(josephus(n as u64, k), survivor, "{} {}", n, k);
+            }
+        }
+    }
+    #[test]
+    fn josephus_large() {
+        // for k = 2 the survivor is 2 (n - 2^floor(log2 n))
+        for n in [1_000_000_000_000_000_000u64, u64::MAX, (1 << 63) + 12345] {
+            let high = 1 << (63 - n.leading_zeros());
+            assert_eq!(josephus(n, 2), 2 * (n - high), "{}", n);
+        }
+        for n in [100_000u64, 123_457] {
+            for k in [3, 10, 1000, 99_999, 1 << 40] {
+                assert_eq!(josephus_skipping(n, k), josephus_recurrence(n, k), "{} {}", n, k);
+            }
+        }
+        assert_eq!(josephus(u64::MAX, 1), u64::MAX - 1);
+        assert_eq!(josephus_eliminations(100_000, 7).last(), Some(josephus(100_000, 7) as usize));
+        assert_eq!(josephus_eliminations(5, u64::MAX).len(), 5);
+    }
+    #[test]
+    #[should_panic]
+    fn josephus_zero_k() {
+        josephus(5, 0);
+    }
+}
@@ -1,3 +1,71 @@
+mod integer;
+mod modular;
+mod crt;
+mod modint;
+mod factor;
+mod ecm;
+mod siqs;
+mod primality;
+mod sieve;
+mod wheel;
+mod powers;
+mod arithmetic;
+mod residues;
+mod discrete_log;
+mod order;
+mod diophantine;
+mod squares;
+mod pythagorean;
+mod egyptian;
+mod josephus;
+mod rational;
+mod continued_fraction;
+mod stern_brocot;
+mod farey;
+mod digits;
+mod binomial;
+mod counting;
+mod combinations;
+mod permutations;
+mod partitions;
+mod necklaces;
+mod wide;
+mod fenwick;
+
+#[cfg(feature = "bigint")]
+pub mod bigint;
+
+pub use integer::*;
+pub use modular::*;
+pub use crt::*;
+pub use modint::*;
+pub use factor::*;
+pub use ecm::*;
+pub use siqs::*;
+pub use primality::*;
+pub use sieve::*;
+pub use wheel::*;
+pub use powers::*;
+pub use arithmetic::*;
+pub use residues::*;
+pub use discrete_log::*;
+pub use order::*;
+pub use diophantine::*;
+pub use squares::*;
+pub use pythagorean::*;
+pub use egyptian::*;
+pub use josephus::*;
+pub use rational::*;
+pub use continued_fraction::*;
+pub use stern_brocot::*;
+pub use farey::*;
+pub use digits::*;
+pub use binomial::*;
+pub use counting::*;
+pub use combinations::*;
+pub use permutations::*;
+pub use partitions::*;
+pub use necklaces::*;
 
 /// Returns true if `a` divides `b`. Otherwise returns false.
 ///
@@ -15,27 +83,28 @@
 /// assert_eq!(divides(5, -10), true);
 /// assert_eq!(divides(5, -7), false);
 /// ```
-pub fn divides(a: i64, b: i64) -> bool
+pub fn divides<T: Integer>(a: T, b: T) -> bool
 {
-    if a != 0 { b % a == 0 } else { true }
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+    if a != T::Unsigned::ZERO { b % a == T::Unsigned::ZERO } else { true }
 }
 
 /// Returns true if `a` is divisible by `b`. Otherwise returns false.
 /// `a` is divisible by `b` if `b` divides `a`. 
 /// Go to [divides] for further information.
-pub fn is_divisible_by(a: i64, b: i64) -> bool
+pub fn is_divisible_by<T: Integer>(a: T, b: T) -> bool
 {
     divides(b, a)
 }
 
 /// Returns true if `d` is common divisor of `a` and `b`. Otherwise returns false.
-pub fn is_common_divisor(d: i64, a: i64, b: i64) -> bool
+pub fn is_common_divisor<T: Integer>(d: T, a: T, b: T) -> bool
 {
     divides(d, a) && divides(d, b)
 }
 
 /// Returns true if `d` is common multiple of `a` and `b`. Otherwise returns false.
-pub fn is_common_multiple(d: i64, a: i64, b: i64) -> bool
+pub fn is_common_multiple<T: Integer>(d: T, a: T, b: T) -> bool
 {
     divides(a, d) && divides(b, d)
 }
@@ -44,44 +113,179 @@ pub fn is_common_multiple(d: i64, a: i64, b: i64) -> bool
 /// 
 /// We define the greatest common divisor as the largest element of the set of common divisors if at least one of `a`, `b` is nonzero.
 /// Otherwise we define `gcd(0, 0) = 0`.
-pub fn gcd(a: i64, b: i64) -> i64
+///
+/// Every input is accepted and the result lies in `[0, T::MAX]`, with a single exception for signed types:
+/// `gcd(T::MIN, 0)`, `gcd(0, T::MIN)` and `gcd(T::MIN, T::MIN)` equal `2^(bits - 1)`, which does not fit in `T`
+/// and wraps to `T::MIN`. Use [unsigned_gcd] to get the exact value.
+///
+/// Dispatches to [gcd_binary], which avoids division.
+pub fn gcd<T: Integer>(a: T, b: T) -> T
+{
+    gcd_binary(a, b)
+}
+
+/// Computes greatest common divisor of `a` and `b` using the binary (Stein's) algorithm.
+/// Go to [gcd] for further information.
+pub fn gcd_binary<T: Integer>(a: T, b: T) -> T
+{
+    T::wrapping_from_unsigned(unsigned_gcd(a, b))
+}
+
+/// Computes greatest common divisor of `a` and `b` using the euclid's algorithm.
+/// Go to [gcd] for further information.
+pub fn gcd_euclid<T: Integer>(a: T, b: T) -> T
+{
+    T::wrapping_from_unsigned(gcd_euclid_noabs(a.unsigned_abs(), b.unsigned_abs()))
+}
+
+/// Computes greatest common divisor of `a` and `b` as the unsigned type of the same width.
+/// Unlike [gcd], the result is exact for every input.
+pub fn unsigned_gcd<T: Integer>(a: T, b: T) -> T::Unsigned
 {
-    gcd_noabs(a.abs(), b.abs())
+    gcd_binary_noabs(a.unsigned_abs(), b.unsigned_abs())
 }
 
 /// Computes least common multiple of `a` and `b`.
 /// 
 /// We define the least common multiple as the smallest element of the set of common multiples if both `a`, `b` are nonzero.
 /// Otherwise we define `lmc(a, 0) = lmc(0, b) = 0`.
-pub fn lcm(mut a: i64, mut b: i64) -> i64
+///
+/// Every input is accepted, including `T::MIN`, but the result overflows if it does not fit in `T`,
+/// see [checked_lcm] and [overflowing_lcm].
+pub fn lcm<T: Integer>(a: T, b: T) -> T
 {
-    if a == 0 || b == 0 {
-        return 0;
+    let (res, overflow) = overflowing_lcm(a, b);
+    debug_assert!(!overflow, "attempt to compute lcm with overflow");
+    res
+}
+
+/// Computes least common multiple of `a` and `b`.
+/// Returns `None` if the result does not fit in `T`.
+/// Go to [lcm] for further information.
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Option<T>
+{
+    let (a, b) = lcm_factors(a, b);
+    T::checked_from_unsigned(a.checked_mul(b)?)
+}
+
+/// Computes least common multiple of `a` and `b`.
+/// Returns the wrapped result together with a flag indicating whether overflow occurred.
+/// Go to [lcm] for further information.
+pub fn overflowing_lcm<T: Integer>(a: T, b: T) -> (T, bool)
+{
+    let (a, b) = lcm_factors(a, b);
+    let (res, overflow) = a.overflowing_mul(b);
+    match T::checked_from_unsigned(res) {
+        Some(res) => (res, overflow),
+        None => (T::wrapping_from_unsigned(res), true)
+    }
+}
+
+/// Computes greatest common divisor of all elements of `a`.
+///
+/// Returns 0 for an empty slice. Stops early once the running gcd reaches 1.
+pub fn gcd_all<T: Integer>(a: &[T]) -> T
+{
+    a.iter().copied().gcd()
+}
+
+/// Computes least common multiple of all elements of `a`.
+///
+/// Returns 1 for an empty slice. Stops early once the running lcm reaches 0.
+pub fn lcm_all<T: Integer>(a: &[T]) -> T
+{
+    a.iter().copied().lcm()
+}
+
+/// Extends iterators over integers with [gcd] and [lcm] folds.
+pub trait IteratorGcdExt: Iterator {
+    /// Computes greatest common divisor of all items. Go to [gcd_all] for further information.
+    fn gcd(self) -> Self::Item;
+
+    /// Computes least common multiple of all items. Go to [lcm_all] for further information.
+    fn lcm(self) -> Self::Item;
+}
+
+impl<T: Integer, I: Iterator<Item = T>> IteratorGcdExt for I {
+    fn gcd(self) -> T {
+        let mut res = T::ZERO;
+        for x in self {
+            res = gcd(res, x);
+            if res == T::ONE {
+                break;
+            }
+        }
+        res
+    }
+
+    fn lcm(self) -> T {
+        let mut res = T::ONE;
+        for x in self {
+            res = lcm(res, x);
+            if res == T::ZERO {
+                break;
+            }
+        }
+        res
+    }
+}
+
+/// Returns the two unsigned factors whose product is the least common multiple of `a` and `b`,
+/// so that only the final multiplication can overflow.
+fn lcm_factors<T: Integer>(a: T, b: T) -> (T::Unsigned, T::Unsigned)
+{
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+    if a == T::Unsigned::ZERO || b == T::Unsigned::ZERO {
+        return (T::Unsigned::ZERO, T::Unsigned::ZERO);
+    }
+    (a / gcd_binary_noabs(a, b), b)
+}
+
+/// Computes greatest common divisor of `a` and `b`,
+/// where `a` and `b` are not negative. 
+fn gcd_binary_noabs<T: Integer>(mut a: T, mut b: T) -> T
+{
+    if a == T::ZERO {
+        return b;
+    }
+    if b == T::ZERO {
+        return a;
+    }
+    let (za, zb) = (a.trailing_zeros(), b.trailing_zeros());
+    let shift = za.min(zb);
+    a = a >> za;
+    b = b >> zb;
+    loop {
+        if a > b {
+            (a, b) = (b, a);
+        }
+        b = b - a;
+        if b == T::ZERO {
+            return a << shift;
+        }
+        b = b >> b.trailing_zeros();
     }
-    a = a.abs();
-    b = b.abs();
-    (a * b) / gcd_noabs(a, b)
 }
 
 /// Computes greatest common divisor of `a` and `b`,
 /// where `a` and `b` are not negative. 
-fn gcd_noabs(a: i64, b: i64) -> i64
+fn gcd_euclid_noabs<T: Integer>(a: T, b: T) -> T
 {
     match (a, b) {
-        (a, b) if a == 0 && b == 0 => 0,
-        (a, b) if b == 0 => a,
-        (a, b) if a == 0 => b,
-        (a, b) if a > b => gcd_euclid(a, b),
-        (a, b) if a < b => gcd_euclid(b, a),
+        (a, b) if a == T::ZERO && b == T::ZERO => T::ZERO,
+        (a, b) if b == T::ZERO => a,
+        (a, b) if a == T::ZERO => b,
+        (a, b) if a > b => gcd_euclid_loop(a, b),
+        (a, b) if a < b => gcd_euclid_loop(b, a),
         (a, _) => a
     }
 }
 
 /// Computes greatest common divisor of `a` and `b`,
 /// where `a` and `b` are positive and `a` > `b`.
-fn gcd_euclid(mut a: i64, mut b: i64) -> i64 
+fn gcd_euclid_loop<T: Integer>(mut a: T, mut b: T) -> T
 {
-    while b != 0 {
+    while b != T::ZERO {
         let r = a % b;
         (a, b) = (b, r);
     }
@@ -89,97 +293,251 @@ fn gcd_euclid(mut a: i64, mut b: i64) -> i64
 }
 
 #[derive(PartialEq, Debug)]
-pub struct GcdExtendedResult
+pub struct GcdExtendedResult<T = i64>
 {
-    pub gcd: i64,
-    pub x0: i64,
-    pub y0: i64,
-    pub x1: i64,
-    pub y1: i64
+    pub gcd: T,
+    pub x0: T,
+    pub y0: T,
+    pub x1: T,
+    pub y1: T
 }
 
 /// Computes greatest common divisor of `a` and `b`.
 /// This is an extended variant which also computes `x0`, `y0`, `x1`, `y1`,
 /// satisfying `gcd(a, b) = x0*a + y0*b` and `0 = x1*a + y1*b`.
 /// If there are more solutions, only one will be returned.
-pub fn gcd_extended(a: i64, b: i64) -> GcdExtendedResult 
+///
+/// Like [gcd], it works on the magnitudes, so every input is accepted, including `T::MIN`.
+/// Panics if the gcd does not fit in `T`, which only happens when one input is `T::MIN` and the other is `0` or `T::MIN`.
+pub fn gcd_extended<T: SignedInteger>(a: T, b: T) -> GcdExtendedResult<T>
 {
-    let mut res = gcd_extended_noabs(a.abs(), b.abs());
-    if a < 0 {
+    let (zero, one) = (T::ZERO, T::ONE);
+    let (ua, ub) = (a.unsigned_abs(), b.unsigned_abs());
+    let gcd = |g| T::checked_from_unsigned(g).expect("gcd does not fit in the signed type");
+    let uzero = <T::Unsigned as Integer>::ZERO;
+    let mut res = if ua == uzero && ub == uzero {
+        GcdExtendedResult { gcd: zero, x0: zero, y0: zero, x1: zero, y1: zero }
+    } else if ub == uzero {
+        GcdExtendedResult { gcd: gcd(ua), x0: one, y0: zero, x1: zero, y1: zero }
+    } else if ua == uzero {
+        GcdExtendedResult { gcd: gcd(ub), x0: zero, y0: one, x1: zero, y1: zero }
+    } else if ua == ub {
+        GcdExtendedResult { gcd: gcd(ua), x0: one, y0: zero, x1: -one, y1: one }
+    } else {
+        let swap = ua < ub;
+        let (g, mut x0, mut y0, odd) = if swap { gcd_extended_magnitudes::<T>(ub, ua) } else { gcd_extended_magnitudes::<T>(ua, ub) };
+        if swap {
+            (x0, y0) = (y0, x0);
+        }
+        // x1 = ±b/g and y1 = ∓a/g, the coefficient of the larger magnitude is positive after an odd number of steps
+        let (mut x1_negative, mut y1_negative) = (odd == swap, odd != swap);
+        x1_negative ^= a < zero;
+        y1_negative ^= b < zero;
+        let (mx, my) = (ub / g, ua / g);
+        if (!x1_negative && T::checked_from_unsigned(mx).is_none()) || (!y1_negative && T::checked_from_unsigned(my).is_none()) {
+            // only the negative magnitude 2^(bits-1) fits, so both signs are flipped
+            x1_negative = !x1_negative;
+            y1_negative = !y1_negative;
+        }
+        let x1 = from_magnitude(mx, x1_negative);
+        let y1 = from_magnitude(my, y1_negative);
+        return GcdExtendedResult { gcd: gcd(g), x0: if a < zero { -x0 } else { x0 }, y0: if b < zero { -y0 } else { y0 }, x1, y1 };
+    };
+    if a < zero {
         res.x0 = -res.x0;
         res.x1 = -res.x1;
     }
-    if b < 0 {
+    if b < zero {
         res.y0 = -res.y0;
         res.y1 = -res.y1;
     }
     res
 }
 
-fn gcd_extended_noabs(a: i64, b: i64) -> GcdExtendedResult 
+/// Runs the extended Euclid's algorithm on magnitudes `a > b > 0`. Returns the gcd, the coefficients `x0`, `y0`
+/// and whether the number of steps is odd. The final coefficients `±b/g` and `∓a/g` are not computed, since they might not fit.
+fn gcd_extended_magnitudes<T: SignedInteger>(mut a: T::Unsigned, mut b: T::Unsigned) -> (T::Unsigned, T, T, bool)
 {
-    match (a, b) {
-        (a, b) if a == 0 && b == 0 => GcdExtendedResult { gcd: 0, x0: 0, y0: 0, x1: 0, y1: 0 },
-        (a, b) if b == 0 => GcdExtendedResult { gcd: a, x0: 1, y0: 0, x1: 0, y1: 0 },
-        (a, b) if a == 0 => GcdExtendedResult { gcd: b, x0: 0, y0: 1, x1: 0, y1: 0 },
-        (a, b) if a > b => gcd_extended_bezout(a, b),
-        (a, b) if a < b => {
-            let mut res = gcd_extended_bezout(b, a);
-            (res.x0, res.y0) = (res.y0, res.x0);
-            (res.x1, res.y1) = (res.y1, res.x1);
-            res
-        },
-        (a, _) => GcdExtendedResult { gcd: a, x0: 1, y0: 0, x1: -1, y1: 1 }
-    }
-}
-
-fn gcd_extended_bezout(mut a: i64, mut b: i64) -> GcdExtendedResult 
-{
-    let mut a0 = 1;
-    let mut a1 = 0;
-    let mut b0 = 0;
-    let mut b1 = 1;
-    while b != 0 {
+    let (mut a0, mut a1, mut b0, mut b1) = (T::ONE, T::ZERO, T::ZERO, T::ONE);
+    let mut odd = false;
+    loop {
         let q = a / b;
-        let r = a - b * q;
+        let r = a % b;
         (a, b) = (b, r);
+        odd = !odd;
+        if r == <T::Unsigned as Integer>::ZERO {
+            return (a, a1, b1, odd);
+        }
+        // all but the final coefficients are at most a/(2g) in magnitude, so q fits as well
+        let q = T::wrapping_from_unsigned(q);
         (a0, a1) = (a1, a0 - q * a1);
         (b0, b1) = (b1, b0 - q * b1);
     }
-    GcdExtendedResult { gcd: a, x0: a0, y0: b0, x1: a1, y1: b1 }
+}
+
+/// Converts a magnitude up to `2^(bits-1)` with a sign to `T`, the magnitude `2^(bits-1)` must be negative.
+fn from_magnitude<T: SignedInteger>(m: T::Unsigned, negative: bool) -> T
+{
+    match T::checked_from_unsigned(m) {
+        Some(v) if negative => -v,
+        Some(v) => v,
+        None => T::wrapping_from_unsigned(m)
+    }
+}
+
+/// Computes greatest common divisor of `a` and `b` together with canonical coefficients.
+///
+/// Same as [gcd_extended], but the returned solution is unique:
+/// `0 <= x0 < |b| / gcd` (or `x0 = a / gcd` and `y0 = 0` if `b = 0`),
+/// `x1 = b / gcd` and `y1 = -a / gcd`. For `a = b = 0` all values are 0.
+/// Panics if `a / gcd` or `b / gcd` is `T::MIN`, since the canonical coefficients do not fit then.
+pub fn gcd_extended_minimal<T: SignedInteger>(a: T, b: T) -> GcdExtendedResult<T>
+{
+    let res = gcd_extended(a, b);
+    let g = res.gcd;
+    if g == T::ZERO {
+        return res;
+    }
+    let (x1, y1) = (b / g, -(a / g));
+    let m = x1.abs();
+    if m == T::ZERO {
+        return GcdExtendedResult { gcd: g, x0: a / g, y0: T::ZERO, x1, y1 };
+    }
+    let mut x0 = res.x0 % m;
+    if x0 < T::ZERO {
+        x0 = x0 + m;
+    }
+    let t = (res.x0 - x0) / m;
+    let y0 = if b < T::ZERO { res.y0 - t * (a / g) } else { res.y0 + t * (a / g) };
+    GcdExtendedResult { gcd: g, x0, y0, x1, y1 }
+}
+
+/// Computes greatest common divisor of all elements of `a` together with coefficients `c`,
+/// satisfying `gcd = c[0]*a[0] + c[1]*a[1] + ... + c[n-1]*a[n-1]`.
+///
+/// Returns `(0, vec![])` for an empty slice. If there are more solutions, only one will be returned.
+/// Each coefficient but the last nonzero one is at most `max |a[j]| / 2` in magnitude, and the last one
+/// at most `1 + Σ |a[j]| / 2`, so the coefficients always fit when the sum of the magnitudes does.
+/// Panics if the last coefficient does not fit in `T`, or the gcd does not fit as in [gcd_extended].
+pub fn gcd_extended_many<T: SignedInteger>(a: &[T]) -> (T, Vec<T>)
+{
+    let mut g = T::ZERO;
+    let mut c: Vec<T> = Vec::with_capacity(a.len());
+    for &ai in a {
+        if ai == T::ZERO {
+            c.push(T::ZERO);
+            continue;
+        }
+        let res = gcd_extended(g, ai);
+        let m = ai.unsigned_abs() / res.gcd.unsigned_abs();
+        let half = m >> 1;
+        let x0 = residue(res.x0, m);
+        // the new coefficients c[j] * x0 are reduced into (-m/2, m/2], the last one is then
+        // (1 - Σ c[j] * a[j] / gcd) / (ai / gcd), accumulated as a quotient and a remainder modulo m
+        let (mut q, mut r) = (T::ZERO, <T::Unsigned as Integer>::ZERO);
+        for (cj, &aj) in c.iter_mut().zip(a) {
+            let cr = mul_div_rem(residue(*cj, m), x0, m).1;
+            *cj = if cr > half { -T::wrapping_from_unsigned(m - cr) } else { T::wrapping_from_unsigned(cr) };
+            let bj = aj.unsigned_abs() / res.gcd.unsigned_abs();
+            let (tq, tr) = mul_div_rem(cj.unsigned_abs(), bj % m, m);
+            let tq = T::checked_from_unsigned(tq + cj.unsigned_abs() * (bj / m)).expect("coefficient overflow");
+            let (tq, tr) = match (*cj < T::ZERO) == (aj < T::ZERO) {
+                true => (tq, tr),
+                false if tr == <T::Unsigned as Integer>::ZERO => (-tq, tr),
+                false => (-tq - T::ONE, m - tr)
+            };
+            q = q.checked_add(tq).expect("coefficient overflow");
+            if r >= m - tr {
+                r = r - (m - tr);
+                q = q.checked_add(T::ONE).expect("coefficient overflow");
+            } else {
+                r = r + tr;
+            }
+        }
+        // 1 - q*m - r is divisible by m, so r is 1, or 0 if m is 1
+        if m == <T::Unsigned as Integer>::ONE {
+            q = q.checked_sub(T::ONE).expect("coefficient overflow");
+        }
+        let ci = if ai > T::ZERO { T::ZERO.checked_sub(q).expect("coefficient overflow") } else { q };
+        c.push(ci);
+        g = res.gcd;
+    }
+    (g, c)
+}
+
+/// Residue of `a` modulo `m` in `[0, m)`.
+fn residue<T: Integer>(a: T, m: T::Unsigned) -> T::Unsigned
+{
+    let r = a.unsigned_abs() % m;
+    if a < T::ZERO && r != <T::Unsigned as Integer>::ZERO { m - r } else { r }
+}
+
+/// Computes `(a*b / m, a*b % m)` for `a, b < m` without overflow, by binary multiplication.
+fn mul_div_rem<U: Integer>(a: U, mut b: U, m: U) -> (U, U)
+{
+    let two = U::ONE + U::ONE;
+    // a * 2^k = cur.0 * m + cur.1
+    let (mut acc, mut cur) = ((U::ZERO, U::ZERO), (U::ZERO, a));
+    let add = |x: (U, U), y: (U, U)| {
+        if x.1 >= m - y.1 { (x.0 + y.0 + U::ONE, x.1 - (m - y.1)) } else { (x.0 + y.0, x.1 + y.1) }
+    };
+    while b != U::ZERO {
+        if b % two == U::ONE {
+            acc = add(acc, cur);
+        }
+        b = b >> 1;
+        if b != U::ZERO {
+            cur = add(cur, cur);
+        }
+    }
+    acc
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-pub struct GcdIteration
+pub struct GcdIteration<T = i64>
 {
-    pub a: i64,
-    pub b: i64
+    pub a: T,
+    pub b: T
 }
 
 /// Iterates through the euclid's alorithm for finging greatest common divisor.
+///
+/// Negative inputs are replaced by their magnitudes. If a magnitude does not fit (`T::MIN`),
+/// the step after the inputs is already taken on the magnitudes, and the iteration ends early
+/// when the gcd itself would be `2^(bits-1)`, i.e. for `(T::MIN, 0)`, `(0, T::MIN)` and `(T::MIN, T::MIN)`.
 #[derive(PartialEq, Debug, Clone, Copy)]
-pub struct GcdIterator
+pub struct GcdIterator<T = i64>
 {
-    i: Option<GcdIteration>
+    i: Option<GcdIteration<T>>
 }
 
-impl GcdIterator {
-    pub fn new(a: i64, b: i64) -> Self {
+impl<T: Integer> GcdIterator<T> {
+    pub fn new(a: T, b: T) -> Self {
         GcdIterator { i: Some(GcdIteration{ a, b })}
     }
 }
 
-impl Iterator for GcdIterator {
-    type Item = GcdIteration;
+impl<T: Integer> Iterator for GcdIterator<T> {
+    type Item = GcdIteration<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let prev_iteration = self.i;
         if let Some(i) = self.i {
             self.i = match (i.a, i.b) {
-                (a, b) if a < 0 || b < 0 => {
-                    Some(GcdIteration { a : a.abs(), b : b.abs() })
+                (a, b) if a < T::ZERO || b < T::ZERO => {
+                    let (ua, ub) = (a.unsigned_abs(), b.unsigned_abs());
+                    match (T::checked_from_unsigned(ua), T::checked_from_unsigned(ub)) {
+                        (Some(a), Some(b)) => Some(GcdIteration { a, b }),
+                        _ => {
+                            let (lo, hi) = if ua < ub { (ua, ub) } else { (ub, ua) };
+                            match T::checked_from_unsigned(lo) {
+                                Some(a) if a != T::ZERO => Some(GcdIteration { a, b: T::wrapping_from_unsigned(hi % lo) }),
+                                _ => None
+                            }
+                        }
+                    }
                 },
-                (_, 0) => {
+                (_, b) if b == T::ZERO => {
                     None
                 },
                 (a, b) if a < b => {
@@ -196,48 +554,49 @@ impl Iterator for GcdIterator {
 }
 
 #[derive(PartialEq, Debug, Clone, Copy)]
-pub struct GcdExtendedIteration
+pub struct GcdExtendedIteration<T = i64>
 {
-    pub a: i64,
-    pub b: i64,
-    pub a0: i64,
-    pub a1: i64,
-    pub b0: i64,
-    pub b1: i64,
-    pub q: i64
+    pub a: T,
+    pub b: T,
+    pub a0: T,
+    pub a1: T,
+    pub b0: T,
+    pub b1: T,
+    pub q: T
 }
 
-impl GcdExtendedIteration {
-    pub fn new(mut a: i64, mut b: i64) -> Self {
+impl<T: SignedInteger> GcdExtendedIteration<T> {
+    pub fn new(mut a: T, mut b: T) -> Self {
         (a, b) = (a.abs(), b.abs());
         if a < b {
             (a, b) = (b, a);
         }
-        GcdExtendedIteration { a, b, a0: 1, a1: 0, b0: 0, b1: 1, q: 0 }
+        let (zero, one) = (T::ZERO, T::ONE);
+        GcdExtendedIteration { a, b, a0: one, a1: zero, b0: zero, b1: one, q: zero }
     }
 }
 
 /// Iterates through extended version of the euclid's alorithm for finging greatest common divisor.
 #[derive(PartialEq, Debug, Clone, Copy)]
-pub struct GcdExtendedIterator
+pub struct GcdExtendedIterator<T = i64>
 {
-    i: Option<GcdExtendedIteration>,
+    i: Option<GcdExtendedIteration<T>>,
 }
 
-impl GcdExtendedIterator {
-    pub fn new(a: i64, b: i64) -> Self {
+impl<T: SignedInteger> GcdExtendedIterator<T> {
+    pub fn new(a: T, b: T) -> Self {
         GcdExtendedIterator { i: Some(GcdExtendedIteration::new(a, b))}
     }
 }
 
-impl Iterator for GcdExtendedIterator {
-    type Item = GcdExtendedIteration;
+impl<T: SignedInteger> Iterator for GcdExtendedIterator<T> {
+    type Item = GcdExtendedIteration<T>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let prev_iteration = self.i;
         if let Some(i) = self.i {
             self.i = 
-            if i.b == 0 {
+            if i.b == T::ZERO {
                 None
             } else {
                 let (a, b) = (i.a, i.b);
@@ -254,6 +613,128 @@ impl Iterator for GcdExtendedIterator {
     }
 }
 
+/// Const counterpart of [divides] for `i64`, usable in const contexts.
+pub const fn divides_const(a: i64, b: i64) -> bool
+{
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+    if a != 0 { b % a == 0 } else { true }
+}
+
+/// Const counterpart of [gcd] for `i64`, usable in const contexts.
+pub const fn gcd_const(a: i64, b: i64) -> i64
+{
+    gcd_const_noabs(a.unsigned_abs(), b.unsigned_abs()) as i64
+}
+
+/// Const counterpart of [lcm] for `i64`, usable in const contexts.
+/// Fails to compile if the result overflows when evaluated in a const context.
+pub const fn lcm_const(a: i64, b: i64) -> i64
+{
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    let res = (a / gcd_const_noabs(a, b)) * b;
+    assert!(res <= i64::MAX as u64, "attempt to compute lcm with overflow");
+    res as i64
+}
+
+/// Const counterpart of [gcd_extended] for `i64`, usable in const contexts.
+/// Returns the same solution as [gcd_extended] and panics in the same case.
+pub const fn gcd_extended_const(a: i64, b: i64) -> GcdExtendedResult
+{
+    let (ua, ub) = (a.unsigned_abs(), b.unsigned_abs());
+    let mut res = match (ua, ub) {
+        (0, 0) => return GcdExtendedResult { gcd: 0, x0: 0, y0: 0, x1: 0, y1: 0 },
+        (ua, 0) => GcdExtendedResult { gcd: gcd_fit_const(ua), x0: 1, y0: 0, x1: 0, y1: 0 },
+        (0, ub) => GcdExtendedResult { gcd: gcd_fit_const(ub), x0: 0, y0: 1, x1: 0, y1: 0 },
+        (ua, ub) if ua == ub => GcdExtendedResult { gcd: gcd_fit_const(ua), x0: 1, y0: 0, x1: -1, y1: 1 },
+        (ua, ub) => {
+            let swap = ua < ub;
+            let (g, mut x0, mut y0, odd) = if swap { gcd_extended_magnitudes_const(ub, ua) } else { gcd_extended_magnitudes_const(ua, ub) };
+            if swap {
+                (x0, y0) = (y0, x0);
+            }
+            let mut x1_negative = (odd == swap) != (a < 0);
+            let mut y1_negative = (odd != swap) != (b < 0);
+            let (mx, my) = (ub / g, ua / g);
+            if (!x1_negative && mx > i64::MAX as u64) || (!y1_negative && my > i64::MAX as u64) {
+                x1_negative = !x1_negative;
+                y1_negative = !y1_negative;
+            }
+            return GcdExtendedResult {
+                gcd: gcd_fit_const(g),
+                x0: if a < 0 { -x0 } else { x0 },
+                y0: if b < 0 { -y0 } else { y0 },
+                x1: from_magnitude_const(mx, x1_negative),
+                y1: from_magnitude_const(my, y1_negative)
+            };
+        }
+    };
+    if a < 0 {
+        res.x0 = -res.x0;
+        res.x1 = -res.x1;
+    }
+    if b < 0 {
+        res.y0 = -res.y0;
+        res.y1 = -res.y1;
+    }
+    res
+}
+
+const fn gcd_fit_const(g: u64) -> i64
+{
+    assert!(g <= i64::MAX as u64, "gcd does not fit in the signed type");
+    g as i64
+}
+
+const fn from_magnitude_const(m: u64, negative: bool) -> i64
+{
+    if negative { (m as i64).wrapping_neg() } else { m as i64 }
+}
+
+const fn gcd_const_noabs(mut a: u64, mut b: u64) -> u64
+{
+    if a == 0 {
+        return b;
+    }
+    if b == 0 {
+        return a;
+    }
+    let (za, zb) = (a.trailing_zeros(), b.trailing_zeros());
+    let shift = if za < zb { za } else { zb };
+    a >>= za;
+    b >>= zb;
+    loop {
+        if a > b {
+            (a, b) = (b, a);
+        }
+        b -= a;
+        if b == 0 {
+            return a << shift;
+        }
+        b >>= b.trailing_zeros();
+    }
+}
+
+const fn gcd_extended_magnitudes_const(mut a: u64, mut b: u64) -> (u64, i64, i64, bool)
+{
+    let (mut a0, mut a1, mut b0, mut b1) = (1i64, 0i64, 0i64, 1i64);
+    let mut odd = false;
+    loop {
+        let q = a / b;
+        let r = a % b;
+        (a, b) = (b, r);
+        odd = !odd;
+        if r == 0 {
+            return (a, a1, b1, odd);
+        }
+        let q = q as i64;
+        (a0, a1) = (a1, a0 - q * a1);
+        (b0, b1) = (b1, b0 - q * b1);
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -261,7 +742,7 @@ mod tests {
 
     fn test_divides(a: i64, b: i64, res: bool)
     {
-        assert_eq!(divides(a.clone(), b.clone()), res);
+        assert_eq!(divides(a, b), res);
         assert_eq!(is_divisible_by(b, a), res);
     }
 
@@ -332,6 +813,8 @@ mod tests {
 
 
     fn test_gcd(a: i64, b: i64, res: i64) {
+        assert_eq!(gcd_binary(a, b), res);
+        assert_eq!(gcd_euclid(a, b), res);
         assert_eq!(gcd(a, b), res);
         assert_eq!(gcd(-a, b), res);
         assert_eq!(gcd(a, -b), res);
@@ -405,6 +888,8 @@ mod tests {
 
 
     fn test_lcm(a: i64, b: i64, res: i64) {
+        assert_eq!(checked_lcm(a, b), Some(res));
+        assert_eq!(overflowing_lcm(a, b), (res, false));
         assert_eq!(lcm(a, b), res);
         assert_eq!(lcm(-a, b), res);
         assert_eq!(lcm(a, -b), res);
@@ -500,6 +985,181 @@ mod tests {
         test_lcm(12, 12, 12);
     }
 
+    #[test]
+    fn divides_u8() {
+        assert!(divides(3u8, 255u8));
+        assert!(!divides(2u8, 255u8));
+        assert!(divides(0u8, 0u8));
+    }
+    #[test]
+    fn divides_i128() {
+        assert!(divides(-(1i128 << 100), 1i128 << 120));
+        assert!(!divides(3i128, (1i128 << 120) + 1));
+    }
+    #[test]
+    fn gcd_u8() {
+        assert_eq!(gcd(12u8, 18u8), 6);
+        assert_eq!(gcd(255u8, 0u8), 255);
+    }
+    #[test]
+    fn gcd_i8() {
+        assert_eq!(gcd(-12i8, 18i8), 6);
+        assert_eq!(gcd(127i8, -127i8), 127);
+    }
+    #[test]
+    fn gcd_u16_i16() {
+        assert_eq!(gcd(1071u16, 462u16), 21);
+        assert_eq!(gcd(1071i16, -462i16), 21);
+    }
+    #[test]
+    fn gcd_u32_i32() {
+        assert_eq!(gcd(4_294_967_295u32, 65_535u32), 65_535);
+        assert_eq!(gcd(-2_147_483_646i32, 7i32), 7);
+    }
+    #[test]
+    fn gcd_u64() {
+        assert_eq!(gcd(u64::MAX, 3u64 * 5 * 17), 3 * 5 * 17);
+    }
+    #[test]
+    fn gcd_u128_i128() {
+        assert_eq!(gcd(1u128 << 100, 3u128 << 64), 1u128 << 64);
+        assert_eq!(gcd(-(6i128 << 90), 9i128 << 80), 3i128 << 80);
+    }
+    #[test]
+    fn gcd_usize_isize() {
+        assert_eq!(gcd(48usize, 36usize), 12);
+        assert_eq!(gcd(-48isize, 36isize), 12);
+    }
+    #[test]
+    fn gcd_binary_matches_euclid() {
+        for a in 0..200u32 {
+            for b in 0..200u32 {
+                assert_eq!(gcd_binary(a, b), gcd_euclid(a, b));
+            }
+        }
+    }
+    #[test]
+    fn gcd_binary_powers_of_two() {
+        assert_eq!(gcd_binary(1u64 << 63, 1u64 << 40), 1u64 << 40);
+        assert_eq!(gcd_binary(3u128 << 127, 5u128 << 100), 1u128 << 100);
+        assert_eq!(gcd_binary(-(1i64 << 62), 3i64 << 20), 1i64 << 20);
+    }
+    #[test]
+    fn gcd_binary_u64_max() {
+        assert_eq!(gcd_binary(u64::MAX, u64::MAX - 1), 1);
+        assert_eq!(gcd_binary(u64::MAX, u64::MAX), u64::MAX);
+        assert_eq!(gcd_binary(u128::MAX, 0), u128::MAX);
+    }
+    #[test]
+    fn lcm_u8() {
+        assert_eq!(lcm(15u8, 17u8), 255);
+        assert_eq!(lcm(0u8, 17u8), 0);
+    }
+    #[test]
+    fn lcm_i16() {
+        assert_eq!(lcm(-21i16, 6i16), 42);
+    }
+    #[test]
+    fn lcm_u128() {
+        assert_eq!(lcm(1u128 << 100, 3u128 << 20), 3u128 << 100);
+    }
+    #[test]
+    fn lcm_large_gcd_no_overflow() {
+        assert_eq!(lcm(1u64 << 40, 1u64 << 50), 1u64 << 50);
+        assert_eq!(lcm(1u128 << 100, 3u128 << 64), 3u128 << 100);
+        assert_eq!(lcm(i64::MAX, i64::MAX), i64::MAX);
+    }
+    #[test]
+    fn checked_lcm_overflow() {
+        assert_eq!(checked_lcm(u64::MAX, u64::MAX - 1), None);
+        assert_eq!(checked_lcm(16u8, 17u8), None);
+        assert_eq!(checked_lcm(15u8, 17u8), Some(255));
+        assert_eq!(checked_lcm(-(1i64 << 40), 3i64 << 30), Some(3i64 << 40));
+        assert_eq!(checked_lcm(i64::MAX, 2), None);
+    }
+    #[test]
+    fn overflowing_lcm_overflow() {
+        assert_eq!(overflowing_lcm(16u8, 17u8), (16u8.wrapping_mul(17), true));
+        assert_eq!(overflowing_lcm(0u8, 17u8), (0, false));
+        assert_eq!(overflowing_lcm(i32::MAX, 2), (i32::MAX.wrapping_mul(2), true));
+    }
+    #[test]
+    fn gcd_all_slices() {
+        assert_eq!(gcd_all::<i64>(&[]), 0);
+        assert_eq!(gcd_all(&[-12]), 12);
+        assert_eq!(gcd_all(&[12, -18, 30]), 6);
+        assert_eq!(gcd_all(&[0, 0, 7]), 7);
+        assert_eq!(gcd_all(&[4u8, 6, 9]), 1);
+    }
+    #[test]
+    fn lcm_all_slices() {
+        assert_eq!(lcm_all::<i64>(&[]), 1);
+        assert_eq!(lcm_all(&[-12]), 12);
+        assert_eq!(lcm_all(&[2, -3, 4, 5]), 60);
+        assert_eq!(lcm_all(&[2u32, 0, 5]), 0);
+    }
+    #[test]
+    fn gcd_iterator_ext_early_exit() {
+        let mut it = [6u64, 10, 15, 7, 9].iter().copied();
+        assert_eq!(it.by_ref().take(3).gcd(), 1);
+        assert_eq!(it.next(), Some(7));
+
+        let mut calls = 0;
+        let res = (1..1_000_000u32).inspect(|_| calls += 1).gcd();
+        assert_eq!((res, calls), (1, 1));
+    }
+    #[test]
+    fn lcm_iterator_ext() {
+        assert_eq!((1..=10u64).lcm(), 2520);
+        assert_eq!([3i128, 0, 5].iter().copied().lcm(), 0);
+        assert_eq!((1..=20).map(|x| x * 6).gcd(), 6);
+    }
+    #[test]
+    fn divides_i64_min() {
+        assert!(divides(-1, i64::MIN));
+        assert!(divides(i64::MIN, i64::MIN));
+        assert!(divides(1i64 << 62, i64::MIN));
+        assert!(!divides(i64::MIN, 1i64 << 62));
+        assert!(divides(i64::MIN, 0));
+        assert!(!divides(3, i64::MIN));
+    }
+    #[test]
+    fn gcd_i64_min() {
+        assert_eq!(gcd(i64::MIN, 6), 2);
+        assert_eq!(gcd(i64::MIN, -6), 2);
+        assert_eq!(gcd(i64::MIN, i64::MAX), 1);
+        assert_eq!(gcd(i64::MIN, 1i64 << 62), 1i64 << 62);
+        assert_eq!(gcd_euclid(i64::MIN, 1i64 << 62), 1i64 << 62);
+        assert_eq!(gcd(i64::MIN, 0), i64::MIN);
+        assert_eq!(gcd(i8::MIN, i8::MIN), i8::MIN);
+        assert_eq!(gcd_all(&[i64::MIN, 12, -18]), 2);
+    }
+    #[test]
+    fn unsigned_gcd_exact() {
+        assert_eq!(unsigned_gcd(i64::MIN, 0), 1u64 << 63);
+        assert_eq!(unsigned_gcd(i64::MIN, i64::MIN), 1u64 << 63);
+        assert_eq!(unsigned_gcd(0, i128::MIN), 1u128 << 127);
+        assert_eq!(unsigned_gcd(-12i8, 18i8), 6u8);
+        assert_eq!(unsigned_gcd(u64::MAX, 0), u64::MAX);
+    }
+    #[test]
+    fn lcm_i64_min() {
+        assert_eq!(checked_lcm(i64::MIN, 2), None);
+        assert_eq!(checked_lcm(i64::MIN, 1), None);
+        assert_eq!(checked_lcm(i64::MIN, 0), Some(0));
+        assert_eq!(overflowing_lcm(i64::MIN, -1), (i64::MIN, true));
+        assert_eq!(checked_lcm(i64::MIN / 2, 2), Some(1i64 << 62));
+        assert_eq!(lcm(i64::MIN / 2, -4), 1i64 << 62);
+        assert_eq!(checked_lcm(i8::MIN, 3), None);
+    }
+    #[test]
+    fn common_divisor_multiple_u32() {
+        assert!(is_common_divisor(7u32, 14u32, 21u32));
+        assert!(!is_common_divisor(7u32, 14u32, 22u32));
+        assert!(is_common_multiple(42u32, 14u32, 21u32));
+        assert!(!is_common_multiple(21u32, 14u32, 21u32));
+    }
+
     fn test_gcd_extended(a: i64, b: i64, res: GcdExtendedResult) {
         assert_eq!(gcd_extended( a,  b), res);
         assert_eq!(gcd_extended(-a,  b), GcdExtendedResult { gcd: res.gcd, x0: -res.x0, y0:  res.y0, x1: -res.x1, y1:  res.y1});
@@ -572,6 +1232,192 @@ mod tests {
         test_gcd_extended(12, 18, GcdExtendedResult { gcd: 6, x0: -1, y0: 1, x1: 3, y1: -2});
     }
 
+    #[test]
+    fn gcd_extended_i128() {
+        let (a, b) = (3i128 << 100, 5i128 << 90);
+        let res = gcd_extended(a, b);
+        assert_eq!(res.gcd, 1i128 << 90);
+        assert_eq!(res.x0 * a + res.y0 * b, res.gcd);
+        assert_eq!(res.x1 * a + res.y1 * b, 0);
+    }
+    #[test]
+    fn gcd_extended_i8() {
+        assert_eq!(gcd_extended(-12i8, 18i8), GcdExtendedResult { gcd: 6, x0: 1, y0: 1, x1: -3, y1: -2 });
+    }
+    #[test]
+    fn gcd_extended_i8_exhaustive() {
+        for a in i8::MIN..=i8::MAX {
+            for b in i8::MIN..=i8::MAX {
+                if gcd(a as i16, b as i16) > i8::MAX as i16 {
+                    continue;
+                }
+                let res = gcd_extended(a, b);
+                let (a, b) = (a as i32, b as i32);
+                assert_eq!(res.gcd as i32, gcd(a, b));
+                assert_eq!(res.x0 as i32 * a + res.y0 as i32 * b, res.gcd as i32);
+                assert_eq!(res.x1 as i32 * a + res.y1 as i32 * b, 0);
+                if a != 0 && b != 0 {
+                    assert_eq!((res.x1 as i32).abs(), b.abs() / res.gcd as i32);
+                }
+            }
+        }
+    }
+    #[test]
+    fn gcd_extended_i64_min() {
+        for (a, b) in [(i64::MIN, 1), (1, i64::MIN), (i64::MIN, -1), (i64::MIN, 3), (-6, i64::MIN), (i64::MIN, i64::MAX)] {
+            let res = gcd_extended(a, b);
+            let (a, b) = (a as i128, b as i128);
+            assert_eq!(res.gcd as i128, gcd(a, b));
+            assert_eq!(res.x0 as i128 * a + res.y0 as i128 * b, res.gcd as i128);
+            assert_eq!(res.x1 as i128 * a + res.y1 as i128 * b, 0);
+        }
+        assert_eq!(gcd_extended(i64::MIN, 1), GcdExtendedResult { gcd: 1, x0: 0, y0: 1, x1: -1, y1: i64::MIN });
+    }
+    #[test]
+    #[should_panic(expected = "gcd does not fit")]
+    fn gcd_extended_i64_min_0() {
+        gcd_extended(i64::MIN, 0);
+    }
+    #[test]
+    fn gcd_extended_i64_as_i128() {
+        let (a, b) = (i64::MAX as i128, (i64::MAX - 1) as i128);
+        let res = gcd_extended(a, b);
+        assert_eq!(res.gcd, 1);
+        assert_eq!(res.x0 * a + res.y0 * b, 1);
+    }
+    #[test]
+    fn lcm_i64_as_i128() {
+        let (a, b) = (i64::MAX as i128, (i64::MAX - 1) as i128);
+        assert_eq!(lcm(a, b), a * b);
+        assert_eq!(lcm(u64::MAX as u128, 2), 2 * u64::MAX as u128);
+    }
+
+    #[test]
+    fn gcd_extended_minimal_brute_force() {
+        for a in -30..=30i64 {
+            for b in -30..=30i64 {
+                let res = gcd_extended_minimal(a, b);
+                let g = gcd(a, b);
+                assert_eq!(res.gcd, g);
+                assert_eq!(res.x0 * a + res.y0 * b, g);
+                assert_eq!(res.x1 * a + res.y1 * b, 0);
+                if g != 0 && b != 0 {
+                    assert!(0 <= res.x0 && res.x0 < b.abs() / g);
+                    assert_eq!((res.x1, res.y1), (b / g, -a / g));
+                }
+            }
+        }
+    }
+    #[test]
+    fn gcd_extended_minimal_values() {
+        assert_eq!(gcd_extended_minimal(0, 0), GcdExtendedResult { gcd: 0, x0: 0, y0: 0, x1: 0, y1: 0 });
+        assert_eq!(gcd_extended_minimal(-5, 0), GcdExtendedResult { gcd: 5, x0: -1, y0: 0, x1: 0, y1: 1 });
+        assert_eq!(gcd_extended_minimal(0, -5), GcdExtendedResult { gcd: 5, x0: 0, y0: -1, x1: -1, y1: 0 });
+        assert_eq!(gcd_extended_minimal(2, 3), GcdExtendedResult { gcd: 1, x0: 2, y0: -1, x1: 3, y1: -2 });
+        assert_eq!(gcd_extended_minimal(2, -3), GcdExtendedResult { gcd: 1, x0: 2, y0: 1, x1: -3, y1: -2 });
+        assert_eq!(gcd_extended_minimal(12, 18), GcdExtendedResult { gcd: 6, x0: 2, y0: -1, x1: 3, y1: -2 });
+        assert_eq!(gcd_extended_minimal(18, 6), GcdExtendedResult { gcd: 6, x0: 0, y0: 1, x1: 1, y1: -3 });
+    }
+    #[test]
+    fn gcd_extended_minimal_large() {
+        let (a, b) = (i64::MAX, i64::MAX - 1);
+        let res = gcd_extended_minimal(a as i128, b as i128);
+        assert_eq!(res.gcd, 1);
+        assert!(0 <= res.x0 && res.x0 < b as i128);
+        assert_eq!(res.x0 * a as i128 + res.y0 * b as i128, 1);
+    }
+
+    #[test]
+    fn const_fns_match_generic() {
+        for a in -40..=40i64 {
+            for b in -40..=40i64 {
+                assert_eq!(divides_const(a, b), divides(a, b));
+                assert_eq!(gcd_const(a, b), gcd(a, b));
+                assert_eq!(lcm_const(a, b), lcm(a, b));
+                assert_eq!(gcd_extended_const(a, b), gcd_extended(a, b));
+            }
+        }
+        assert_eq!(gcd_const(i64::MIN, 6), 2);
+        assert!(divides_const(-1, i64::MIN));
+        for (a, b) in [(i64::MIN, 1), (-1, i64::MIN), (i64::MIN, 3), (i64::MIN, i64::MAX), (i64::MAX, i64::MIN + 1)] {
+            assert_eq!(gcd_extended_const(a, b), gcd_extended(a, b));
+        }
+    }
+    #[test]
+    fn const_fns_in_const_context() {
+        const G: i64 = gcd_const(12, -18);
+        const L: i64 = lcm_const(4, 6);
+        const D: bool = divides_const(7, 49);
+        const E: GcdExtendedResult = gcd_extended_const(240, 46);
+        let table = [0u8; lcm_const(4, 6) as usize];
+        assert_eq!((G, L, D), (6, 12, true));
+        assert_eq!(table.len(), 12);
+        assert_eq!(E.gcd, 2);
+        assert_eq!(E.x0 * 240 + E.y0 * 46, 2);
+    }
+
+    fn test_gcd_extended_many(a: &[i64], res: i64) {
+        let (g, c) = gcd_extended_many(a);
+        assert_eq!(g, res);
+        assert_eq!(c.len(), a.len());
+        assert_eq!(a.iter().zip(c.iter()).map(|(a, c)| a * c).sum::<i64>(), g);
+    }
+
+    #[test]
+    fn gcd_extended_many_empty() {
+        assert_eq!(gcd_extended_many::<i64>(&[]), (0, vec![]));
+    }
+    #[test]
+    fn gcd_extended_many_single() {
+        assert_eq!(gcd_extended_many(&[-7]), (7, vec![-1]));
+        assert_eq!(gcd_extended_many(&[0]), (0, vec![0]));
+    }
+    #[test]
+    fn gcd_extended_many_pair() {
+        test_gcd_extended_many(&[6, 10], 2);
+        test_gcd_extended_many(&[-9, 12], 3);
+    }
+    #[test]
+    fn gcd_extended_many_triple() {
+        test_gcd_extended_many(&[6, 10, 15], 1);
+        test_gcd_extended_many(&[12, -18, 30], 6);
+        test_gcd_extended_many(&[0, 0, 14], 14);
+    }
+    #[test]
+    fn gcd_extended_many_longer() {
+        test_gcd_extended_many(&[1001, 770, 455, 390, 143], 1);
+        test_gcd_extended_many(&[360, 840, -1260, 0, 2100, 3960], 60);
+    }
+    #[test]
+    fn gcd_extended_many_large() {
+        let a = [2000000000000000006i64, 1555555555555555554, 4611686018427387905];
+        let (g, c) = gcd_extended_many(&a);
+        assert_eq!(g, 1);
+        assert_eq!(a.iter().zip(&c).map(|(&ai, &ci)| ai as i128 * ci as i128).sum::<i128>(), 1);
+        assert!(c[..2].iter().all(|&ci| ci.unsigned_abs() <= a[2].unsigned_abs() / 2));
+        let a = [i64::MAX, i64::MIN, i64::MIN + 2, 3, -(1 << 61) - 1];
+        let (g, c) = gcd_extended_many(&a);
+        assert_eq!(g, 1);
+        assert_eq!(a.iter().zip(&c).map(|(&ai, &ci)| ai as i128 * ci as i128).sum::<i128>(), 1);
+    }
+    #[test]
+    fn gcd_extended_many_brute_force() {
+        for a in -12..=12i64 {
+            for b in -12..=12i64 {
+                for d in [-10, 0, 7, 15] {
+                    test_gcd_extended_many(&[a, b, d, 0, a + b], gcd(gcd(a, b), d));
+                }
+            }
+        }
+    }
+    #[test]
+    fn gcd_extended_many_i128() {
+        let a = [6i128 << 80, 10i128 << 80, 15i128 << 80];
+        let (g, c) = gcd_extended_many(&a);
+        assert_eq!(g, 1i128 << 80);
+        assert_eq!(a[0] * c[0] + a[1] * c[1] + a[2] * c[2], g);
+    }
+
     #[test]
     fn gcd_iterator_0_0() {
         let mut gcd_it = GcdIterator::new(0, 0);
@@ -609,6 +1455,34 @@ mod tests {
         assert_eq!(gcd_it.next(), None);
     }
     
+    #[test]
+    fn gcd_iterator_u128() {
+        let mut gcd_it = GcdIterator::new(9u128 << 100, 12u128 << 100);
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a:  9 << 100, b: 12 << 100 }));
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a: 12 << 100, b:  9 << 100 }));
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a:  9 << 100, b:  3 << 100 }));
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a:  3 << 100, b:  0 }));
+        assert_eq!(gcd_it.next(), None);
+    }
+
+    #[test]
+    fn gcd_iterator_i64_min() {
+        let mut gcd_it = GcdIterator::new(i64::MIN, 3);
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a: i64::MIN, b: 3 }));
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a: 3, b: 2 }));
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a: 2, b: 1 }));
+        assert_eq!(gcd_it.next(), Some(GcdIteration{ a: 1, b: 0 }));
+        assert_eq!(gcd_it.next(), None);
+        assert_eq!(GcdIterator::new(-4, i64::MIN).nth(1), Some(GcdIteration{ a: 4, b: 0 }));
+        assert_eq!(GcdIterator::new(i64::MIN, 0).count(), 1);
+    }
+
+    #[test]
+    fn gcd_extended_iterator_i128() {
+        let last = GcdExtendedIterator::new(9i128 << 100, -(12i128 << 100)).last().unwrap();
+        assert_eq!(last, GcdExtendedIteration{ a: 3 << 100, b: 0, a0: 1, a1: -3, b0: -1, b1: 4, q: 3 });
+    }
+
     #[test]
     fn gcd_extended_iterator_0_0() {
         let mut gcd_it = GcdExtendedIterator::new(0, 0);
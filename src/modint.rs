@@ -0,0 +1,429 @@
+use std::fmt;
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+
+use crate::{gcd_extended, mod_mul, mod_pow};
+use crate::wide::mul_high;
+
+/// Integer modulo `M`, where the modulus is known at compile time.
+///
+/// The value is always kept reduced in `[0, M)`. Every `u64` modulus except 0 is supported.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+pub struct ModInt<const M: u64>
+{
+    value: u64
+}
+
+impl<const M: u64> ModInt<M> {
+    pub const MODULUS: u64 = M;
+
+    pub fn new(value: u64) -> Self {
+        ModInt { value: value % M }
+    }
+
+    /// Returns the representative of `self` in `[0, M)`.
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    /// Computes `self^exp`.
+    pub fn pow(self, exp: u64) -> Self {
+        ModInt { value: mod_pow(self.value, exp, M) }
+    }
+
+    /// Computes the multiplicative inverse of `self`.
+    /// Returns `None` if `self` is not coprime with `M`.
+    pub fn inverse(self) -> Option<Self> {
+        Some(ModInt { value: inverse_u64(self.value, M)? })
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        ModInt::new(value)
+    }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(value: i64) -> Self {
+        ModInt { value: (value as i128).rem_euclid(M as i128) as u64 }
+    }
+}
+
+impl<const M: u64> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let (sum, overflow) = self.value.overflowing_add(rhs.value);
+        let value = if overflow || sum >= M { sum.wrapping_sub(M) } else { sum };
+        ModInt { value }
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let value = if self.value >= rhs.value {
+            self.value - rhs.value
+        } else {
+            self.value.wrapping_sub(rhs.value).wrapping_add(M)
+        };
+        ModInt { value }
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        ModInt { value: mod_mul(self.value, rhs.value, M) }
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = Self;
+
+    /// Panics if `rhs` is not invertible.
+    fn div(self, rhs: Self) -> Self {
+        let inv = rhs.inverse().expect("division by an element that is not invertible");
+        ModInt { value: mod_mul(self.value, inv.value, M) }
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        ModInt { value: if self.value == 0 { 0 } else { M - self.value } }
+    }
+}
+
+macro_rules! impl_modint_assign {
+    ($($trait:ident $method:ident $op:tt)*) => {$(
+        impl<const M: u64> $trait for ModInt<M> {
+            fn $method(&mut self, rhs: Self) {
+                *self = *self $op rhs;
+            }
+        }
+    )*}
+}
+
+impl_modint_assign!(AddAssign add_assign + SubAssign sub_assign - MulAssign mul_assign * DivAssign div_assign /);
+
+/// Modulus known only at runtime, together with a precomputed Barrett constant.
+///
+/// Reduction uses two multiplications instead of a division, which makes [ModContext::mul]
+/// and [ModContext::pow] faster than [mod_mul] and [mod_pow]. Every `u64` modulus except 0 is supported.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct ModContext
+{
+    modulus: u64,
+    barrett: u128
+}
+
+impl ModContext {
+    /// Panics if `modulus` is zero.
+    pub fn new(modulus: u64) -> Self {
+        assert!(modulus != 0, "modulus must be nonzero");
+        ModContext { modulus, barrett: u128::MAX / modulus as u128 }
+    }
+
+    pub fn modulus(&self) -> u64 {
+        self.modulus
+    }
+
+    /// Computes `x mod m` for any `x`.
+    pub fn reduce(&self, x: u128) -> u64 {
+        // q is at most 2 less than floor(x / m), so at most two corrections are needed.
+        let m = self.modulus as u128;
+        let q = mul_high(x, self.barrett);
+        let mut r = x - q * m;
+        while r >= m {
+            r -= m;
+        }
+        r as u64
+    }
+
+    /// Computes `a + b mod m`.
+    pub fn add(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 + b as u128)
+    }
+
+    /// Computes `a - b mod m`.
+    pub fn sub(&self, a: u64, b: u64) -> u64 {
+        let (a, b) = (self.reduce(a as u128), self.reduce(b as u128));
+        if a >= b { a - b } else { (a as u128 + self.modulus as u128 - b as u128) as u64 }
+    }
+
+    /// Computes `a * b mod m`.
+    pub fn mul(&self, a: u64, b: u64) -> u64 {
+        self.reduce(a as u128 * b as u128)
+    }
+
+    /// Computes `base^exp mod m`. We define `0^0 = 1`.
+    pub fn pow(&self, base: u64, mut exp: u64) -> u64 {
+        let mut base = self.reduce(base as u128);
+        let mut res = self.reduce(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = self.mul(res, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        res
+    }
+
+    /// Computes the inverse of `a` modulo `m`.
+    /// Returns `None` if `a` is not coprime with `m`.
+    pub fn inv(&self, a: u64) -> Option<u64> {
+        inverse_u64(self.reduce(a as u128), self.modulus)
+    }
+
+    /// Wraps `value` into an element of this context.
+    pub fn element(&self, value: u64) -> DynModInt<'_> {
+        DynModInt { value: self.reduce(value as u128), ctx: self }
+    }
+}
+
+/// Integer modulo a runtime modulus, bound to its [ModContext].
+///
+/// The value is always kept reduced in `[0, m)`. Combining elements of different contexts is a logic error.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct DynModInt<'a>
+{
+    value: u64,
+    ctx: &'a ModContext
+}
+
+impl<'a> DynModInt<'a> {
+    /// Returns the representative of `self` in `[0, m)`.
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn context(self) -> &'a ModContext {
+        self.ctx
+    }
+
+    /// Computes `self^exp`.
+    pub fn pow(self, exp: u64) -> Self {
+        DynModInt { value: self.ctx.pow(self.value, exp), ctx: self.ctx }
+    }
+
+    /// Computes the multiplicative inverse of `self`.
+    /// Returns `None` if `self` is not coprime with the modulus.
+    pub fn inverse(self) -> Option<Self> {
+        Some(DynModInt { value: self.ctx.inv(self.value)?, ctx: self.ctx })
+    }
+}
+
+impl fmt::Display for DynModInt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+macro_rules! impl_dynmodint_op {
+    ($($trait:ident $method:ident $assign_trait:ident $assign_method:ident)*) => {$(
+        impl<'a> $trait for DynModInt<'a> {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                debug_assert_eq!(self.ctx.modulus, rhs.ctx.modulus, "elements of different contexts");
+                DynModInt { value: self.ctx.$method(self.value, rhs.value), ctx: self.ctx }
+            }
+        }
+
+        impl<'a> $assign_trait for DynModInt<'a> {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = $trait::$method(*self, rhs);
+            }
+        }
+    )*}
+}
+
+impl_dynmodint_op!(Add add AddAssign add_assign Sub sub SubAssign sub_assign Mul mul MulAssign mul_assign);
+
+impl<'a> Div for DynModInt<'a> {
+    type Output = Self;
+
+    /// Panics if `rhs` is not invertible.
+    fn div(self, rhs: Self) -> Self {
+        let inv = rhs.inverse().expect("division by an element that is not invertible");
+        DynModInt { value: self.ctx.mul(self.value, inv.value), ctx: self.ctx }
+    }
+}
+
+impl<'a> DivAssign for DynModInt<'a> {
+    fn div_assign(&mut self, rhs: Self) {
+        *self = *self / rhs;
+    }
+}
+
+impl<'a> Neg for DynModInt<'a> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        DynModInt { value: self.ctx.sub(0, self.value), ctx: self.ctx }
+    }
+}
+
+/// Computes the inverse of `a` modulo `m` for any `u64` modulus.
+fn inverse_u64(a: u64, m: u64) -> Option<u64>
+{
+    let res = gcd_extended(a as i128, m as i128);
+    if res.gcd != 1 {
+        return None;
+    }
+    Some(res.x0.rem_euclid(m as i128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    type Mint = ModInt<998_244_353>;
+    type Small = ModInt<12>;
+    type Huge = ModInt<18_446_744_073_709_551_557>;
+
+    #[test]
+    fn modint_new() {
+        assert_eq!(Mint::new(998_244_354).value(), 1);
+        assert_eq!(Mint::from(-1i64).value(), 998_244_352);
+        assert_eq!(Mint::from(i64::MIN).value(), (i64::MIN as i128).rem_euclid(998_244_353) as u64);
+        assert_eq!(Mint::MODULUS, 998_244_353);
+    }
+    #[test]
+    fn modint_arithmetic_small() {
+        for a in 0..12u64 {
+            for b in 0..12u64 {
+                let (x, y) = (Small::new(a), Small::new(b));
+                assert_eq!((x + y).value(), (a + b) % 12);
+                assert_eq!((x - y).value(), (a + 12 - b) % 12);
+                assert_eq!((x * y).value(), a * b % 12);
+                assert_eq!((-x).value(), (12 - a) % 12);
+            }
+        }
+    }
+    #[test]
+    fn modint_division() {
+        let a = Mint::new(5);
+        let b = Mint::new(7);
+        assert_eq!(a / b * b, a);
+        assert_eq!(Small::new(5).inverse(), Some(Small::new(5)));
+        assert_eq!(Small::new(4).inverse(), None);
+    }
+    #[test]
+    #[should_panic]
+    fn modint_division_not_invertible() {
+        let _ = Small::new(1) / Small::new(6);
+    }
+    #[test]
+    fn modint_pow() {
+        assert_eq!(Mint::new(3).pow(998_244_352), Mint::new(1));
+        assert_eq!(Mint::new(2).pow(10).value(), 1024);
+        assert_eq!(Mint::new(0).pow(0).value(), 1);
+    }
+    #[test]
+    fn modint_huge_modulus() {
+        let a = Huge::new(u64::MAX);
+        let b = Huge::new(u64::MAX - 1);
+        assert_eq!(a.value(), 58);
+        assert_eq!((a + Huge::new(18_446_744_073_709_551_500)).value(), 1);
+        assert_eq!((Huge::new(1) - a).value(), 18_446_744_073_709_551_557 - 57);
+        assert_eq!((a * b).value(), 58 * 57);
+        assert_eq!(a / b * b, a);
+        assert_eq!(a.inverse().unwrap() * a, Huge::new(1));
+    }
+    #[test]
+    fn modint_assign_ops() {
+        let mut a = Mint::new(10);
+        a += Mint::new(5);
+        a -= Mint::new(20);
+        a *= Mint::new(3);
+        a /= Mint::new(3);
+        assert_eq!(a, Mint::from(-5i64));
+    }
+    #[test]
+    fn modint_display() {
+        assert_eq!(Mint::from(-1i64).to_string(), "998244352");
+        assert_eq!(format!("{:>5}", Small::new(7)), "    7");
+    }
+
+    #[test]
+    fn mod_context_reduce() {
+        for &m in &[1u64, 2, 3, 7, 1_000_000_007, (1 << 32) - 1, 1 << 32, (1 << 63) + 1, u64::MAX - 1, u64::MAX] {
+            let ctx = ModContext::new(m);
+            for &x in &[0u128, 1, m as u128 - 1, m as u128, m as u128 + 1, (m as u128) * (m as u128) - 1, u128::MAX, u128::MAX / 3] {
+                assert_eq!(ctx.reduce(x) as u128, x % m as u128);
+            }
+        }
+    }
+    #[test]
+    fn mod_context_matches_mod_mul() {
+        for &m in &[1u64, 12, 998_244_353, 18_446_744_073_709_551_557, u64::MAX] {
+            let ctx = ModContext::new(m);
+            let mut x = 0x9e37_79b9_7f4a_7c15u64;
+            for _ in 0..1000 {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                let y = x.rotate_left(29);
+                assert_eq!(ctx.mul(x, y), mod_mul(x, y, m));
+                assert_eq!(ctx.add(x, y) as u128, (x as u128 + y as u128) % m as u128);
+                assert_eq!(ctx.sub(x, y) as i128, (x as i128 - y as i128).rem_euclid(m as i128));
+                assert_eq!(ctx.pow(x, y), mod_pow(x, y, m));
+            }
+        }
+    }
+    #[test]
+    fn mod_context_inv() {
+        let ctx = ModContext::new(12);
+        assert_eq!(ctx.inv(5), Some(5));
+        assert_eq!(ctx.inv(17), Some(5));
+        assert_eq!(ctx.inv(4), None);
+        let ctx = ModContext::new(u64::MAX);
+        let x = ctx.inv(2).unwrap();
+        assert_eq!(ctx.mul(x, 2), 1);
+    }
+    #[test]
+    #[should_panic]
+    fn mod_context_zero_modulus() {
+        ModContext::new(0);
+    }
+    #[test]
+    fn dyn_modint_arithmetic() {
+        let ctx = ModContext::new(1_000_000_007);
+        let a = ctx.element(5);
+        let b = ctx.element(1_000_000_010);
+        assert_eq!(b.value(), 3);
+        assert_eq!((a + b).value(), 8);
+        assert_eq!((b - a).value(), 1_000_000_005);
+        assert_eq!((a * b).value(), 15);
+        assert_eq!((-a).value(), 1_000_000_002);
+        assert_eq!(a / b * b, a);
+        assert_eq!(a.pow(1_000_000_006).value(), 1);
+        assert_eq!(a.inverse().unwrap() * a, ctx.element(1));
+        assert_eq!(a.context().modulus(), 1_000_000_007);
+    }
+    #[test]
+    fn dyn_modint_assign_ops() {
+        let ctx = ModContext::new(12);
+        let mut a = ctx.element(10);
+        a += ctx.element(5);
+        a -= ctx.element(7);
+        a *= ctx.element(5);
+        a /= ctx.element(5);
+        assert_eq!(a.value(), 8);
+        assert_eq!(ctx.element(0).inverse(), None);
+        assert_eq!(ctx.element(7).to_string(), "7");
+    }
+}
@@ -0,0 +1,393 @@
+use crate::{gcd_extended, totient};
+
+/// Computes `a*b mod m` without overflow.
+///
+/// The product is computed in `u128`, so the result is correct for every `u64` input.
+///
+/// Panics if `m` is zero.
+pub fn mod_mul(a: u64, b: u64, m: u64) -> u64
+{
+    assert!(m != 0, "modulus must be nonzero");
+    (a as u128 * b as u128 % m as u128) as u64
+}
+
+/// Computes `base^exp mod modulus` using square-and-multiply.
+///
+/// Multiplications go through [mod_mul], so the result is correct for every `u64` input.
+/// We define `0^0 = 1`.
+///
+/// Panics if `modulus` is zero.
+pub fn mod_pow(base: u64, mut exp: u64, modulus: u64) -> u64
+{
+    assert!(modulus != 0, "modulus must be nonzero");
+    let mut base = base % modulus;
+    let mut res = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = mod_mul(res, base, modulus);
+        }
+        base = mod_mul(base, base, modulus);
+        exp >>= 1;
+    }
+    res
+}
+
+/// Computes the inverse of `a` modulo `m`, that is `x` in `[0, m)` satisfying `a*x ≡ 1 (mod m)`.
+///
+/// Returns `None` if `gcd(a, m) != 1` or if `m` is not positive.
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64>
+{
+    if m <= 0 {
+        return None;
+    }
+    let res = gcd_extended(a.rem_euclid(m), m);
+    if res.gcd != 1 {
+        return None;
+    }
+    Some(res.x0.rem_euclid(m))
+}
+
+/// Computes the inverses of all elements of `a` modulo `m`, each normalized into `[0, m)`.
+///
+/// Uses the prefix product trick, so only one extended gcd and `O(n)` multiplications are needed.
+/// Returns `None` if some element is not coprime with `m` or if `m` is not positive.
+pub fn mod_inverse_batch(a: &[i64], m: i64) -> Option<Vec<i64>>
+{
+    if m <= 0 {
+        return None;
+    }
+    let mu = m as u64;
+    let a: Vec<u64> = a.iter().map(|&x| x.rem_euclid(m) as u64).collect();
+    let mut prefix = Vec::with_capacity(a.len() + 1);
+    prefix.push(1 % mu);
+    for (i, &x) in a.iter().enumerate() {
+        prefix.push(mod_mul(prefix[i], x, mu));
+    }
+    let mut inv = mod_inverse(prefix[a.len()] as i64, m)? as u64;
+    let mut res = vec![0; a.len()];
+    for i in (0..a.len()).rev() {
+        res[i] = mod_mul(inv, prefix[i], mu) as i64;
+        inv = mod_mul(inv, a[i], mu);
+    }
+    Some(res)
+}
+
+/// Solution set of a linear congruence `a*x ≡ b (mod m)`.
+///
+/// The solutions are exactly the integers congruent to `x` modulo `modulus`,
+/// which gives `count` distinct solutions modulo `m = count * modulus`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct LinearCongruenceSolution
+{
+    pub x: i64,
+    pub modulus: i64,
+    pub count: i64
+}
+
+impl LinearCongruenceSolution {
+    /// Iterates through all solutions in `[0, m)` in increasing order.
+    pub fn solutions(&self) -> impl Iterator<Item = i64> {
+        let (x, modulus) = (self.x, self.modulus);
+        (0..self.count).map(move |k| x + k * modulus)
+    }
+}
+
+/// Solves the linear congruence `a*x ≡ b (mod m)`.
+///
+/// Returns `None` if there is no solution or if `m` is not positive.
+/// Otherwise `x` of the result is the smallest non-negative solution.
+pub fn solve_linear_congruence(a: i64, b: i64, m: i64) -> Option<LinearCongruenceSolution>
+{
+    if m <= 0 {
+        return None;
+    }
+    let (a, b) = (a.rem_euclid(m), b.rem_euclid(m));
+    let res = gcd_extended(a, m);
+    let g = res.gcd;
+    if b % g != 0 {
+        return None;
+    }
+    let modulus = m / g;
+    let x = (res.x0 as i128 * (b / g) as i128).rem_euclid(modulus as i128) as i64;
+    Some(LinearCongruenceSolution { x, modulus, count: g })
+}
+
+/// Computes the power tower `a1^(a2^(a3^...)) mod modulus` for `tower = [a1, a2, a3, ...]`, evaluated from the top.
+///
+/// Exponents are reduced by the generalized Euler theorem `a^x ≡ a^(φ(m) + x mod φ(m)) (mod m)`, which holds
+/// for every `x >= log2(m)` even when `a` is not coprime with `m`, so the exponent of each level is computed
+/// modulo the iterated totient together with whether its exact value reaches [u64::BITS].
+/// The iterated totient reaches `1` after `O(log m)` levels, so only these levels are reduced modulo
+/// and the levels above them only contribute their exact value capped at [u64::BITS], evaluated iteratively from the top.
+/// This takes linear time in the height without recursion, so towers of any height are cheap.
+/// We define `0^0 = 1` and the empty tower as `1`.
+///
+/// Panics if `modulus` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(power_tower_mod(&[2, 3, 2], 1000), 512);
+/// assert_eq!(power_tower_mod(&[2, 2, 2, 2, 2], 1_000_000), 156_736);
+/// assert_eq!(power_tower_mod(&[3; 100], 100), 87);
+/// ```
+pub fn power_tower_mod(tower: &[u64], modulus: u64) -> u64
+{
+    assert!(modulus != 0, "modulus must be nonzero");
+    if tower.is_empty() {
+        return 1 % modulus;
+    }
+    // level j is reduced modulo the j-th iterated totient, levels from the first modulus 1 on are all 0
+    let mut moduli = vec![modulus];
+    while moduli[moduli.len() - 1] > 1 && moduli.len() < tower.len() {
+        moduli.push(totient(moduli[moduli.len() - 1]));
+    }
+    let mut exp = tower[moduli.len()..].iter().rev().fold(1, |exp, &base| capped_power(base, exp));
+    let mut res = 0;
+    for (&base, (j, &m)) in tower.iter().zip(moduli.iter().enumerate()).rev() {
+        res = if m == 1 {
+            0
+        } else if exp < TOWER_CAP {
+            mod_pow(base, exp, m)
+        } else {
+            let phi = moduli[j + 1];
+            mod_mul(mod_pow(base, res, m), mod_pow(base, phi, m), m)
+        };
+        exp = capped_power(base, exp);
+    }
+    res
+}
+
+/// Bound on exact values of power towers, at least the binary logarithm of every `u64` modulus.
+const TOWER_CAP: u64 = u64::BITS as u64;
+
+/// Computes the exact value of `base^exp` or [TOWER_CAP] if it is greater or equal, where `exp <= TOWER_CAP`.
+fn capped_power(base: u64, exp: u64) -> u64
+{
+    match (base, exp) {
+        (_, 0) => 1,
+        (0, _) | (1, _) => base,
+        _ => base.checked_pow(exp as u32).map_or(TOWER_CAP, |v| v.min(TOWER_CAP))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn mod_mul_small() {
+        for m in 1..30u64 {
+            for a in 0..40u64 {
+                for b in 0..40u64 {
+                    assert_eq!(mod_mul(a, b, m), a * b % m);
+                }
+            }
+        }
+    }
+    #[test]
+    fn mod_mul_large() {
+        assert_eq!(mod_mul(u64::MAX, u64::MAX, u64::MAX), 0);
+        assert_eq!(mod_mul(u64::MAX - 1, u64::MAX - 1, u64::MAX), 1);
+        assert_eq!(mod_mul(1 << 32, 1 << 32, (1 << 63) + 1), (1 << 63) - 1);
+        assert_eq!(mod_mul(1 << 40, 1 << 40, 1_000_000_007), ((1u128 << 80) % 1_000_000_007) as u64);
+        assert_eq!(mod_mul(123, 456, 1), 0);
+    }
+    #[test]
+    #[should_panic]
+    fn mod_mul_zero_modulus() {
+        mod_mul(2, 3, 0);
+    }
+
+    fn mod_pow_naive(base: u64, exp: u64, modulus: u64) -> u64 {
+        let mut res = 1 % modulus;
+        for _ in 0..exp {
+            res = res * (base % modulus) % modulus;
+        }
+        res
+    }
+
+    #[test]
+    fn mod_pow_small() {
+        for m in 1..20 {
+            for b in 0..20 {
+                for e in 0..20 {
+                    assert_eq!(mod_pow(b, e, m), mod_pow_naive(b, e, m));
+                }
+            }
+        }
+    }
+    #[test]
+    fn mod_pow_zero_exponent() {
+        assert_eq!(mod_pow(0, 0, 7), 1);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+    }
+    #[test]
+    fn mod_pow_fermat() {
+        let p = 1_000_000_007;
+        assert_eq!(mod_pow(2, p - 1, p), 1);
+        assert_eq!(mod_pow(123_456_789, p - 2, p) * 123_456_789 % p, 1);
+    }
+    #[test]
+    fn mod_pow_large_modulus() {
+        let p = 18_446_744_073_709_551_557; // largest prime below 2^64
+        assert_eq!(mod_pow(3, p - 1, p), 1);
+        assert_eq!(mod_pow(u64::MAX, 2, p), 58 * 58);
+        assert_eq!(mod_pow(u64::MAX, 1, u64::MAX), 0);
+        assert_eq!(mod_pow(2, 64, u64::MAX), 1);
+    }
+    #[test]
+    #[should_panic]
+    fn mod_pow_zero_modulus() {
+        mod_pow(2, 3, 0);
+    }
+    #[test]
+    fn mod_inverse_small() {
+        for m in 1..50i64 {
+            for a in -60..60i64 {
+                match mod_inverse(a, m) {
+                    Some(x) => {
+                        assert!(0 <= x && x < m);
+                        assert_eq!((a * x).rem_euclid(m), 1 % m);
+                    },
+                    None => assert_ne!(crate::gcd(a, m), 1)
+                }
+            }
+        }
+    }
+    #[test]
+    fn mod_inverse_values() {
+        assert_eq!(mod_inverse(3, 7), Some(5));
+        assert_eq!(mod_inverse(-3, 7), Some(2));
+        assert_eq!(mod_inverse(10, 7), Some(5));
+        assert_eq!(mod_inverse(0, 1), Some(0));
+        assert_eq!(mod_inverse(4, 8), None);
+        assert_eq!(mod_inverse(0, 7), None);
+    }
+    #[test]
+    fn mod_inverse_invalid_modulus() {
+        assert_eq!(mod_inverse(3, 0), None);
+        assert_eq!(mod_inverse(3, -7), None);
+    }
+    #[test]
+    fn mod_inverse_large() {
+        let m = i64::MAX; // 7^2 * 73 * 127 * 337 * 92737 * 649657
+        assert_eq!(mod_inverse(7, m), None);
+        let x = mod_inverse(2, m).unwrap();
+        assert_eq!((2 * x as i128) % m as i128, 1);
+        let x = mod_inverse(i64::MIN, m).unwrap();
+        assert_eq!((i64::MIN as i128 * x as i128).rem_euclid(m as i128), 1);
+    }
+    #[test]
+    fn mod_inverse_batch_matches_single() {
+        let p = 1_000_000_007;
+        let a: Vec<i64> = (1..200).chain(-50..-1).collect();
+        let expected: Vec<i64> = a.iter().map(|&x| mod_inverse(x, p).unwrap()).collect();
+        assert_eq!(mod_inverse_batch(&a, p), Some(expected));
+    }
+    #[test]
+    fn mod_inverse_batch_factorials() {
+        let p = 998_244_353;
+        let mut fact = vec![1i64; 20];
+        for i in 1..20 {
+            fact[i] = fact[i - 1] * i as i64 % p;
+        }
+        let inv = mod_inverse_batch(&fact, p).unwrap();
+        for i in 0..20 {
+            assert_eq!(fact[i] * inv[i] % p, 1);
+        }
+    }
+    #[test]
+    fn mod_inverse_batch_not_invertible() {
+        assert_eq!(mod_inverse_batch(&[1, 3, 5, 6], 9), None);
+        assert_eq!(mod_inverse_batch(&[0], 7), None);
+        assert_eq!(mod_inverse_batch(&[1, 2], 0), None);
+    }
+    #[test]
+    fn mod_inverse_batch_edge_cases() {
+        assert_eq!(mod_inverse_batch(&[], 7), Some(vec![]));
+        assert_eq!(mod_inverse_batch(&[3, 5], 1), Some(vec![0, 0]));
+        assert_eq!(mod_inverse_batch(&[2, i64::MIN, i64::MAX - 1], i64::MAX),
+            Some(vec![mod_inverse(2, i64::MAX).unwrap(), mod_inverse(i64::MIN, i64::MAX).unwrap(), mod_inverse(i64::MAX - 1, i64::MAX).unwrap()]));
+    }
+    #[test]
+    fn solve_linear_congruence_brute_force() {
+        for m in 1..25i64 {
+            for a in -25..25i64 {
+                for b in -25..25i64 {
+                    let expected: Vec<i64> = (0..m).filter(|x| (a * x - b).rem_euclid(m) == 0).collect();
+                    match solve_linear_congruence(a, b, m) {
+                        Some(res) => {
+                            assert_eq!(res.count * res.modulus, m);
+                            assert_eq!(res.solutions().collect::<Vec<_>>(), expected);
+                        },
+                        None => assert!(expected.is_empty())
+                    }
+                }
+            }
+        }
+    }
+    #[test]
+    fn solve_linear_congruence_values() {
+        assert_eq!(solve_linear_congruence(14, 30, 100), Some(LinearCongruenceSolution { x: 45, modulus: 50, count: 2 }));
+        assert_eq!(solve_linear_congruence(3, 2, 7), Some(LinearCongruenceSolution { x: 3, modulus: 7, count: 1 }));
+        assert_eq!(solve_linear_congruence(0, 0, 4), Some(LinearCongruenceSolution { x: 0, modulus: 1, count: 4 }));
+        assert_eq!(solve_linear_congruence(4, 2, 8), None);
+        assert_eq!(solve_linear_congruence(1, 2, 0), None);
+    }
+    #[test]
+    fn solve_linear_congruence_large() {
+        let m = i64::MAX;
+        let res = solve_linear_congruence(i64::MAX - 2, 5, m).unwrap();
+        assert_eq!(((i64::MAX - 2) as i128 * res.x as i128 - 5).rem_euclid(m as i128), 0);
+        assert_eq!(res.count, 1);
+    }
+
+    #[test]
+    fn power_tower_mod_small_towers() {
+        // exact values fitting in u128
+        let towers: Vec<(Vec<u64>, u128)> = vec![
+            (vec![], 1),
+            (vec![7], 7),
+            (vec![2, 3, 2], 512),
+            (vec![3, 3, 3], 7_625_597_484_987),
+            (vec![2, 2, 2, 2], 65_536),
+            (vec![2, 5, 3], 1 << 125),
+            (vec![0, 0], 1),
+            (vec![0, 0, 0], 0),
+            (vec![5, 0, 7], 1),
+            (vec![1, 100, 100], 1),
+            (vec![10, 2, 2, 2, 1], 10u128.pow(16)),
+            (vec![6, 2, 5], 6u128.pow(32)),
+            (vec![0, 3, 0, 4], 0)
+        ];
+        for (tower, value) in towers {
+            for m in 1..200u64 {
+                assert_eq!(power_tower_mod(&tower, m) as u128, value % m as u128, "{:?} {}", tower, m);
+            }
+            assert_eq!(power_tower_mod(&tower, u64::MAX) as u128, value % u64::MAX as u128, "{:?}", tower);
+        }
+    }
+    #[test]
+    fn power_tower_mod_tall_towers() {
+        // 2^2^2^2^2 = 2^65536 and towers of 3 converge to the last digits of Graham's number
+        assert_eq!(power_tower_mod(&[2, 2, 2, 2, 2], 1_000_000), 156_736);
+        assert_eq!(power_tower_mod(&[3; 1000], 10_000_000_000), 2_464_195_387);
+        assert_eq!(power_tower_mod(&[2; 6], 1 << 40), 0);
+        assert_eq!(power_tower_mod(&vec![3; 1_000_000], 100), 87);
+        // zeros and ones settle the value of everything above them
+        let mut tower = vec![2; 1_000_000];
+        tower.push(0);
+        assert_eq!(power_tower_mod(&tower, 1_000_000), power_tower_mod(&[2; 30], 1_000_000));
+        let mut tower = vec![7, 0, 0];
+        tower.extend(vec![5; 1_000_000]);
+        assert_eq!(power_tower_mod(&tower, 1_000), 7);
+        assert_eq!(power_tower_mod(&tower[1..], 1_000), 1);
+        let m = 1_000_000_007;
+        assert_eq!(power_tower_mod(&[5, 7], m), mod_pow(5, 7, m));
+        assert_eq!(power_tower_mod(&[5, 7, 11], m), mod_pow(5, mod_pow(7, 11, m - 1), m));
+    }
+}
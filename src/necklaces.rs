@@ -0,0 +1,492 @@
+use std::convert::TryFrom;
+
+use crate::{divisors, gcd, mobius, totient, Permutation};
+
+/// Computes a de Bruijn sequence `B(k, n)`, a cyclic sequence of length `k^n` over the digits `0, ..., k-1`
+/// in which every word of length `n` appears exactly once as a contiguous cyclic subsequence.
+/// Go to [de_bruijn_sequence] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(de_bruijn(2, 3), [0, 0, 0, 1, 0, 1, 1, 1]);
+/// assert_eq!(de_bruijn(3, 2), [0, 0, 1, 0, 2, 1, 1, 2, 2]);
+/// ```
+pub fn de_bruijn(k: u64, n: usize) -> Vec<u64>
+{
+    de_bruijn_sequence(k, n).collect()
+}
+
+/// Creates an iterator through the digits of the lexicographically smallest de Bruijn sequence `B(k, n)`.
+///
+/// Uses the FKM algorithm of Fredricksen, Kessler and Maiorana: the sequence is the concatenation of the Lyndon words
+/// over `k` letters whose length divides `n` in lexicographic order. The prenecklaces are generated in place,
+/// so each digit takes `O(1)` amortized time. Yields nothing if `k` or `n` is zero.
+pub fn de_bruijn_sequence(k: u64, n: usize) -> DeBruijnSequence
+{
+    DeBruijnSequence { prenecklaces: Prenecklaces::new(k, n), position: 0, period: 0 }
+}
+
+/// Iterator through the digits of a de Bruijn sequence. Go to [de_bruijn_sequence] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DeBruijnSequence
+{
+    prenecklaces: Prenecklaces,
+    // position in the current Lyndon word of length period
+    position: usize,
+    period: usize
+}
+
+impl Iterator for DeBruijnSequence {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.position == self.period {
+            let n = self.prenecklaces.word.len();
+            let period = self.prenecklaces.next_period()?;
+            if n.is_multiple_of(period) {
+                self.position = 0;
+                self.period = period;
+            }
+        }
+        self.position += 1;
+        Some(self.prenecklaces.word[self.position - 1])
+    }
+}
+
+/// Creates an iterator through the Lyndon words of length `n` over the letters `0, ..., k-1` in lexicographic order.
+/// A Lyndon word is strictly smaller than all of its proper rotations, so it is the unique smallest representative
+/// of an aperiodic necklace. Yields nothing if `k` or `n` is zero.
+///
+/// Filters the prenecklaces generated in place by the FKM algorithm, which takes `O(1)` amortized time per word.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let w: Vec<Vec<u64>> = lyndon_words(2, 4).collect();
+/// assert_eq!(w, [[0, 0, 0, 1], [0, 0, 1, 1], [0, 1, 1, 1]]);
+/// ```
+pub fn lyndon_words(k: u64, n: usize) -> LyndonWords
+{
+    LyndonWords { prenecklaces: Prenecklaces::new(k, n) }
+}
+
+/// Iterator through the Lyndon words. Go to [lyndon_words] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct LyndonWords
+{
+    prenecklaces: Prenecklaces
+}
+
+impl Iterator for LyndonWords {
+    type Item = Vec<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.prenecklaces.word.len();
+        while self.prenecklaces.next_period()? != n {}
+        Some(self.prenecklaces.word.clone())
+    }
+}
+
+/// Creates an iterator through the necklaces of length `n` over the letters `0, ..., k-1`, the classes of words
+/// equivalent under rotation, each represented by its lexicographically smallest rotation, in lexicographic order.
+/// Yields nothing if `k` or `n` is zero.
+///
+/// Filters the prenecklaces generated in place by the FKM algorithm, which takes `O(1)` amortized time per necklace.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let w: Vec<Vec<u64>> = necklaces(2, 4).collect();
+/// assert_eq!(w, [[0, 0, 0, 0], [0, 0, 0, 1], [0, 0, 1, 1], [0, 1, 0, 1], [0, 1, 1, 1], [1, 1, 1, 1]]);
+/// ```
+pub fn necklaces(k: u64, n: usize) -> Necklaces
+{
+    Necklaces { prenecklaces: Prenecklaces::new(k, n) }
+}
+
+/// Iterator through the necklaces. Go to [necklaces] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Necklaces
+{
+    prenecklaces: Prenecklaces
+}
+
+impl Iterator for Necklaces {
+    type Item = Vec<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.prenecklaces.word.len();
+        while !n.is_multiple_of(self.prenecklaces.next_period()?) {}
+        Some(self.prenecklaces.word.clone())
+    }
+}
+
+/// Counts the necklaces of length `n` over `k` letters by the formula `(1/n) sum of φ(d) k^(n/d)` over the divisors `d` of `n`,
+/// which follows from Burnside's lemma applied to the rotations. Returns `0` for `n = 0`.
+/// Returns `None` if the sum does not fit in `u128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(count_necklaces(2, 4), Some(6));
+/// assert_eq!(count_necklaces(10, 30), Some(33333333333333366667333473496));
+/// ```
+pub fn count_necklaces(k: u64, n: u64) -> Option<u128>
+{
+    if n == 0 {
+        return Some(0);
+    }
+    let mut sum: u128 = 0;
+    for d in divisors(n) {
+        let term = checked_power(k, n / d)?.checked_mul(totient(d) as u128)?;
+        sum = sum.checked_add(term)?;
+    }
+    Some(sum / n as u128)
+}
+
+/// Counts the bracelets of length `n` over `k` letters, the necklaces up to reversal, by Burnside's lemma
+/// applied to the dihedral group. Half of its elements are the rotations counted by [count_necklaces],
+/// the reflections fix `k^((n+1)/2)` words for odd `n` and on average `(k^(n/2) + k^(n/2+1))/2` words for even `n`.
+/// Returns `0` for `n = 0`. Returns `None` if twice the result does not fit in `u128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(count_bracelets(2, 6), Some(13));
+/// assert_eq!(count_bracelets(3, 6), Some(92));
+/// ```
+pub fn count_bracelets(k: u64, n: u64) -> Option<u128>
+{
+    if n == 0 {
+        return Some(0);
+    }
+    let half = checked_power(k, n / 2)?;
+    let reflections = if n % 2 == 1 {
+        half.checked_mul(k as u128)?
+    } else {
+        half.checked_mul(k as u128 + 1)? / 2
+    };
+    Some(count_necklaces(k, n)?.checked_add(reflections)? / 2)
+}
+
+/// Counts the orbits of the colorings of `0, ..., n-1` by `k` colors under the action of a group of permutations
+/// by Burnside's lemma, the average number `k^c(g)` of colorings fixed by `g` over the group, where `c(g)`
+/// is the number of cycles of `g`. The group has to list each of its elements exactly once,
+/// otherwise the result is meaningless. For cyclic groups go to [count_orbits_cyclic].
+/// Returns `None` if the sum does not fit in `u128`.
+///
+/// Panics if the group is empty or the permutations have different lengths.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // the rotations of a square acting on its vertices give the necklaces
+/// let r = Permutation::new(vec![1, 2, 3, 0]);
+/// let group: Vec<Permutation> = (0..4).map(|i| r.pow(i)).collect();
+/// assert_eq!(count_orbits(&group, 3), count_necklaces(3, 4));
+/// ```
+pub fn count_orbits(group: &[Permutation], k: u64) -> Option<u128>
+{
+    assert!(!group.is_empty(), "group must not be empty");
+    let n = group[0].len();
+    let mut sum: u128 = 0;
+    for g in group {
+        assert_eq!(g.len(), n, "permutations must have the same length");
+        sum = sum.checked_add(checked_power(k, g.cycles().len() as u64)?)?;
+    }
+    Some(sum / group.len() as u128)
+}
+
+/// Counts the orbits of the colorings of `0, ..., n-1` by `k` colors under the cyclic group generated by `generator`
+/// without enumerating the group. The elements of order `d` of a cyclic group of order `m` are the `φ(d)` generators
+/// of its subgroup of order `d`, which all have the cycle type of `generator^(m/d)`, so Burnside's lemma reduces
+/// to the sum of `φ(d) k^c(generator^(m/d))` over the divisors `d` of `m`, like in [count_necklaces].
+/// Returns `None` if the order of `generator` does not fit in `u64` or the sum does not fit in `u128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let g = Permutation::from_cycles(12, &[vec![0, 1, 2, 3, 4, 5, 6], vec![7, 8, 9, 10, 11]]);
+/// assert_eq!(count_orbits_cyclic(&g, 2), Some(160));
+/// ```
+pub fn count_orbits_cyclic(generator: &Permutation, k: u64) -> Option<u128>
+{
+    let m = u64::try_from(generator.order()?).ok()?;
+    let lengths: Vec<u64> = generator.cycles().iter().map(|c| c.len() as u64).collect();
+    let mut sum: u128 = 0;
+    for d in divisors(m) {
+        // a cycle of length l splits into gcd(e, l) cycles of the power e
+        let cycles: u64 = lengths.iter().map(|&l| gcd(m / d, l)).sum();
+        let term = checked_power(k, cycles)?.checked_mul(totient(d) as u128)?;
+        sum = sum.checked_add(term)?;
+    }
+    Some(sum / m as u128)
+}
+
+/// Counts the Lyndon words of length `n` over `k` letters by Witt's formula `(1/n) sum of μ(d) k^(n/d)`
+/// over the divisors `d` of `n`, obtained by Möbius inversion of `k^n = sum of d L(k, d)`. Returns `0` for `n = 0`.
+/// Returns `None` if the sum of the positive terms does not fit in `u128`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(count_lyndon_words(2, 4), Some(3));
+/// assert_eq!(count_lyndon_words(2, 127), Some(((1u128 << 127) - 2) / 127));
+/// ```
+pub fn count_lyndon_words(k: u64, n: u64) -> Option<u128>
+{
+    if n == 0 {
+        return Some(0);
+    }
+    let (mut plus, mut minus): (u128, u128) = (0, 0);
+    for d in divisors(n) {
+        let mu = mobius(d);
+        if mu == 0 {
+            continue;
+        }
+        let term = checked_power(k, n / d)?;
+        if mu == 1 {
+            plus = plus.checked_add(term)?;
+        } else {
+            minus += term;
+        }
+    }
+    Some((plus - minus) / n as u128)
+}
+
+/// Computes `k^exp`, returns `None` if it does not fit in `u128`.
+fn checked_power(k: u64, exp: u64) -> Option<u128>
+{
+    match k {
+        0 | 1 => Some(if exp == 0 { 1 } else { k as u128 }),
+        _ => (k as u128).checked_pow(u32::try_from(exp).ok()?)
+    }
+}
+
+/// Computes the Lyndon factorization of a word by Duval's algorithm in `O(n)` time, the unique factorization
+/// into Lyndon words `w_1 >= w_2 >= ... >= w_m` in lexicographic order. Returns the lengths of the factors.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // b a n a n a = b · an · an · a
+/// assert_eq!(lyndon_factorization(b"banana"), [1, 2, 2, 1]);
+/// ```
+pub fn lyndon_factorization<T: Ord>(word: &[T]) -> Vec<usize>
+{
+    let n = word.len();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < n {
+        // word[i..j] is a power of a Lyndon word of length j - k followed by its prefix
+        let (mut j, mut k) = (i + 1, i);
+        while j < n && word[k] <= word[j] {
+            k = if word[k] < word[j] { i } else { k + 1 };
+            j += 1;
+        }
+        while i <= k {
+            res.push(j - k);
+            i += j - k;
+        }
+    }
+    res
+}
+
+/// Generator of the prenecklaces of length `n` over `k` letters in lexicographic order, the prefixes of necklaces,
+/// by the FKM algorithm. The current prenecklace is kept in `word`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct Prenecklaces
+{
+    k: u64,
+    word: Vec<u64>,
+    started: bool,
+    done: bool
+}
+
+impl Prenecklaces {
+    fn new(k: u64, n: usize) -> Self {
+        Prenecklaces { k, word: vec![0; n], started: false, done: k == 0 || n == 0 }
+    }
+
+    /// Moves to the next prenecklace and returns the length of its longest Lyndon prefix,
+    /// which is `n` for Lyndon words and divides `n` for necklaces.
+    fn next_period(&mut self) -> Option<usize> {
+        if self.done {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(1);
+        }
+        let n = self.word.len();
+        let i = match self.word.iter().rposition(|&x| x + 1 < self.k) {
+            Some(i) => i,
+            None => {
+                self.done = true;
+                return None;
+            }
+        };
+        self.word[i] += 1;
+        for j in i + 1..n {
+            self.word[j] = self.word[j - i - 1];
+        }
+        Some(i + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn de_bruijn_contains_every_word() {
+        for k in 1..=4u64 {
+            for n in 1..=6usize {
+                let s = de_bruijn(k, n);
+                assert_eq!(s.len() as u64, k.pow(n as u32), "{} {}", k, n);
+                let mut seen = vec![false; s.len()];
+                for start in 0..s.len() {
+                    let word = (0..n).fold(0, |acc, j| acc * k + s[(start + j) % s.len()]);
+                    assert!(!seen[word as usize], "{} {}", k, n);
+                    seen[word as usize] = true;
+                }
+            }
+        }
+        assert_eq!(de_bruijn(1, 5), [0]);
+        assert_eq!(de_bruijn(0, 5), []);
+        assert_eq!(de_bruijn(5, 0), []);
+        assert_eq!(de_bruijn_sequence(2, 20).count(), 1 << 20);
+    }
+
+    fn smallest_rotation(w: &[u64]) -> Vec<u64> {
+        (0..w.len()).map(|r| [&w[r..], &w[..r]].concat()).min().unwrap()
+    }
+
+    #[test]
+    fn necklaces_match_naive() {
+        for k in 1..=3u64 {
+            for n in 1..=7usize {
+                let words: Vec<Vec<u64>> = (0..k.pow(n as u32)).map(|mut x| {
+                    let mut w = vec![0; n];
+                    for i in (0..n).rev() {
+                        w[i] = x % k;
+                        x /= k;
+                    }
+                    w
+                }).collect();
+                let expected_necklaces: Vec<Vec<u64>> = words.iter().filter(|w| smallest_rotation(w) == **w).cloned().collect();
+                let expected_lyndon: Vec<Vec<u64>> = expected_necklaces.iter()
+                    .filter(|w| (1..n).all(|r| [&w[r..], &w[..r]].concat() != **w)).cloned().collect();
+                assert_eq!(necklaces(k, n).collect::<Vec<_>>(), expected_necklaces);
+                assert_eq!(lyndon_words(k, n).collect::<Vec<_>>(), expected_lyndon);
+                assert_eq!(count_necklaces(k, n as u64), Some(expected_necklaces.len() as u128));
+                assert_eq!(count_lyndon_words(k, n as u64), Some(expected_lyndon.len() as u128));
+            }
+        }
+        assert_eq!(necklaces(0, 3).count(), 0);
+        assert_eq!(lyndon_words(3, 0).count(), 0);
+        assert_eq!(count_necklaces(3, 0), Some(0));
+        assert_eq!(count_necklaces(2, 128), None);
+        // exponents beyond u32 for the prime 4294967311
+        assert_eq!(count_necklaces(2, 4_294_967_311), None);
+        assert_eq!(count_lyndon_words(2, 4_294_967_311), None);
+        assert_eq!(count_necklaces(1, 4_294_967_311), Some(1));
+        assert_eq!(count_lyndon_words(1, 4_294_967_311), Some(0));
+        assert_eq!(count_necklaces(0, 4_294_967_311), Some(0));
+        assert_eq!(count_lyndon_words(5, 12), Some(20343700));
+        assert_eq!(lyndon_words(3, 11).count() as u128, count_lyndon_words(3, 11).unwrap());
+        assert_eq!(necklaces(4, 9).count() as u128, count_necklaces(4, 9).unwrap());
+    }
+
+    #[test]
+    fn lyndon_factorization_is_non_increasing() {
+        for x in 0..1u32 << 12 {
+            let word: Vec<u32> = (0..12).map(|i| x >> i & 1).collect();
+            let lengths = lyndon_factorization(&word);
+            assert_eq!(lengths.iter().sum::<usize>(), word.len());
+            let mut factors = Vec::new();
+            let mut start = 0;
+            for &len in &lengths {
+                let factor = &word[start..start + len];
+                assert!((1..len).all(|r| [&factor[r..], &factor[..r]].concat().as_slice() > factor));
+                factors.push(factor);
+                start += len;
+            }
+            assert!(factors.windows(2).all(|w| w[0] >= w[1]));
+        }
+        assert_eq!(lyndon_factorization::<u8>(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn bracelets_and_orbits_match_naive() {
+        for n in 1..=7usize {
+            let rotation = Permutation::new((0..n).map(|i| (i + 1) % n).collect());
+            let reflection = Permutation::new((0..n).map(|i| (n - i) % n).collect());
+            let rotations: Vec<Permutation> = (0..n as i64).map(|i| rotation.pow(i)).collect();
+            let dihedral: Vec<Permutation> = rotations.iter().flat_map(|r| vec![r.clone(), r.compose(&reflection)]).collect();
+            for k in 1..=3u64 {
+                let words: Vec<Vec<u64>> = (0..k.pow(n as u32)).map(|mut x| {
+                    let mut w = vec![0; n];
+                    for i in (0..n).rev() {
+                        w[i] = x % k;
+                        x /= k;
+                    }
+                    w
+                }).collect();
+                let bracelets = words.iter().filter(|w| {
+                    let reversed: Vec<u64> = w.iter().rev().copied().collect();
+                    smallest_rotation(w) == **w && smallest_rotation(&reversed) >= **w
+                }).count() as u128;
+                assert_eq!(count_bracelets(k, n as u64), Some(bracelets), "{} {}", k, n);
+                assert_eq!(count_orbits(&dihedral, k), Some(bracelets));
+                assert_eq!(count_orbits(&rotations, k), count_necklaces(k, n as u64));
+                assert_eq!(count_orbits_cyclic(&rotation, k), count_necklaces(k, n as u64));
+            }
+        }
+        assert_eq!(count_bracelets(3, 0), Some(0));
+        assert_eq!(count_bracelets(2, 1 << 33), None);
+        assert_eq!(count_bracelets(1, 1 << 33), Some(1));
+        assert_eq!(count_bracelets(2, 100), Some(6338253001141997061913538748));
+        // colorings of the faces of a cube, (k^6 + 3k^4 + 12k^3 + 8k^2)/24
+        let a = Permutation::from_cycles(6, &[vec![0, 1, 2, 3]]);
+        let b = Permutation::from_cycles(6, &[vec![0, 4, 2, 5]]);
+        let mut group = vec![Permutation::identity(6)];
+        let mut i = 0;
+        while i < group.len() {
+            for g in [&a, &b].iter() {
+                let h = g.compose(&group[i]);
+                if !group.contains(&h) {
+                    group.push(h);
+                }
+            }
+            i += 1;
+        }
+        assert_eq!(group.len(), 24);
+        for k in 0..10u128 {
+            assert_eq!(count_orbits(&group, k as u64), Some((k.pow(6) + 3 * k.pow(4) + 12 * k.pow(3) + 8 * k * k) / 24));
+        }
+        for g in &group {
+            let cyclic: Vec<Permutation> = (0..g.order().unwrap() as i64).map(|i| g.pow(i)).collect();
+            assert_eq!(count_orbits_cyclic(g, 5), count_orbits(&cyclic, 5));
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use std::cmp::Reverse;
+
+use crate::residues::inverse;
+use crate::{as_prime_power, carmichael_lambda, factorize, gcd, mod_mul, mod_pow};
+
+/// Computes the smallest primitive root modulo `n`, a generator of the multiplicative group of units modulo `n`.
+///
+/// A primitive root exists iff `n` is `1`, `2`, `4`, `p^k` or `2p^k` for an odd prime `p`, returns `None` otherwise.
+/// Candidates `g` are tested by checking `g^(φ(n)/q) ≢ 1 (mod n)` for every prime `q` dividing `φ(n)`.
+/// The smallest primitive root is small in practice, so only a few candidates are needed.
+/// For `n = 1` the only residue `0` is returned.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(primitive_root(7), Some(3));
+/// assert_eq!(primitive_root(998_244_353), Some(3));
+/// assert_eq!(primitive_root(8), None);
+/// ```
+pub fn primitive_root(n: u64) -> Option<u64>
+{
+    match n {
+        0 => return None,
+        1 => return Some(0),
+        2 => return Some(1),
+        4 => return Some(3),
+        _ => {}
+    }
+    let odd = if n.is_multiple_of(2) { n / 2 } else { n };
+    let (p, k) = as_prime_power(odd)?;
+    if p == 2 {
+        return None;
+    }
+    let phi = (p - 1) * p.pow(k - 1);
+    let primes: Vec<u64> = factorize(phi).factors().iter().map(|&(q, _)| q).collect();
+    (2..n).find(|&g| gcd(g, n) == 1 && primes.iter().all(|&q| mod_pow(g, phi / q, n) != 1))
+}
+
+/// Computes the multiplicative order of `a` modulo `n`, the smallest `k > 0` satisfying `a^k ≡ 1 (mod n)`.
+///
+/// The order divides the Carmichael function `λ(n)`, so `λ(n)` is factorized and its prime factors are stripped
+/// one at a time while `a` raised to the smaller exponent is still `1`.
+///
+/// Returns `None` if `a` is not coprime with `n`. Panics if `n` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(multiplicative_order(2, 7), Some(3));
+/// assert_eq!(multiplicative_order(10, 21), Some(6));
+/// assert_eq!(multiplicative_order(3, 12), None);
+/// ```
+pub fn multiplicative_order(a: u64, n: u64) -> Option<u64>
+{
+    assert!(n != 0, "modulus must be positive");
+    if gcd(a, n) != 1 {
+        return None;
+    }
+    let mut order = carmichael_lambda(n);
+    for &(q, e) in factorize(order).factors() {
+        for _ in 0..e {
+            if mod_pow(a, order / q, n) != 1 % n {
+                break;
+            }
+            order /= q;
+        }
+    }
+    Some(order)
+}
+
+/// Computes the structure of the multiplicative group of units modulo `n` as a product of cyclic groups.
+///
+/// Returns the pairs `(g, d)` of generators `g` with orders `d`, such that every unit is uniquely a product of powers `g^a`
+/// with `0 <= a < d`. The orders are the invariant factors, each one divides the next one and the last one is `λ(n)`.
+/// The group modulo every prime power is cyclic generated by a primitive root, except for `2^k` with `k >= 3` generated by `-1` and `5`.
+/// These generators are lifted to modulo `n` by the Chinese remainder theorem, split into parts of prime power orders
+/// and the parts of different primes are multiplied together, so a cyclic group keeps its primitive root. Returns an empty vector for the trivial groups modulo `1` and `2`.
+///
+/// Panics if `n` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(unit_group_structure(7), [(3, 6)]);
+/// assert_eq!(unit_group_structure(15), [(11, 2), (7, 4)]);
+/// assert_eq!(unit_group_structure(32), [(31, 2), (5, 8)]);
+/// ```
+pub fn unit_group_structure(n: u64) -> Vec<(u64, u64)>
+{
+    assert!(n != 0, "modulus must be positive");
+    let mut cyclic = Vec::new();
+    for &(p, k) in factorize(n).factors() {
+        let m = p.pow(k);
+        let local = if p != 2 {
+            vec![(primitive_root(m).unwrap(), m / p * (p - 1))]
+        } else if k >= 3 {
+            vec![(m - 1, 2), (5, m / 4)]
+        } else if k == 2 {
+            vec![(3, 2)]
+        } else {
+            vec![]
+        };
+        // x ≡ g (mod m), x ≡ 1 (mod n/m)
+        let rest = n / m;
+        let rest_inverse = inverse(rest % m, m);
+        for (g, d) in local {
+            let t = mod_mul((g + m - 1) % m, rest_inverse, m);
+            cyclic.push((1 + rest * t, d));
+        }
+    }
+    // components of prime power orders grouped by the primes, largest first
+    let mut components: Vec<(u64, Vec<(u64, u64)>)> = Vec::new();
+    for (g, d) in cyclic {
+        for &(q, e) in factorize(d).factors() {
+            // the exponent is 1 modulo q^e and 0 modulo d/q^e, so the components multiply back to g
+            let qe = q.pow(e);
+            let exponent = d / qe * inverse(d / qe % qe, qe);
+            let component = (mod_pow(g, exponent, n), qe);
+            match components.iter_mut().find(|(p, _)| *p == q) {
+                Some((_, list)) => list.push(component),
+                None => components.push((q, vec![component]))
+            }
+        }
+    }
+    let count = components.iter().map(|(_, list)| list.len()).max().unwrap_or(0);
+    let mut res = vec![(1 % n, 1); count];
+    for (_, mut list) in components {
+        list.sort_unstable_by_key(|&(_, d)| Reverse(d));
+        for (i, (g, d)) in list.into_iter().enumerate() {
+            res[count - 1 - i] = (mod_mul(res[count - 1 - i].0, g, n), res[count - 1 - i].1 * d);
+        }
+    }
+    res
+}
+
+/// Creates an iterator through the units modulo `n`, the residues `0 <= a < n` coprime with `n`, in increasing order.
+/// For `n = 1` the only residue `0` is yielded.
+///
+/// Panics if `n` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(units(12).collect::<Vec<_>>(), [1, 5, 7, 11]);
+/// assert_eq!(units(1_000_000).count() as u64, totient(1_000_000));
+/// ```
+pub fn units(n: u64) -> Units
+{
+    assert!(n != 0, "modulus must be positive");
+    let primes = factorize(n).factors().iter().map(|&(p, _)| p).collect();
+    Units { n, primes, next: 0 }
+}
+
+/// Iterator through the units modulo `n`.
+/// Go to [units] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Units
+{
+    n: u64,
+    primes: Vec<u64>,
+    next: u64
+}
+
+impl Iterator for Units {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.next < self.n {
+            let a = self.next;
+            self.next += 1;
+            if self.primes.iter().all(|&p| !a.is_multiple_of(p)) {
+                return Some(a);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn order_naive(a: u64, n: u64) -> u64 {
+        let mut value = a % n;
+        let mut k = 1;
+        while value != 1 {
+            value = value * a % n;
+            k += 1;
+        }
+        k
+    }
+
+    #[test]
+    fn primitive_root_brute_force() {
+        assert_eq!(primitive_root(1), Some(0));
+        for n in 2..1000u64 {
+            let phi = crate::totient(n);
+            let expected = (1..n).find(|&g| gcd(g, n) == 1 && order_naive(g, n) == phi);
+            assert_eq!(primitive_root(n), expected, "{}", n);
+        }
+    }
+    #[test]
+    fn primitive_root_large() {
+        assert_eq!(primitive_root(1_000_000_007), Some(5));
+        assert_eq!(primitive_root(18_446_744_069_414_584_321), Some(7));
+        assert_eq!(primitive_root(2 * 3u64.pow(39)), Some(5));
+        assert_eq!(primitive_root(4_294_967_291 * 4_294_967_291), Some(2));
+        assert_eq!(primitive_root(1_000_000_007 * 998_244_353), None);
+        assert_eq!(primitive_root(1 << 63), None);
+        assert_eq!(primitive_root(0), None);
+    }
+
+    #[test]
+    fn multiplicative_order_brute_force() {
+        for n in 2..500u64 {
+            for a in 0..n {
+                let expected = if gcd(a, n) == 1 { Some(order_naive(a, n)) } else { None };
+                assert_eq!(multiplicative_order(a, n), expected, "{} {}", a, n);
+            }
+        }
+        assert_eq!(multiplicative_order(0, 1), Some(1));
+        assert_eq!(multiplicative_order(5, 1), Some(1));
+    }
+    #[test]
+    fn multiplicative_order_large() {
+        let p = 18_446_744_069_414_584_321;
+        assert_eq!(multiplicative_order(7, p), Some(p - 1));
+        assert_eq!(multiplicative_order(2, p), Some(192));
+        assert_eq!(multiplicative_order(3, 1 << 63), Some(1 << 61));
+        assert_eq!(multiplicative_order(10, 1_000_000_007 * 998_244_353), Some(crate::lcm(1_000_000_006u64, 998_244_352)));
+        assert_eq!(multiplicative_order(u64::MAX - 1, u64::MAX), Some(2));
+        assert_eq!(multiplicative_order(6, 1 << 40), None);
+    }
+
+    #[test]
+    fn unit_group_structure_brute_force() {
+        assert_eq!(unit_group_structure(1), []);
+        assert_eq!(unit_group_structure(2), []);
+        assert_eq!(units(1).collect::<Vec<_>>(), [0]);
+        for n in 1..400u64 {
+            let expected: Vec<u64> = (0..n).filter(|&a| gcd(a, n) == 1).collect();
+            assert_eq!(units(n).collect::<Vec<_>>(), expected, "{}", n);
+            let structure = unit_group_structure(n);
+            for (i, &(g, d)) in structure.iter().enumerate() {
+                assert!(d > 1, "{}", n);
+                assert_eq!(multiplicative_order(g, n), Some(d), "{}", n);
+                if i + 1 < structure.len() {
+                    assert!(structure[i + 1].1.is_multiple_of(d), "{}", n);
+                } else {
+                    assert_eq!(d, carmichael_lambda(n), "{}", n);
+                }
+            }
+            // the products of powers of the generators are exactly the units
+            let mut generated = vec![1 % n];
+            for &(g, d) in &structure {
+                let mut next = Vec::new();
+                for &x in &generated {
+                    let mut y = x;
+                    for _ in 0..d {
+                        next.push(y);
+                        y = y * g % n;
+                    }
+                }
+                generated = next;
+            }
+            generated.sort_unstable();
+            assert_eq!(generated, expected, "{}", n);
+        }
+    }
+    #[test]
+    fn unit_group_structure_large() {
+        let n = 1_000_000_007 * 998_244_353;
+        let structure = unit_group_structure(n);
+        assert_eq!(structure.iter().map(|&(_, d)| d).collect::<Vec<_>>(), [2, crate::lcm(1_000_000_006u64, 998_244_352)]);
+        for &(g, d) in &structure {
+            assert_eq!(multiplicative_order(g, n), Some(d));
+        }
+        let structure = unit_group_structure(1 << 63);
+        assert_eq!(structure, [((1 << 63) - 1, 2), (5, 1 << 61)]);
+        assert_eq!(units(u64::MAX).take(3).collect::<Vec<_>>(), [1, 2, 4]);
+    }
+}
@@ -0,0 +1,418 @@
+/// Creates an iterator through all partitions of `n` into positive parts in reverse lexicographic order,
+/// starting with `[n]` and ending with `[1, ..., 1]`. The parts of each partition are in non-increasing order
+/// and the empty partition is the only partition of 0. Go to [partitions_bounded] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let p: Vec<Vec<u64>> = partitions(4).collect();
+/// assert_eq!(p, [vec![4], vec![3, 1], vec![2, 2], vec![2, 1, 1], vec![1, 1, 1, 1]]);
+/// ```
+pub fn partitions(n: u64) -> Partitions
+{
+    partitions_bounded(n, usize::MAX, n)
+}
+
+/// Creates an iterator through the partitions of `n` into at most `max_count` parts, each at most `max_part`,
+/// in reverse lexicographic order. The parts of each partition are in non-increasing order.
+///
+/// The partitions are generated in place, each one from the previous by decrementing the rightmost part
+/// that can be decremented and refilling the rest greedily, only the yielded vectors are allocated.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let p: Vec<Vec<u64>> = partitions_bounded(6, 2, 4).collect();
+/// assert_eq!(p, [vec![4, 2], vec![3, 3]]);
+/// ```
+pub fn partitions_bounded(n: u64, max_count: usize, max_part: u64) -> Partitions
+{
+    let m = max_part.min(n);
+    if n > 0 && (m == 0 || n.div_ceil(m) > max_count as u64) {
+        return Partitions { parts: Vec::new(), max_count, done: true };
+    }
+    let mut parts = Vec::new();
+    fill(&mut parts, n, m);
+    Partitions { parts, max_count, done: false }
+}
+
+/// Iterator through the partitions of an integer. Go to [partitions_bounded] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Partitions
+{
+    parts: Vec<u64>,
+    max_count: usize,
+    done: bool
+}
+
+impl Partitions {
+    fn advance(&mut self) -> bool {
+        let mut suffix = 0;
+        for i in (0..self.parts.len()).rev() {
+            // decrement the part at i and spread the rest over at most max_count - i - 1 parts
+            let v = self.parts[i] - 1;
+            let slots = (self.max_count - i - 1) as u64;
+            if v > 0 && suffix < v.saturating_mul(slots) {
+                self.parts[i] = v;
+                self.parts.truncate(i + 1);
+                fill(&mut self.parts, suffix + 1, v);
+                return true;
+            }
+            suffix += self.parts[i];
+        }
+        false
+    }
+}
+
+impl Iterator for Partitions {
+    type Item = Vec<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.parts.clone();
+        self.done = !self.advance();
+        Some(res)
+    }
+}
+
+/// Creates an iterator through all compositions of `n`, the sequences of positive parts summing to `n`,
+/// in lexicographic order, starting with `[1, ..., 1]` and ending with `[n]`. There are `2^(n-1)` of them for positive `n`
+/// and the empty composition is the only composition of 0.
+///
+/// Each composition is obtained from the previous one in place by incrementing the second to last part
+/// and splitting the rest of the last part into ones.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let c: Vec<Vec<u64>> = compositions(3).collect();
+/// assert_eq!(c, [vec![1, 1, 1], vec![1, 2], vec![2, 1], vec![3]]);
+/// ```
+pub fn compositions(n: u64) -> Compositions
+{
+    Compositions { parts: vec![1; n as usize], done: false }
+}
+
+/// Iterator through the compositions of an integer. Go to [compositions] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Compositions
+{
+    parts: Vec<u64>,
+    done: bool
+}
+
+impl Iterator for Compositions {
+    type Item = Vec<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.parts.clone();
+        let len = self.parts.len();
+        if len < 2 {
+            self.done = true;
+        } else {
+            let last = self.parts.pop().unwrap();
+            self.parts[len - 2] += 1;
+            self.parts.extend(std::iter::repeat_n(1, (last - 1) as usize));
+        }
+        Some(res)
+    }
+}
+
+/// Creates an iterator through the compositions of `n` into exactly `k` positive parts in lexicographic order.
+/// There are `C(n-1, k-1)` of them. Go to [weak_compositions] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let c: Vec<Vec<u64>> = compositions_into(5, 3).collect();
+/// assert_eq!(c, [[1, 1, 3], [1, 2, 2], [1, 3, 1], [2, 1, 2], [2, 2, 1], [3, 1, 1]]);
+/// ```
+pub fn compositions_into(n: u64, k: usize) -> CompositionsInto
+{
+    match n.checked_sub(k as u64) {
+        Some(rest) if k > 0 || n == 0 => CompositionsInto { min: 1, ..weak_compositions(rest, k) },
+        _ => CompositionsInto { parts: Vec::new(), min: 1, done: true }
+    }
+}
+
+/// Creates an iterator through the weak compositions of `n` into exactly `k` non-negative parts in lexicographic order,
+/// which are the ways to distribute `n` identical items into `k` distinct boxes counted by stars and bars.
+/// There are `C(n+k-1, k-1)` of them. Yields nothing if `k = 0` and `n > 0`.
+///
+/// Each composition is obtained from the previous one in place by incrementing the rightmost part, except for the last one,
+/// that has a nonzero part after it and moving everything after it into the last part.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let c: Vec<Vec<u64>> = weak_compositions(2, 3).collect();
+/// assert_eq!(c, [[0, 0, 2], [0, 1, 1], [0, 2, 0], [1, 0, 1], [1, 1, 0], [2, 0, 0]]);
+/// ```
+pub fn weak_compositions(n: u64, k: usize) -> CompositionsInto
+{
+    if k == 0 {
+        return CompositionsInto { parts: Vec::new(), min: 0, done: n > 0 };
+    }
+    let mut parts = vec![0; k];
+    parts[k - 1] = n;
+    CompositionsInto { parts, min: 0, done: false }
+}
+
+/// Iterator through the compositions of an integer into a fixed number of parts.
+/// Go to [compositions_into] and [weak_compositions] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct CompositionsInto
+{
+    // the weak composition of n - k * min
+    parts: Vec<u64>,
+    min: u64,
+    done: bool
+}
+
+impl Iterator for CompositionsInto {
+    type Item = Vec<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.parts.iter().map(|&x| x + self.min).collect();
+        let k = self.parts.len();
+        let mut suffix = self.parts.last().copied().unwrap_or(0);
+        self.done = true;
+        for i in (0..k.saturating_sub(1)).rev() {
+            if suffix > 0 {
+                self.parts[i] += 1;
+                for x in &mut self.parts[i + 1..k - 1] {
+                    *x = 0;
+                }
+                self.parts[k - 1] = suffix - 1;
+                self.done = false;
+                break;
+            }
+            suffix += self.parts[i];
+        }
+        Some(res)
+    }
+}
+
+/// Creates an iterator through all partitions of the set `{0, ..., n-1}` into nonempty blocks.
+/// There are `B_n` of them, the Bell number. Go to [set_partitions_bounded] for further information.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let p: Vec<Vec<Vec<usize>>> = set_partitions(3).collect();
+/// assert_eq!(p, [
+///     vec![vec![0, 1, 2]],
+///     vec![vec![0, 1], vec![2]],
+///     vec![vec![0, 2], vec![1]],
+///     vec![vec![0], vec![1, 2]],
+///     vec![vec![0], vec![1], vec![2]]
+/// ]);
+/// ```
+pub fn set_partitions(n: usize) -> SetPartitions
+{
+    set_partitions_bounded(n, n)
+}
+
+/// Creates an iterator through the partitions of the set `{0, ..., n-1}` into at most `max_blocks` nonempty blocks.
+///
+/// Each partition is yielded as its blocks, each sorted and ordered by their smallest elements.
+/// The partitions are enumerated through their restricted growth strings `a`, where `a[i]` is the index of the block
+/// containing `i`, so `a[0] = 0` and `a[i]` is at most one more than the maximum of `a[0], ..., a[i-1]`.
+/// The strings are generated in place in lexicographic order, starting with the single block.
+/// The empty partition is the only partition of the empty set.
+pub fn set_partitions_bounded(n: usize, max_blocks: usize) -> SetPartitions
+{
+    SetPartitions { growth: vec![0; n], max_blocks, done: n > 0 && max_blocks == 0 }
+}
+
+/// Iterator through the partitions of a set. Go to [set_partitions_bounded] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct SetPartitions
+{
+    growth: Vec<usize>,
+    max_blocks: usize,
+    done: bool
+}
+
+impl SetPartitions {
+    fn advance(&mut self) -> bool {
+        let n = self.growth.len();
+        // prefix_max[i] is the maximum of growth[0..i]
+        let mut prefix_max = vec![0; n];
+        for i in 1..n {
+            prefix_max[i] = prefix_max[i - 1].max(self.growth[i - 1]);
+        }
+        for i in (1..n).rev() {
+            let a = self.growth[i];
+            if a <= prefix_max[i] && a + 1 < self.max_blocks {
+                self.growth[i] += 1;
+                for x in &mut self.growth[i + 1..] {
+                    *x = 0;
+                }
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Iterator for SetPartitions {
+    type Item = Vec<Vec<usize>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut blocks: Vec<Vec<usize>> = Vec::new();
+        for (i, &a) in self.growth.iter().enumerate() {
+            if a == blocks.len() {
+                blocks.push(Vec::new());
+            }
+            blocks[a].push(i);
+        }
+        self.done = !self.advance();
+        Some(blocks)
+    }
+}
+
+/// Appends the parts of the largest partition of `n` with parts at most `m` in reverse lexicographic order.
+fn fill(parts: &mut Vec<u64>, n: u64, m: u64)
+{
+    if n == 0 {
+        return;
+    }
+    parts.extend(std::iter::repeat_n(m, (n / m) as usize));
+    if !n.is_multiple_of(m) {
+        parts.push(n % m);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partitions_naive(n: u64, max_part: u64) -> Vec<Vec<u64>> {
+        if n == 0 {
+            return vec![vec![]];
+        }
+        let mut res = Vec::new();
+        for first in (1..=max_part.min(n)).rev() {
+            for mut rest in partitions_naive(n - first, first) {
+                rest.insert(0, first);
+                res.push(rest);
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn partitions_match_naive() {
+        let counts = crate::partition_counts_mod(25, u64::MAX);
+        for n in 0..=25u64 {
+            let all: Vec<Vec<u64>> = partitions(n).collect();
+            assert_eq!(all, partitions_naive(n, n));
+            assert_eq!(all.len() as u64, counts[n as usize]);
+            for max_count in 0..=n as usize + 1 {
+                for max_part in 0..=n + 1 {
+                    let expected: Vec<Vec<u64>> = all.iter().filter(|p| p.len() <= max_count && p.iter().all(|&x| x <= max_part)).cloned().collect();
+                    assert_eq!(partitions_bounded(n, max_count, max_part).collect::<Vec<_>>(), expected, "{} {} {}", n, max_count, max_part);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn partitions_large() {
+        assert_eq!(partitions(50).count(), 204226);
+        assert_eq!(partitions_bounded(1 << 40, 1, u64::MAX).collect::<Vec<_>>(), [vec![1 << 40]]);
+        assert_eq!(partitions_bounded(1 << 40, 2, 1 << 39).collect::<Vec<_>>(), [vec![1 << 39, 1 << 39]]);
+        assert_eq!(partitions_bounded(100, 3, 100).count(), 884);
+    }
+
+    fn tuples(n: u64, k: usize) -> Vec<Vec<u64>> {
+        if k == 0 {
+            return vec![vec![]];
+        }
+        let shorter = tuples(n, k - 1);
+        (0..=n).flat_map(|first| shorter.iter().map(move |t| {
+            let mut t = t.clone();
+            t.insert(0, first);
+            t
+        })).collect()
+    }
+
+    #[test]
+    fn compositions_match_naive() {
+        for n in 0..=7u64 {
+            let mut all = Vec::new();
+            for k in 0..=n as usize + 1 {
+                let weak: Vec<Vec<u64>> = tuples(n, k).into_iter().filter(|t| t.iter().sum::<u64>() == n).collect();
+                assert_eq!(weak_compositions(n, k).collect::<Vec<_>>(), weak, "{} {}", n, k);
+                let positive: Vec<Vec<u64>> = weak.into_iter().filter(|t| t.iter().all(|&x| x > 0)).collect();
+                assert_eq!(compositions_into(n, k).collect::<Vec<_>>(), positive, "{} {}", n, k);
+                all.extend(positive);
+            }
+            all.sort();
+            assert_eq!(compositions(n).collect::<Vec<_>>(), all);
+        }
+        assert_eq!(compositions(20).count(), 1 << 19);
+        assert_eq!(weak_compositions(10, 5).count(), 1001);
+        assert_eq!(compositions_into(1 << 40, 1).collect::<Vec<_>>(), [vec![1 << 40]]);
+    }
+
+    #[test]
+    fn set_partitions_match_stirling() {
+        // stirling[n][k] is the number of partitions of an n-element set into k blocks
+        let mut stirling = vec![vec![1u64]];
+        for n in 1..=9usize {
+            let prev = &stirling[n - 1];
+            let row: Vec<u64> = (0..=n).map(|k| {
+                let same = if k < prev.len() { k as u64 * prev[k] } else { 0 };
+                same + if k > 0 { prev[k - 1] } else { 0 }
+            }).collect();
+            stirling.push(row);
+        }
+        for (n, row) in stirling.iter().enumerate() {
+            let all: Vec<Vec<Vec<usize>>> = set_partitions(n).collect();
+            // the Bell number is the sum of the Stirling row
+            assert_eq!(all.len() as u64, row.iter().sum::<u64>());
+            for p in &all {
+                let mut elements: Vec<usize> = p.iter().flatten().copied().collect();
+                elements.sort_unstable();
+                assert_eq!(elements, (0..n).collect::<Vec<_>>());
+                assert!(p.iter().all(|b| !b.is_empty() && b.windows(2).all(|w| w[0] < w[1])));
+                assert!(p.windows(2).all(|w| w[0][0] < w[1][0]));
+            }
+            let mut sorted = all.clone();
+            sorted.sort();
+            sorted.dedup();
+            assert_eq!(sorted.len(), all.len());
+            for max_blocks in 0..=n + 1 {
+                let expected: u64 = row.iter().take(max_blocks + 1).sum();
+                let bounded: Vec<Vec<Vec<usize>>> = set_partitions_bounded(n, max_blocks).collect();
+                assert_eq!(bounded.len() as u64, expected, "{} {}", n, max_blocks);
+                assert!(bounded.iter().all(|p| p.len() <= max_blocks));
+            }
+        }
+    }
+}
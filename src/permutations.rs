@@ -0,0 +1,603 @@
+use std::fmt;
+
+use crate::checked_lcm;
+use crate::fenwick::{fenwick_find, fenwick_full, fenwick_prefix, fenwick_remove};
+
+/// Rearranges the elements into the lexicographically next greater permutation, like `std::next_permutation` in C++.
+///
+/// Returns true if such a permutation exists. Otherwise rearranges the elements into the first permutation,
+/// which is sorted in increasing order, and returns false. Equal elements are handled,
+/// so repeated calls starting from a sorted slice enumerate every distinct permutation once.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let mut p = [1, 3, 2];
+/// assert!(next_permutation(&mut p));
+/// assert_eq!(p, [2, 1, 3]);
+/// let mut p = [3, 2, 1];
+/// assert!(!next_permutation(&mut p));
+/// assert_eq!(p, [1, 2, 3]);
+/// ```
+pub fn next_permutation<T: Ord>(items: &mut [T]) -> bool
+{
+    let n = items.len();
+    // the longest non-increasing suffix starts at i
+    let i = match (1..n).rev().find(|&i| items[i - 1] < items[i]) {
+        Some(i) => i,
+        None => {
+            items.reverse();
+            return false;
+        }
+    };
+    let j = (i..n).rev().find(|&j| items[j] > items[i - 1]).unwrap();
+    items.swap(i - 1, j);
+    items[i..].reverse();
+    true
+}
+
+/// Rearranges the elements into the lexicographically next smaller permutation, like `std::prev_permutation` in C++.
+///
+/// Returns true if such a permutation exists. Otherwise rearranges the elements into the last permutation,
+/// which is sorted in decreasing order, and returns false.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let mut p = [2, 1, 3];
+/// assert!(prev_permutation(&mut p));
+/// assert_eq!(p, [1, 3, 2]);
+/// ```
+pub fn prev_permutation<T: Ord>(items: &mut [T]) -> bool
+{
+    let n = items.len();
+    // the longest non-decreasing suffix starts at i
+    let i = match (1..n).rev().find(|&i| items[i - 1] > items[i]) {
+        Some(i) => i,
+        None => {
+            items.reverse();
+            return false;
+        }
+    };
+    let j = (i..n).rev().find(|&j| items[j] < items[i - 1]).unwrap();
+    items.swap(i - 1, j);
+    items[i..].reverse();
+    true
+}
+
+/// Creates an iterator through all `n!` permutations of `0, ..., n-1` generated by Heap's algorithm,
+/// which obtains each permutation from the previous one by a single swap. The order is not lexicographic,
+/// use [next_permutation] for that.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let p: Vec<Vec<usize>> = permutations(3).collect();
+/// assert_eq!(p, [[0, 1, 2], [1, 0, 2], [2, 0, 1], [0, 2, 1], [1, 2, 0], [2, 1, 0]]);
+/// ```
+pub fn permutations(n: usize) -> Permutations
+{
+    Permutations { items: (0..n).collect(), counters: vec![0; n], i: 1, first: true }
+}
+
+/// Iterator through the permutations by Heap's algorithm. Go to [permutations] for further information.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Permutations
+{
+    items: Vec<usize>,
+    // the stack state of the recursive formulation
+    counters: Vec<usize>,
+    i: usize,
+    first: bool
+}
+
+impl Iterator for Permutations {
+    type Item = Vec<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.first {
+            self.first = false;
+            return Some(self.items.clone());
+        }
+        while self.i < self.items.len() {
+            let i = self.i;
+            if self.counters[i] < i {
+                let j = if i.is_multiple_of(2) { 0 } else { self.counters[i] };
+                self.items.swap(j, i);
+                self.counters[i] += 1;
+                self.i = 1;
+                return Some(self.items.clone());
+            }
+            self.counters[i] = 0;
+            self.i += 1;
+        }
+        None
+    }
+}
+
+/// Computes the Lehmer code of a permutation of `0, ..., n-1`, where the `i`-th entry is the number of elements
+/// after position `i` smaller than the element at `i`. The code is the permutation's lexicographic rank written
+/// in the factorial number system, like the digits of [crate::to_factorial_base].
+///
+/// Counts the smaller remaining elements by a binary indexed tree in `O(n log n)` time.
+///
+/// Panics if `permutation` is not a permutation of `0, ..., n-1`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(lehmer_code(&[2, 0, 3, 1]), [2, 0, 1, 0]);
+/// assert_eq!(permutation_from_lehmer_code(&[2, 0, 1, 0]), [2, 0, 3, 1]);
+/// ```
+pub fn lehmer_code(permutation: &[usize]) -> Vec<usize>
+{
+    let n = permutation.len();
+    let mut tree = fenwick_full(n);
+    let mut seen = vec![false; n];
+    permutation.iter().map(|&x| {
+        assert!(x < n && !seen[x], "not a permutation of 0..{}", n);
+        seen[x] = true;
+        let smaller = fenwick_prefix(&tree, x);
+        fenwick_remove(&mut tree, x);
+        smaller
+    }).collect()
+}
+
+/// Computes the permutation of `0, ..., n-1` with the given Lehmer code, the inverse of [lehmer_code].
+/// Finds the element with the given number of smaller remaining elements by a binary indexed tree in `O(n log n)` time.
+///
+/// Panics if the `i`-th entry of `code` is greater than `n-1-i`.
+pub fn permutation_from_lehmer_code(code: &[usize]) -> Vec<usize>
+{
+    let n = code.len();
+    let mut tree = fenwick_full(n);
+    code.iter().enumerate().map(|(i, &c)| {
+        assert!(c < n - i, "entry {} at index {} of the Lehmer code must be smaller than {}", c, i, n - i);
+        let x = fenwick_find(&tree, c);
+        fenwick_remove(&mut tree, x);
+        x
+    }).collect()
+}
+
+/// Computes the position of a permutation of `0, ..., n-1` in lexicographic order, starting with 0,
+/// from its [lehmer_code] `L` as `L_0 (n-1)! + L_1 (n-2)! + ... + L_(n-1) 0!`. Takes `O(n log n)` time.
+/// Returns `None` if the rank does not fit in `u128`, which is possible only for `n > 34`.
+///
+/// Panics if `permutation` is not a permutation of `0, ..., n-1`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(rank_permutation(&[0, 1, 2]), Some(0));
+/// assert_eq!(rank_permutation(&[2, 0, 3, 1]), Some(13));
+/// assert_eq!(rank_permutation(&[2, 1, 0]), Some(5));
+/// ```
+pub fn rank_permutation(permutation: &[usize]) -> Option<u128>
+{
+    let n = permutation.len();
+    lehmer_code(permutation).iter().enumerate().try_fold(0u128, |res, (i, &c)| {
+        res.checked_mul((n - i) as u128)?.checked_add(c as u128)
+    })
+}
+
+/// Computes the permutation of `0, ..., n-1` at position `rank` in lexicographic order, the inverse of [rank_permutation].
+/// Unranking a uniformly random number in `[0, n!)` samples a permutation uniformly. Takes `O(n log n)` time.
+///
+/// Returns `None` if `rank >= n!`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(unrank_permutation(13, 4), Some(vec![2, 0, 3, 1]));
+/// assert_eq!(unrank_permutation(24, 4), None);
+/// ```
+pub fn unrank_permutation(mut rank: u128, n: usize) -> Option<Vec<usize>>
+{
+    // the factorial number system digits from the least significant one
+    let mut code = vec![0; n];
+    for i in 0..n {
+        let radix = (i + 1) as u128;
+        code[n - 1 - i] = (rank % radix) as usize;
+        rank /= radix;
+    }
+    if rank != 0 {
+        return None;
+    }
+    Some(permutation_from_lehmer_code(&code))
+}
+
+/// Permutation of `0, ..., n-1` given by the images of the elements.
+///
+/// Composition follows the usual convention of functions, `p.compose(q)` maps `i` to `p(q(i))`.
+/// The cycle decomposition lists every cycle starting with its smallest element, ordered by the smallest elements,
+/// and the [Display](fmt::Display) implementation prints it in cycle notation without the fixed points.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let p = Permutation::new(vec![1, 2, 0, 4, 3]);
+/// assert_eq!(p.cycles(), [vec![0, 1, 2], vec![3, 4]]);
+/// assert_eq!(p.cycle_type(), [3, 2]);
+/// assert_eq!(p.to_string(), "(0 1 2)(3 4)");
+/// assert_eq!(p.compose(&p.inverse()), Permutation::identity(5));
+/// assert_eq!(p.apply(&['a', 'b', 'c', 'd', 'e']), ['c', 'a', 'b', 'e', 'd']);
+/// ```
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
+pub struct Permutation
+{
+    images: Vec<usize>
+}
+
+impl Permutation {
+    /// Creates the permutation mapping `i` to `images[i]`.
+    ///
+    /// Panics if `images` is not a permutation of `0, ..., n-1`.
+    pub fn new(images: Vec<usize>) -> Self {
+        let n = images.len();
+        let mut seen = vec![false; n];
+        for &x in &images {
+            assert!(x < n && !seen[x], "not a permutation of 0..{}", n);
+            seen[x] = true;
+        }
+        Permutation { images }
+    }
+
+    /// Creates the identity permutation of `0, ..., n-1`.
+    pub fn identity(n: usize) -> Self {
+        Permutation { images: (0..n).collect() }
+    }
+
+    /// Creates the permutation of `0, ..., n-1` with the given disjoint cycles, each mapping every element to the next one
+    /// and the last one to the first. The elements not in any cycle are fixed.
+    ///
+    /// Panics if the cycles are not disjoint or contain an element not smaller than `n`.
+    pub fn from_cycles(n: usize, cycles: &[Vec<usize>]) -> Self {
+        let mut images: Vec<usize> = (0..n).collect();
+        let mut seen = vec![false; n];
+        for cycle in cycles {
+            for (i, &x) in cycle.iter().enumerate() {
+                assert!(x < n && !seen[x], "cycles must be disjoint and smaller than {}", n);
+                seen[x] = true;
+                images[x] = cycle[(i + 1) % cycle.len()];
+            }
+        }
+        Permutation { images }
+    }
+
+    /// Number of permuted elements `n`.
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// The images of `0, ..., n-1`.
+    pub fn images(&self) -> &[usize] {
+        &self.images
+    }
+
+    /// Returns the image of `i`. Panics if `i >= n`.
+    pub fn image(&self, i: usize) -> usize {
+        self.images[i]
+    }
+
+    /// Computes the composition mapping `i` to `self(other(i))`.
+    ///
+    /// Panics if the permutations have different lengths.
+    pub fn compose(&self, other: &Permutation) -> Permutation {
+        assert_eq!(self.len(), other.len(), "permutations must have the same length");
+        Permutation { images: other.images.iter().map(|&x| self.images[x]).collect() }
+    }
+
+    /// Computes the inverse permutation.
+    pub fn inverse(&self) -> Permutation {
+        let mut images = vec![0; self.len()];
+        for (i, &x) in self.images.iter().enumerate() {
+            images[x] = i;
+        }
+        Permutation { images }
+    }
+
+    /// Moves the item at position `i` to position `self(i)`.
+    ///
+    /// Panics if `items` does not have length `n`.
+    pub fn apply<T: Clone>(&self, items: &[T]) -> Vec<T> {
+        assert_eq!(items.len(), self.len(), "slice must have the same length as the permutation");
+        let mut res = items.to_vec();
+        for (item, &x) in items.iter().zip(&self.images) {
+            res[x] = item.clone();
+        }
+        res
+    }
+
+    /// Computes the cycle decomposition including the fixed points, each cycle starting with its smallest element,
+    /// ordered by the smallest elements.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut seen = vec![false; self.len()];
+        let mut res = Vec::new();
+        for start in 0..self.len() {
+            if seen[start] {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut x = start;
+            while !seen[x] {
+                seen[x] = true;
+                cycle.push(x);
+                x = self.images[x];
+            }
+            res.push(cycle);
+        }
+        res
+    }
+
+    /// Computes the cycle type, the lengths of the cycles including the fixed points in non-increasing order,
+    /// which is a partition of `n` like the ones yielded by [crate::partitions].
+    pub fn cycle_type(&self) -> Vec<u64> {
+        let mut res: Vec<u64> = self.cycles().iter().map(|c| c.len() as u64).collect();
+        res.sort_unstable_by(|a, b| b.cmp(a));
+        res
+    }
+
+    /// Computes the order, the smallest positive `k` with `self^k` being the identity,
+    /// as the [lcm](crate::lcm) of the cycle lengths.
+    /// Returns `None` if the result does not fit in `u128`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dma::*;
+    ///
+    /// let p = Permutation::from_cycles(7, &[vec![0, 1, 2], vec![3, 4, 5, 6]]);
+    /// assert_eq!(p.order(), Some(12));
+    /// assert_eq!(p.sign(), -1);
+    /// assert_eq!(p.pow(12), Permutation::identity(7));
+    /// assert_eq!(p.pow(-1), p.inverse());
+    /// ```
+    pub fn order(&self) -> Option<u128> {
+        self.cycles().iter().try_fold(1, |res, c| checked_lcm(res, c.len() as u128))
+    }
+
+    /// Computes the sign, 1 for even and -1 for odd permutations.
+    pub fn sign(&self) -> i8 {
+        let transpositions: usize = self.cycles().iter().map(|c| c.len() - 1).sum();
+        if transpositions.is_multiple_of(2) { 1 } else { -1 }
+    }
+
+    /// Computes the `k`-th power by rotating every cycle by `k` modulo its length.
+    /// Negative `k` gives the powers of the inverse.
+    pub fn pow(&self, k: i64) -> Permutation {
+        let mut images = vec![0; self.len()];
+        for cycle in self.cycles() {
+            let shift = k.rem_euclid(cycle.len() as i64) as usize;
+            for (i, &x) in cycle.iter().enumerate() {
+                images[x] = cycle[(i + shift) % cycle.len()];
+            }
+        }
+        Permutation { images }
+    }
+}
+
+impl fmt::Display for Permutation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cycles: Vec<Vec<usize>> = self.cycles().into_iter().filter(|c| c.len() > 1).collect();
+        if cycles.is_empty() {
+            return f.write_str("()");
+        }
+        for cycle in cycles {
+            let elements: Vec<String> = cycle.iter().map(|x| x.to_string()).collect();
+            write!(f, "({})", elements.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn heap_permutations_are_distinct() {
+        let mut factorial = 1;
+        for n in 0..8 {
+            if n > 0 {
+                factorial *= n;
+            }
+            let all: Vec<Vec<usize>> = permutations(n).collect();
+            assert_eq!(all.len(), factorial);
+            let distinct: HashSet<&Vec<usize>> = all.iter().collect();
+            assert_eq!(distinct.len(), factorial);
+            for (p, q) in all.iter().zip(all.iter().skip(1)) {
+                assert_eq!(p.iter().zip(q).filter(|(a, b)| a != b).count(), 2);
+            }
+        }
+    }
+
+    #[test]
+    fn next_and_prev_permutation() {
+        let mut p: Vec<usize> = (0..6).collect();
+        let mut all = vec![p.clone()];
+        while next_permutation(&mut p) {
+            all.push(p.clone());
+        }
+        assert_eq!(all.len(), 720);
+        assert!(all.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(p, [0, 1, 2, 3, 4, 5]);
+        let mut p = all[719].clone();
+        for q in all.iter().rev().skip(1) {
+            assert!(prev_permutation(&mut p));
+            assert_eq!(&p, q);
+        }
+        assert!(!prev_permutation(&mut p));
+        assert_eq!(p, [5, 4, 3, 2, 1, 0]);
+        // multiset permutations of aabbc: 5!/(2! 2!) = 30
+        let mut m = ['a', 'a', 'b', 'b', 'c'];
+        let mut count = 1;
+        while next_permutation(&mut m) {
+            count += 1;
+        }
+        assert_eq!(count, 30);
+        let mut e: [u8; 0] = [];
+        assert!(!next_permutation(&mut e));
+        assert!(!prev_permutation(&mut [1]));
+    }
+
+    #[test]
+    fn permutation_ranks_match_enumeration() {
+        for n in 0..=7usize {
+            let mut p: Vec<usize> = (0..n).collect();
+            let mut rank = 0u128;
+            loop {
+                let code = lehmer_code(&p);
+                for (i, &c) in code.iter().enumerate() {
+                    assert_eq!(c, p[i + 1..].iter().filter(|&&x| x < p[i]).count());
+                }
+                assert_eq!(permutation_from_lehmer_code(&code), p);
+                assert_eq!(rank_permutation(&p), Some(rank));
+                assert_eq!(unrank_permutation(rank, n), Some(p.clone()));
+                rank += 1;
+                if !next_permutation(&mut p) {
+                    break;
+                }
+            }
+            assert_eq!(unrank_permutation(rank, n), None);
+        }
+    }
+
+    #[test]
+    fn permutation_ranks_large() {
+        let n = 100_000;
+        let p: Vec<usize> = (0..n).map(|i| (i * 7919) % n).collect();
+        let code = lehmer_code(&p);
+        assert_eq!(permutation_from_lehmer_code(&code), p);
+        assert_eq!(rank_permutation(&p), None);
+        let reversed: Vec<usize> = (0..34).rev().collect();
+        let last = (1..=34u128).product::<u128>() - 1;
+        assert_eq!(rank_permutation(&reversed), Some(last));
+        assert_eq!(unrank_permutation(last, 34), Some(reversed));
+        assert_eq!(unrank_permutation(u128::MAX, 35).map(|p| rank_permutation(&p)), Some(Some(u128::MAX)));
+        assert_eq!(unrank_permutation(0, 0), Some(vec![]));
+        assert_eq!(unrank_permutation(1, 0), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn lehmer_code_of_non_permutation() {
+        lehmer_code(&[0, 2, 2]);
+    }
+
+    #[test]
+    fn permutation_arithmetic() {
+        let all: Vec<Permutation> = permutations(5).map(Permutation::new).collect();
+        let identity = Permutation::identity(5);
+        for p in &all {
+            let inverse = p.inverse();
+            assert_eq!(p.compose(&inverse), identity);
+            assert_eq!(inverse.compose(p), identity);
+            assert_eq!(Permutation::from_cycles(5, &p.cycles()), *p);
+            assert_eq!(p.cycle_type().iter().sum::<u64>(), 5);
+            assert!(p.cycle_type().windows(2).all(|w| w[0] >= w[1]));
+            for cycle in p.cycles() {
+                assert_eq!(cycle[0], *cycle.iter().min().unwrap());
+                for (i, &x) in cycle.iter().enumerate() {
+                    assert_eq!(p.image(x), cycle[(i + 1) % cycle.len()]);
+                }
+            }
+            let items: Vec<usize> = (10..15).collect();
+            let moved = p.apply(&items);
+            for i in 0..5 {
+                assert_eq!(moved[p.image(i)], items[i]);
+            }
+            for q in all.iter().step_by(7) {
+                let pq = p.compose(q);
+                for i in 0..5 {
+                    assert_eq!(pq.image(i), p.image(q.image(i)));
+                }
+                // applying q and then p is applying p ∘ q
+                assert_eq!(p.apply(&q.apply(&items)), pq.apply(&items));
+            }
+        }
+        // the number of permutations of each cycle type is n!/(prod of k^m_k m_k!)
+        let mut counts = std::collections::HashMap::new();
+        for p in &all {
+            *counts.entry(p.cycle_type()).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 7);
+        assert_eq!(counts[&vec![1, 1, 1, 1, 1]], 1);
+        assert_eq!(counts[&vec![2, 1, 1, 1]], 10);
+        assert_eq!(counts[&vec![5]], 24);
+        assert_eq!(counts[&vec![2, 2, 1]], 15);
+        assert_eq!(identity.to_string(), "()");
+        assert_eq!(Permutation::from_cycles(6, &[vec![4, 0], vec![5, 3, 2]]).to_string(), "(0 4)(2 5 3)");
+        assert!(Permutation::identity(0).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn permutation_with_repeated_image() {
+        Permutation::new(vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn permutation_order_sign_pow() {
+        let all: Vec<Permutation> = permutations(6).map(Permutation::new).collect();
+        let identity = Permutation::identity(6);
+        let mut even = 0;
+        for p in &all {
+            // naive order and powers by repeated composition
+            let mut q = p.clone();
+            let mut order = 1;
+            while q != identity {
+                q = p.compose(&q);
+                order += 1;
+                assert_eq!(p.pow(order), q);
+            }
+            assert_eq!(p.order(), Some(order as u128));
+            assert_eq!(p.pow(0), identity);
+            assert_eq!(p.pow(-1), p.inverse());
+            assert_eq!(p.pow(-5), p.inverse().pow(5));
+            assert_eq!(p.pow(i64::MIN).compose(&p.pow(i64::MAX)), p.inverse());
+            // the sign is the parity of the number of inversions
+            let inversions = (0..6).flat_map(|i| (i + 1..6).map(move |j| (i, j)))
+                .filter(|&(i, j)| p.image(i) > p.image(j)).count();
+            assert_eq!(p.sign(), if inversions.is_multiple_of(2) { 1 } else { -1 });
+            for q in all.iter().step_by(11) {
+                assert_eq!(p.compose(q).sign(), p.sign() * q.sign());
+            }
+            if p.sign() == 1 {
+                even += 1;
+            }
+        }
+        assert_eq!(even, 360);
+        assert_eq!(Permutation::identity(0).order(), Some(1));
+        // cycles of the first 27 primes give an order above u128::MAX
+        let primes = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103];
+        let mut cycles = Vec::new();
+        let mut start = 0;
+        for &p in &primes[..26] {
+            cycles.push((start..start + p).collect());
+            start += p;
+        }
+        let p = Permutation::from_cycles(start, &cycles);
+        assert_eq!(p.order(), primes[..26].iter().try_fold(1u128, |a, &b| a.checked_mul(b as u128)));
+        cycles.push((start..start + 103).collect());
+        assert_eq!(Permutation::from_cycles(start + 103, &cycles).order(), None);
+    }
+}
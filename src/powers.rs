@@ -0,0 +1,164 @@
+use crate::is_prime;
+
+/// Computes the integer `k`-th root of `n`, the largest `r` with `r^k <= n`.
+///
+/// The root is exact for perfect powers. Uses Newton's iteration in integers
+/// starting from a power of two above the root, so no floating point rounding is involved.
+/// Panics if `k` is zero.
+pub fn iroot(n: u64, k: u32) -> u64
+{
+    assert!(k > 0, "zeroth root is not defined");
+    let bits = 64 - n.leading_zeros();
+    if k >= bits {
+        return (n > 0) as u64;
+    }
+    if k == 1 {
+        return n;
+    }
+    // start above the root, Newton's iteration then decreases monotonically towards it
+    let (n, k) = (n as u128, k as u128);
+    let mut x: u128 = 1 << bits.div_ceil(k as u32);
+    loop {
+        let power = x.checked_pow(k as u32 - 1).unwrap_or(u128::MAX);
+        let next = ((k - 1) * x + n / power) / k;
+        if next >= x {
+            return x as u64;
+        }
+        x = next;
+    }
+}
+
+/// Returns `Some((p, k))` if `n = p^k` for a prime `p` and `k >= 1`. Otherwise returns `None`.
+///
+/// Tries every exponent `k` with `2^k <= n`, taking the integer `k`-th root and testing it by [is_prime].
+pub fn as_prime_power(n: u64) -> Option<(u64, u32)>
+{
+    let bits = 64 - n.leading_zeros();
+    (1..bits).find_map(|k| {
+        let p = iroot(n, k);
+        if p.checked_pow(k) == Some(n) && is_prime(p) { Some((p, k)) } else { None }
+    })
+}
+
+/// Returns `Some((base, exp))` if `n = base^exp` for `exp >= 2`, choosing the largest such exponent.
+/// Otherwise returns `None`.
+///
+/// `0` and `1` are powers with any exponent, so `None` is returned for them as well.
+pub fn as_perfect_power(n: u64) -> Option<(u64, u32)>
+{
+    if n < 2 {
+        return None;
+    }
+    let bits = 64 - n.leading_zeros();
+    (2..bits).rev().find_map(|k| {
+        let base = iroot(n, k);
+        if base.pow(k) == n { Some((base, k)) } else { None }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn test_iroot(n: u64, k: u32) {
+        let r = iroot(n, k) as u128;
+        assert!(r.pow(k) <= n as u128, "{} {}", n, k);
+        assert!((r + 1).checked_pow(k).is_none_or(|v| v > n as u128), "{} {}", n, k);
+    }
+
+    #[test]
+    fn iroot_values() {
+        for n in 0..2000 {
+            for k in 1..12 {
+                test_iroot(n, k);
+            }
+        }
+        for k in 1..70 {
+            for &n in &[u64::MAX, u64::MAX - 1, 1 << 63, (1 << 63) - 1, 4_294_967_296, 4_294_967_295, 18_446_744_030_759_878_681] {
+                test_iroot(n, k);
+            }
+        }
+        assert_eq!(iroot(u64::MAX, 2), 4_294_967_295);
+        assert_eq!(iroot(1_000_000_000_000_000_000, 3), 1_000_000);
+        assert_eq!(iroot(999_999_999_999_999_999, 3), 999_999);
+    }
+    #[test]
+    fn as_prime_power_brute_force() {
+        let mut expected = vec![None; 5000];
+        for p in (2..5000u64).filter(|&p| is_prime(p)) {
+            let mut power = p;
+            for k in 1.. {
+                expected[power as usize] = Some((p, k));
+                power *= p;
+                if power >= 5000 {
+                    break;
+                }
+            }
+        }
+        for n in 0..5000 {
+            assert_eq!(as_prime_power(n), expected[n as usize], "{}", n);
+        }
+    }
+    #[test]
+    fn as_prime_power_large() {
+        assert_eq!(as_prime_power(1 << 63), Some((2, 63)));
+        assert_eq!(as_prime_power(3u64.pow(40)), Some((3, 40)));
+        assert_eq!(as_prime_power(4_294_967_291 * 4_294_967_291), Some((4_294_967_291, 2)));
+        assert_eq!(as_prime_power(18_446_744_073_709_551_557), Some((18_446_744_073_709_551_557, 1)));
+        assert_eq!(as_prime_power(4_294_967_291 * 4_294_967_279), None);
+        assert_eq!(as_prime_power(6u64.pow(24)), None);
+        assert_eq!(as_prime_power(u64::MAX), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn iroot_zero() {
+        iroot(10, 0);
+    }
+    #[test]
+    fn iroot_perfect_powers() {
+        for k in 1..64 {
+            for base in 1..=iroot(u64::MAX, k).min(100_000) {
+                let n = base.pow(k);
+                assert_eq!(iroot(n, k), base);
+                assert_eq!(iroot(n - 1, k), base - 1);
+                if let Some(m) = n.checked_add(1) {
+                    assert_eq!(iroot(m, k), if k == 1 { base + 1 } else { base });
+                }
+            }
+        }
+        assert_eq!(iroot(u64::MAX, 64), 1);
+        assert_eq!(iroot(0, 5), 0);
+    }
+    #[test]
+    fn as_perfect_power_brute_force() {
+        let mut expected = vec![None; 100_000];
+        for base in 2..400u64 {
+            let mut power = base * base;
+            for k in 2.. {
+                if power >= 100_000 {
+                    break;
+                }
+                if expected[power as usize].is_none_or(|(_, e)| e < k) {
+                    expected[power as usize] = Some((base, k));
+                }
+                power *= base;
+            }
+        }
+        for n in 0..100_000 {
+            assert_eq!(as_perfect_power(n), expected[n as usize], "{}", n);
+        }
+    }
+    #[test]
+    fn as_perfect_power_large() {
+        assert_eq!(as_perfect_power(1 << 63), Some((2, 63)));
+        assert_eq!(as_perfect_power(6u64.pow(24)), Some((6, 24)));
+        assert_eq!(as_perfect_power(36u64.pow(12)), Some((6, 24)));
+        assert_eq!(as_perfect_power(4_294_967_295 * 4_294_967_295), Some((4_294_967_295, 2)));
+        assert_eq!(as_perfect_power(2_642_245u64.pow(3)), Some((2_642_245, 3)));
+        assert_eq!(as_perfect_power(2_642_245u64.pow(3) - 1), None);
+        assert_eq!(as_perfect_power(u64::MAX), None);
+        assert_eq!(as_perfect_power(1), None);
+    }
+}
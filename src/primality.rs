@@ -0,0 +1,350 @@
+use crate::mod_mul;
+use crate::residues::jacobi_u128;
+use crate::wide::Montgomery;
+use crate::wheel::prev_wheel_candidate;
+use crate::Wheel;
+
+/// Primes used for trial division before the probabilistic tests.
+pub(crate) const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Miller-Rabin bases which give a deterministic test for every `u64`.
+const MILLER_RABIN_BASES: [u64; 7] = [2, 325, 9375, 28178, 450775, 9780504, 1795265022];
+
+/// Returns true if `n` is a prime number. Otherwise returns false.
+///
+/// Uses trial division by small primes followed by Miller-Rabin test with a fixed set of bases,
+/// which is deterministic for every `u64`.
+pub fn is_prime(n: u64) -> bool
+{
+    if n < 2 {
+        return false;
+    }
+    for &p in &SMALL_PRIMES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    if n < 41 * 41 {
+        return true;
+    }
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    MILLER_RABIN_BASES.iter().all(|&a| a % n == 0 || is_strong_probable_prime_u64(n, a % n, d, s))
+}
+
+fn is_strong_probable_prime_u64(n: u64, a: u64, d: u64, s: u32) -> bool
+{
+    let mut x = crate::mod_pow(a, d, n);
+    if x == 1 || x == n - 1 {
+        return true;
+    }
+    for _ in 1..s {
+        x = mod_mul(x, x, n);
+        if x == n - 1 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Largest prime fitting in `u64`.
+const LARGEST_PRIME_U64: u64 = 18_446_744_073_709_551_557;
+
+/// Computes the smallest prime strictly greater than `n`.
+///
+/// Only candidates produced by the [Wheel] are tested by [is_prime].
+/// Returns `None` if there is no such prime in `u64`, that is for `n >= 18446744073709551557`.
+pub fn next_prime(n: u64) -> Option<u64>
+{
+    if n < 7 {
+        return Some([2, 2, 3, 5, 5, 7, 7][n as usize]);
+    }
+    if n >= LARGEST_PRIME_U64 {
+        return None;
+    }
+    Wheel::starting_at(n + 1).find(|&candidate| is_prime(candidate))
+}
+
+/// Computes the largest prime strictly less than `n`.
+///
+/// Only candidates produced by the [Wheel] are tested by [is_prime].
+/// Returns `None` if there is no such prime, that is for `n <= 2`.
+pub fn prev_prime(n: u64) -> Option<u64>
+{
+    if n <= 11 {
+        return [None, None, None, Some(2), Some(3), Some(3), Some(5), Some(5), Some(7), Some(7), Some(7), Some(7)][n as usize];
+    }
+    let mut candidate = n;
+    loop {
+        candidate = prev_wheel_candidate(candidate);
+        if is_prime(candidate) {
+            return Some(candidate);
+        }
+    }
+}
+
+/// Generates a uniformly random prime with exactly `bits` bits, that is in `[2^(bits-1), 2^bits)`.
+///
+/// Candidates are sampled from `rng` and tested by [is_prime] until a prime is found.
+/// Available with the `rand` feature.
+///
+/// Panics if `bits` is not in `[2, 64]`.
+#[cfg(feature = "rand")]
+pub fn random_prime<R: rand::Rng + ?Sized>(bits: u32, rng: &mut R) -> u64
+{
+    assert!((2..=64).contains(&bits), "bits must be in [2, 64]");
+    let (lo, hi) = (1u64 << (bits - 1), u64::MAX >> (64 - bits));
+    loop {
+        let candidate = rng.gen_range(lo..=hi) | (bits > 2) as u64;
+        if is_prime(candidate) {
+            return candidate;
+        }
+    }
+}
+
+/// Generates a random safe prime `p = 2q + 1`, where `q` is also prime, with exactly `bits` bits.
+///
+/// Samples primes `q` with `bits - 1` bits by [random_prime] until `2q + 1` is prime.
+/// Available with the `rand` feature.
+///
+/// Panics if `bits` is not in `[3, 64]`.
+#[cfg(feature = "rand")]
+pub fn random_safe_prime<R: rand::Rng + ?Sized>(bits: u32, rng: &mut R) -> u64
+{
+    assert!((3..=64).contains(&bits), "bits must be in [3, 64]");
+    loop {
+        let q = random_prime(bits - 1, rng);
+        if is_prime(2 * q + 1) {
+            return 2 * q + 1;
+        }
+    }
+}
+
+/// Returns true if `n` is a probable prime according to the Baillie-PSW test. Otherwise returns false.
+///
+/// The test consists of a strong Fermat test to base 2 followed by a strong Lucas test with parameters
+/// chosen by Selfridge's method. No composite number passing both tests is known.
+/// Inputs fitting in `u64` are decided exactly by [is_prime].
+pub fn is_probable_prime(n: u128) -> bool
+{
+    if n <= u64::MAX as u128 {
+        return is_prime(n as u64);
+    }
+    if SMALL_PRIMES.iter().any(|&p| n.is_multiple_of(p as u128)) {
+        return false;
+    }
+    let mont = Montgomery::new(n);
+    is_strong_probable_prime_base_2(&mont) && is_strong_lucas_probable_prime(&mont)
+}
+
+fn is_strong_probable_prime_base_2(mont: &Montgomery) -> bool
+{
+    let n = mont.modulus();
+    let s = (n - 1).trailing_zeros();
+    let one = mont.one();
+    let minus_one = mont.sub(0, one);
+    let mut x = mont.pow(mont.add(one, one), (n - 1) >> s);
+    if x == one || x == minus_one {
+        return true;
+    }
+    for _ in 1..s {
+        x = mont.mul(x, x);
+        if x == minus_one {
+            return true;
+        }
+    }
+    false
+}
+
+/// Strong Lucas test with `P = 1` and `D`, `Q = (1 - D)/4` chosen by Selfridge's method.
+/// Expects odd `n` without small prime factors.
+fn is_strong_lucas_probable_prime(mont: &Montgomery) -> bool
+{
+    let n = mont.modulus();
+    let root = n.isqrt();
+    if root * root == n {
+        return false;
+    }
+    let d = match selfridge_parameter(|d| jacobi_u128(signed_mod(d, n), n)) {
+        Some(d) => d,
+        None => return false
+    };
+    let d_mont = mont.encode(signed_mod(d, n));
+    let q_mont = mont.encode(signed_mod((1 - d) / 4, n));
+    let s = (n + 1).trailing_zeros();
+    let k = (n + 1) >> s;
+    let one = mont.one();
+    // U_1 = 1, V_1 = P = 1
+    let (mut u, mut v, mut q_k) = (one, one, q_mont);
+    for bit in (0..127 - k.leading_zeros()).rev() {
+        u = mont.mul(u, v);
+        v = mont.sub(mont.mul(v, v), mont.add(q_k, q_k));
+        q_k = mont.mul(q_k, q_k);
+        if (k >> bit) & 1 == 1 {
+            let u_next = mont.half(mont.add(u, v));
+            v = mont.half(mont.add(mont.mul(d_mont, u), v));
+            u = u_next;
+            q_k = mont.mul(q_k, q_mont);
+        }
+    }
+    if u == 0 || v == 0 {
+        return true;
+    }
+    for _ in 1..s {
+        v = mont.sub(mont.mul(v, v), mont.add(q_k, q_k));
+        q_k = mont.mul(q_k, q_k);
+        if v == 0 {
+            return true;
+        }
+    }
+    false
+}
+
+/// Finds the first `D` in the sequence `5, -7, 9, -11, ...` with Jacobi symbol `(D/n) = -1`.
+///
+/// Returns `None` if some `D` shares a factor with `n`, which proves `n` composite
+/// for `n` larger than the tried values of `D`.
+pub(crate) fn selfridge_parameter<F: Fn(i64) -> i8>(jacobi_of: F) -> Option<i64>
+{
+    let mut d = 5i64;
+    loop {
+        match jacobi_of(d) {
+            -1 => return Some(d),
+            0 => return None,
+            _ => d = if d > 0 { -(d + 2) } else { -d + 2 }
+        }
+    }
+}
+
+/// Computes `x mod n` for a small signed `x`.
+fn signed_mod(x: i64, n: u128) -> u128
+{
+    if x >= 0 { x as u128 % n } else { n - (x.unsigned_abs() as u128 % n) }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn is_prime_naive(n: u64) -> bool {
+        n >= 2 && (2..).take_while(|d| d * d <= n).all(|d| !n.is_multiple_of(d))
+    }
+
+    #[test]
+    fn is_prime_small() {
+        for n in 0..10_000 {
+            assert_eq!(is_prime(n), is_prime_naive(n), "{}", n);
+        }
+    }
+    #[test]
+    fn is_prime_strong_pseudoprimes() {
+        // strong pseudoprimes to several of the smallest prime bases
+        for &n in &[2047u64, 1_373_653, 25_326_001, 3_215_031_751, 2_152_302_898_747, 3_474_749_660_383,
+            341_550_071_728_321, 3_825_123_056_546_413_051] {
+            assert!(!is_prime(n), "{}", n);
+        }
+    }
+    #[test]
+    fn is_prime_large() {
+        assert!(is_prime(18_446_744_073_709_551_557));
+        assert!(is_prime(1_000_000_007));
+        assert!(is_prime((1 << 61) - 1));
+        assert!(!is_prime(u64::MAX));
+        assert!(!is_prime(4_294_967_291 * 4_294_967_279));
+    }
+
+    #[test]
+    fn next_prev_prime_brute_force() {
+        for n in 0..2000u64 {
+            assert_eq!(next_prime(n), (n + 1..).find(|&p| is_prime(p)), "{}", n);
+            assert_eq!(prev_prime(n), (0..n).rev().find(|&p| is_prime(p)), "{}", n);
+        }
+    }
+    #[test]
+    fn next_prev_prime_u64_max() {
+        let p = LARGEST_PRIME_U64;
+        assert_eq!(next_prime(p - 1), Some(p));
+        assert_eq!(next_prime(p), None);
+        assert_eq!(next_prime(u64::MAX), None);
+        assert_eq!(prev_prime(u64::MAX), Some(p));
+        assert_eq!(prev_prime(p + 1), Some(p));
+        assert_eq!(prev_prime(p), Some(18_446_744_073_709_551_533));
+    }
+    #[test]
+    fn next_prev_prime_large_gap() {
+        // maximal prime gap of 1132 following 1693182318746371
+        assert_eq!(next_prime(1_693_182_318_746_371), Some(1_693_182_318_747_503));
+        assert_eq!(prev_prime(1_693_182_318_747_503), Some(1_693_182_318_746_371));
+    }
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_prime_bits() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for bits in 2..=64 {
+            let p = random_prime(bits, &mut rng);
+            assert!(is_prime(p));
+            assert_eq!(64 - p.leading_zeros(), bits);
+        }
+    }
+    #[cfg(feature = "rand")]
+    #[test]
+    fn random_safe_prime_bits() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(11);
+        for bits in 3..=64 {
+            let p = random_safe_prime(bits, &mut rng);
+            assert!(is_prime(p) && is_prime(p / 2));
+            assert_eq!(64 - p.leading_zeros(), bits);
+        }
+    }
+    #[cfg(feature = "rand")]
+    #[test]
+    #[should_panic]
+    fn random_prime_invalid_bits() {
+        random_prime(65, &mut rand::thread_rng());
+    }
+    #[test]
+    fn strong_lucas_pseudoprimes() {
+        // composites passing the strong Lucas test, they must be caught by the base 2 test
+        for &n in &[5459u128, 5777, 10877, 16109, 18971, 22499, 24569, 25199, 40309, 58519] {
+            assert!(is_strong_lucas_probable_prime(&Montgomery::new(n)), "{}", n);
+            assert!(!is_strong_probable_prime_base_2(&Montgomery::new(n)), "{}", n);
+            assert!(!is_probable_prime(n));
+        }
+    }
+    #[test]
+    fn strong_lucas_primes() {
+        for n in (41..5000u128).step_by(2) {
+            if is_prime(n as u64) {
+                assert!(is_strong_lucas_probable_prime(&Montgomery::new(n)), "{}", n);
+            }
+        }
+    }
+    #[test]
+    fn is_probable_prime_matches_is_prime() {
+        for n in 0..5000u128 {
+            assert_eq!(is_probable_prime(n), is_prime(n as u64));
+        }
+        assert!(is_probable_prime(18_446_744_073_709_551_557));
+    }
+    #[test]
+    fn is_probable_prime_128_bit() {
+        assert!(is_probable_prime((1 << 89) - 1));
+        assert!(is_probable_prime((1 << 107) - 1));
+        assert!(is_probable_prime((1 << 127) - 1));
+        assert!(is_probable_prime(u128::MAX - 158));
+        assert!(is_probable_prime(18_446_744_073_709_551_629)); // smallest prime above 2^64
+    }
+    #[test]
+    fn is_probable_prime_128_bit_composites() {
+        assert!(!is_probable_prime(u128::MAX));
+        assert!(!is_probable_prime((1 << 101) - 1));
+        assert!(!is_probable_prime(18_446_744_073_709_551_557 * 18_446_744_073_709_551_557));
+        assert!(!is_probable_prime(18_446_744_073_709_551_557 * ((1 << 61) - 1)));
+        assert!(!is_probable_prime(1_000_000_007 * 1_000_000_009 * 998_244_353 * 1_000_000_021));
+        // a strong pseudoprime to bases 2 to 37, caught by the Lucas test
+        assert!(!is_probable_prime(318_665_857_834_031_151_167_461));
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::convert::TryFrom;
+
+use crate::factorize;
+use crate::squares::prime_as_two_squares;
+
+/// Returns an iterator through all primitive Pythagorean triples `(a, b, c)` with `a^2 + b^2 = c^2`, `a < b`
+/// and `gcd(a, b) = 1`, in increasing order of `c` and then `a`.
+///
+/// The triples are generated lazily from Berggren's ternary tree rooted at `(3, 4, 5)` using a binary heap.
+/// Every primitive triple appears exactly once in the tree and its children have larger hypotenuses.
+/// The iterator ends when the next hypotenuse would not fit in `u64`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let triples: Vec<_> = pythagorean_triples().take(4).collect();
+/// assert_eq!(triples, [(3, 4, 5), (5, 12, 13), (8, 15, 17), (7, 24, 25)]);
+/// ```
+pub fn pythagorean_triples() -> PythagoreanTriples
+{
+    let mut heap = BinaryHeap::new();
+    heap.push(Reverse((5, 3, 4)));
+    PythagoreanTriples { heap }
+}
+
+/// Iterator through the primitive Pythagorean triples. Go to [pythagorean_triples] for further information.
+#[derive(Debug, Clone)]
+pub struct PythagoreanTriples
+{
+    // (c, a, b) with a < b
+    heap: BinaryHeap<Reverse<(u64, u64, u64)>>
+}
+
+impl Iterator for PythagoreanTriples {
+    type Item = (u64, u64, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((c, a, b)) = self.heap.pop()?;
+        let (a, b, c) = (a as i128, b as i128, c as i128);
+        // swapping the legs swaps the first and the last child, so the order of the legs does not matter
+        let children = [
+            (a - 2 * b + 2 * c, 2 * a - b + 2 * c, 2 * a - 2 * b + 3 * c),
+            (a + 2 * b + 2 * c, 2 * a + b + 2 * c, 2 * a + 2 * b + 3 * c),
+            (-a + 2 * b + 2 * c, -2 * a + b + 2 * c, -2 * a + 2 * b + 3 * c)
+        ];
+        for &(x, y, z) in children.iter() {
+            if let Ok(z) = u64::try_from(z) {
+                self.heap.push(Reverse((z, x.min(y) as u64, x.max(y) as u64)));
+            }
+        }
+        Some((a as u64, b as u64, c as u64))
+    }
+}
+
+/// Finds all Pythagorean triples `(a, b, c)` with `a^2 + b^2 = c^2` and `0 < a < b`, including non-primitive ones,
+/// in increasing order of `a`.
+///
+/// Every such triple is `k` times a primitive triple whose hypotenuse `c/k` is a product of primes `p ≡ 1 (mod 4)`.
+/// The primitive triples with hypotenuse `m^2 + n^2` are `(m^2 - n^2, 2mn)`, where `m + ni` runs through
+/// the Gaussian integers of that norm built from the splittings of its primes.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(pythagorean_triples_with_hypotenuse(25), [(7, 24, 25), (15, 20, 25)]);
+/// assert_eq!(pythagorean_triples_with_hypotenuse(21), []);
+/// ```
+pub fn pythagorean_triples_with_hypotenuse(c: u64) -> Vec<(u64, u64, u64)>
+{
+    if c == 0 {
+        return Vec::new();
+    }
+    // Gaussian integers m + ni together with their norm m^2 + n^2
+    let mut gaussian = vec![(1i128, 0i128, 1u64)];
+    for &(p, e) in factorize(c).factors().iter().filter(|&&(p, _)| p % 4 == 1) {
+        let (x, y) = prime_as_two_squares(p);
+        let mut next = Vec::new();
+        for &(m, n, norm) in &gaussian {
+            next.push((m, n, norm));
+            for &y in [y as i128, -(y as i128)].iter() {
+                let (mut m, mut n, mut norm) = (m, n, norm);
+                for _ in 0..e {
+                    let next_m = m * x as i128 - n * y;
+                    n = m * y + n * x as i128;
+                    m = next_m;
+                    norm *= p;
+                    next.push((m, n, norm));
+                }
+            }
+        }
+        gaussian = next;
+    }
+    let mut res: Vec<(u64, u64, u64)> = gaussian.into_iter().filter(|&(_, _, norm)| norm > 1).map(|(m, n, norm)| {
+        let (m, n) = (m.unsigned_abs() as u64, n.unsigned_abs() as u64);
+        let k = c / norm;
+        let (x, y) = (m.abs_diff(n) * (m + n) * k, 2 * m * n * k);
+        (x.min(y), x.max(y), c)
+    }).collect();
+    res.sort_unstable();
+    res.dedup();
+    res
+}
+
+/// Finds all Pythagorean triples `(a, b, c)` with the given leg `a`, that is `a^2 + b^2 = c^2` and `b > 0`,
+/// including non-primitive ones, in increasing order of `b`.
+///
+/// Each triple corresponds to a factorization `a^2 = (c - b)(c + b)` into two factors of the same parity.
+/// Triples whose hypotenuse does not fit in `u64` are left out. Returns an empty vector for `a = 0`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(pythagorean_triples_with_leg(12), [(12, 5, 13), (12, 9, 15), (12, 16, 20), (12, 35, 37)]);
+/// assert_eq!(pythagorean_triples_with_leg(2), []);
+/// ```
+pub fn pythagorean_triples_with_leg(a: u64) -> Vec<(u64, u64, u64)>
+{
+    if a == 0 {
+        return Vec::new();
+    }
+    let square = a as u128 * a as u128;
+    let mut divisors = vec![1u128];
+    for &(p, e) in factorize(a).factors() {
+        let len = divisors.len();
+        let mut power = 1;
+        for _ in 0..2 * e {
+            power *= p as u128;
+            for i in 0..len {
+                divisors.push(divisors[i] * power);
+            }
+        }
+    }
+    let mut res: Vec<(u64, u64, u64)> = divisors.into_iter().filter(|&d| d < a as u128).filter_map(|d| {
+        let e = square / d;
+        if (e - d) % 2 == 1 {
+            return None;
+        }
+        let c = u64::try_from((e + d) / 2).ok()?;
+        Some((a, ((e - d) / 2) as u64, c))
+    }).collect();
+    res.sort_unstable();
+    res
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn triples_naive(limit: u64) -> Vec<(u64, u64, u64)> {
+        let mut res = Vec::new();
+        for c in 1..limit {
+            for a in 1..c {
+                let b2 = c * c - a * a;
+                let b = b2.isqrt();
+                if a < b && b * b == b2 {
+                    res.push((a, b, c));
+                }
+            }
+        }
+        res
+    }
+
+    #[test]
+    fn pythagorean_triples_brute_force() {
+        let expected: Vec<_> = triples_naive(1000).into_iter().filter(|&(a, b, _)| crate::gcd(a, b) == 1).collect();
+        let triples: Vec<_> = pythagorean_triples().take_while(|&(_, _, c)| c < 1000).collect();
+        assert_eq!(triples, expected);
+    }
+    #[test]
+    fn pythagorean_triples_ends() {
+        let c = 18_446_744_073_709_551_557;
+        let (a, b, _) = pythagorean_triples_with_hypotenuse(c)[0];
+        let mut triples = pythagorean_triples();
+        triples.heap = BinaryHeap::new();
+        triples.heap.push(Reverse((c, a, b)));
+        assert_eq!(triples.next(), Some((a, b, c)));
+        assert!(triples.all(|(_, _, z)| z > c));
+    }
+    #[test]
+    fn pythagorean_triples_with_hypotenuse_brute_force() {
+        let naive = triples_naive(700);
+        for c in 0..700 {
+            let expected: Vec<_> = naive.iter().cloned().filter(|&(_, _, z)| z == c).collect();
+            assert_eq!(pythagorean_triples_with_hypotenuse(c), expected, "{}", c);
+        }
+    }
+    #[test]
+    fn pythagorean_triples_with_hypotenuse_large() {
+        // 5^2 * 13 * 17 * 29 * 37 * 41 has (5 * 3^5 - 1) / 2 triples
+        let c = 25 * 13 * 17 * 29 * 37 * 41;
+        let triples = pythagorean_triples_with_hypotenuse(c);
+        assert_eq!(triples.len(), 607);
+        assert!(triples.iter().all(|&(a, b, z)| a < b && z == c && a as u128 * a as u128 + b as u128 * b as u128 == c as u128 * c as u128));
+        let c = 18_446_744_073_709_551_557; // prime ≡ 1 mod 4
+        let triples = pythagorean_triples_with_hypotenuse(c);
+        assert_eq!(triples.len(), 1);
+        let (a, b, _) = triples[0];
+        assert_eq!(a as u128 * a as u128 + b as u128 * b as u128, c as u128 * c as u128);
+        assert_eq!(pythagorean_triples_with_hypotenuse(3 << 40), []);
+    }
+    #[test]
+    fn pythagorean_triples_with_leg_brute_force() {
+        let naive = triples_naive(1500);
+        for a in 0..300 {
+            let mut expected: Vec<_> = naive.iter().filter_map(|&(x, y, z)| {
+                if x == a { Some((x, y, z)) } else if y == a { Some((y, x, z)) } else { None }
+            }).collect();
+            expected.sort_unstable();
+            // legs up to 300 have hypotenuses below 300^2 / 2, which the naive search does not reach for the largest ones
+            let res: Vec<_> = pythagorean_triples_with_leg(a).into_iter().filter(|&(_, _, c)| c < 1500).collect();
+            assert_eq!(res, expected, "{}", a);
+        }
+    }
+    #[test]
+    fn pythagorean_triples_with_leg_large() {
+        let a = u32::MAX as u64;
+        let triples = pythagorean_triples_with_leg(a);
+        assert!(triples.iter().all(|&(x, b, c)| x == a && (c as u128 * c as u128 - b as u128 * b as u128) == a as u128 * a as u128));
+        // odd a has a^2 = 1 * a^2 giving b = (a^2 - 1) / 2
+        assert_eq!(triples.last(), Some(&(a, (a * a - 1) / 2, (a * a).div_ceil(2))));
+        // a = 2^40 gives c = 2^(79 - i) + 2^(i - 1) for 1 <= i < 40, which fits in u64 for i >= 16
+        assert_eq!(pythagorean_triples_with_leg(1 << 40).len(), 24);
+    }
+}
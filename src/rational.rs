@@ -0,0 +1,342 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::num::ParseIntError;
+use std::ops::{Add, Sub, Mul, Div, Rem, Neg, AddAssign, SubAssign, MulAssign, DivAssign, RemAssign};
+use std::str::FromStr;
+
+use crate::gcd;
+
+/// Rational number `numer/denom` with `i64` numerator and denominator.
+///
+/// The value is always kept in lowest terms with a positive denominator, so the derived equality and hashing
+/// compare values. Intermediate results of the arithmetic are computed in `i128` and reduced by [gcd],
+/// so operations only fail if the reduced result does not fit. The operators panic in that case,
+/// the `checked_*` methods return `None` instead.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
+pub struct Rational
+{
+    numer: i64,
+    denom: i64
+}
+
+impl Rational {
+    /// Creates the rational number `numer/denom` in lowest terms.
+    ///
+    /// Panics if `denom` is zero or the reduced value does not fit, which only happens for `i64::MIN/-1`.
+    pub fn new(numer: i64, denom: i64) -> Self {
+        assert!(denom != 0, "denominator must be nonzero");
+        Self::reduce(numer as i128, denom as i128).expect("rational overflow")
+    }
+
+    /// Reduces `numer/denom` for nonzero `denom`. Returns `None` if the result does not fit.
+    fn reduce(numer: i128, denom: i128) -> Option<Self> {
+        let g = gcd(numer, denom) * denom.signum();
+        Some(Rational { numer: i64::try_from(numer / g).ok()?, denom: i64::try_from(denom / g).ok()? })
+    }
+
+    /// Returns the numerator, which carries the sign.
+    pub fn numer(self) -> i64 {
+        self.numer
+    }
+
+    /// Returns the denominator, which is always positive.
+    pub fn denom(self) -> i64 {
+        self.denom
+    }
+
+    /// Returns `true` if the denominator is 1.
+    pub fn is_integer(self) -> bool {
+        self.denom == 1
+    }
+
+    /// Returns the largest integer not greater than `self`.
+    pub fn floor(self) -> i64 {
+        self.numer.div_euclid(self.denom)
+    }
+
+    /// Returns the smallest integer not less than `self`. Panics if it does not fit.
+    pub fn ceil(self) -> i64 {
+        i64::try_from(-(-(self.numer as i128)).div_euclid(self.denom as i128)).expect("rational overflow")
+    }
+
+    /// Returns the absolute value. Panics if it does not fit.
+    pub fn abs(self) -> Self {
+        if self.numer < 0 { -self } else { self }
+    }
+
+    /// Returns `1/self`. Returns `None` if `self` is zero or the result does not fit.
+    pub fn recip(self) -> Option<Self> {
+        if self.numer == 0 {
+            return None;
+        }
+        Self::reduce(self.denom as i128, self.numer as i128)
+    }
+
+    /// Computes `self^exp`, negative exponents raise the reciprocal.
+    /// Returns `None` if `self` is zero and `exp` negative or the result does not fit.
+    pub fn checked_pow(self, exp: i32) -> Option<Self> {
+        let base = if exp < 0 { self.recip()? } else { self };
+        let exp = exp.unsigned_abs();
+        // numerator and denominator stay coprime, so no reduction is needed
+        Some(Rational { numer: base.numer.checked_pow(exp)?, denom: base.denom.checked_pow(exp)? })
+    }
+
+    /// Computes `self + rhs`. Returns `None` if the result does not fit.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let (a, b, c, d) = self.widen(rhs);
+        Self::reduce(a * d + c * b, b * d)
+    }
+
+    /// Computes `self - rhs`. Returns `None` if the result does not fit.
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        let (a, b, c, d) = self.widen(rhs);
+        Self::reduce(a * d - c * b, b * d)
+    }
+
+    /// Computes `self * rhs`. Returns `None` if the result does not fit.
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let (a, b, c, d) = self.widen(rhs);
+        Self::reduce(a * c, b * d)
+    }
+
+    /// Computes `self / rhs`. Returns `None` if `rhs` is zero or the result does not fit.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.numer == 0 {
+            return None;
+        }
+        let (a, b, c, d) = self.widen(rhs);
+        Self::reduce(a * d, b * c)
+    }
+
+    /// Computes the remainder of `self / rhs` truncated towards zero, with the sign of `self` like for integers.
+    /// Returns `None` if `rhs` is zero or the result does not fit.
+    pub fn checked_rem(self, rhs: Self) -> Option<Self> {
+        if rhs.numer == 0 {
+            return None;
+        }
+        let (a, b, c, d) = self.widen(rhs);
+        Self::reduce((a * d) % (c * b), b * d)
+    }
+
+    fn widen(self, rhs: Self) -> (i128, i128, i128, i128) {
+        (self.numer as i128, self.denom as i128, rhs.numer as i128, rhs.denom as i128)
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Rational { numer: 0, denom: 1 }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational { numer: value, denom: 1 }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, c, d) = self.widen(*other);
+        (a * d).cmp(&(c * b))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.denom == 1 {
+            write!(f, "{}", self.numer)
+        } else {
+            write!(f, "{}/{}", self.numer, self.denom)
+        }
+    }
+}
+
+/// Error returned when parsing a [Rational] fails.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ParseRationalError
+{
+    /// The numerator or the denominator is not a valid `i64`.
+    Integer(ParseIntError),
+    ZeroDenominator,
+    /// The reduced value does not fit.
+    Overflow
+}
+
+impl fmt::Display for ParseRationalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseRationalError::Integer(e) => e.fmt(f),
+            ParseRationalError::ZeroDenominator => f.write_str("denominator is zero"),
+            ParseRationalError::Overflow => f.write_str("rational number does not fit")
+        }
+    }
+}
+
+impl std::error::Error for ParseRationalError {}
+
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    /// Parses `p/q` or an integer `p`, the fraction does not have to be reduced.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (numer, denom) = match s.split_once('/') {
+            Some((numer, denom)) => (numer, denom),
+            None => (s, "1")
+        };
+        let numer: i64 = numer.parse().map_err(ParseRationalError::Integer)?;
+        let denom: i64 = denom.parse().map_err(ParseRationalError::Integer)?;
+        if denom == 0 {
+            return Err(ParseRationalError::ZeroDenominator);
+        }
+        Self::reduce(numer as i128, denom as i128).ok_or(ParseRationalError::Overflow)
+    }
+}
+
+macro_rules! impl_rational_ops {
+    ($($trait:ident $method:ident $checked:ident $assign_trait:ident $assign_method:ident)*) => {$(
+        impl $trait for Rational {
+            type Output = Self;
+
+            /// Panics if the result does not fit or on division by zero.
+            fn $method(self, rhs: Self) -> Self {
+                self.$checked(rhs).expect("rational overflow or division by zero")
+            }
+        }
+
+        impl $assign_trait for Rational {
+            fn $assign_method(&mut self, rhs: Self) {
+                *self = self.$method(rhs);
+            }
+        }
+    )*}
+}
+
+impl_rational_ops!(
+    Add add checked_add AddAssign add_assign
+    Sub sub checked_sub SubAssign sub_assign
+    Mul mul checked_mul MulAssign mul_assign
+    Div div checked_div DivAssign div_assign
+    Rem rem checked_rem RemAssign rem_assign
+);
+
+impl Neg for Rational {
+    type Output = Self;
+
+    /// Panics if the result does not fit, which only happens for `i64::MIN`.
+    fn neg(self) -> Self {
+        Rational { numer: self.numer.checked_neg().expect("rational overflow"), denom: self.denom }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn r(numer: i64, denom: i64) -> Rational {
+        Rational::new(numer, denom)
+    }
+
+    #[test]
+    fn rational_new() {
+        assert_eq!((r(6, -4).numer(), r(6, -4).denom()), (-3, 2));
+        assert_eq!(r(0, -5), Rational::default());
+        assert_eq!(r(i64::MIN, i64::MIN), r(1, 1));
+        assert_eq!(r(i64::MIN, 2).numer(), i64::MIN / 2);
+        assert_eq!(r(7, 1), Rational::from(7));
+        assert!(r(8, 4).is_integer() && !r(8, 3).is_integer());
+    }
+    #[test]
+    #[should_panic]
+    fn rational_new_overflow() {
+        r(i64::MIN, -1);
+    }
+    #[test]
+    #[should_panic]
+    fn rational_zero_denominator() {
+        r(1, 0);
+    }
+    #[test]
+    fn rational_arithmetic_brute_force() {
+        let values: Vec<(i64, i64)> = (-6..=6).flat_map(|a| (1..=6).map(move |b| (a, b))).collect();
+        for &(a, b) in &values {
+            for &(c, d) in &values {
+                let (x, y) = (r(a, b), r(c, d));
+                assert_eq!(x + y, r(a * d + c * b, b * d));
+                assert_eq!(x - y, r(a * d - c * b, b * d));
+                assert_eq!(x * y, r(a * c, b * d));
+                assert_eq!(x.cmp(&y), (a * d).cmp(&(c * b)));
+                if c != 0 {
+                    assert_eq!(x / y, r(a * d, b * c));
+                    let q = (x / y).numer() / (x / y).denom();
+                    assert_eq!(x % y, x - y * Rational::from(q), "{} {}", x, y);
+                } else {
+                    assert_eq!(x.checked_div(y), None);
+                    assert_eq!(x.checked_rem(y), None);
+                }
+            }
+        }
+    }
+    #[test]
+    fn rational_large() {
+        let big = r(i64::MAX, i64::MAX - 1);
+        assert_eq!(big * big.recip().unwrap(), r(1, 1));
+        assert_eq!(big - big, r(0, 1));
+        assert_eq!(big.checked_add(big), Some(r(i64::MAX, (i64::MAX - 1) / 2)));
+        assert_eq!(r(i64::MAX, 1).checked_add(r(1, 1)), None);
+        assert_eq!(r(i64::MAX, 2).checked_add(r(i64::MAX, 2)), Some(r(i64::MAX, 1)));
+        assert!(r(i64::MAX - 1, i64::MAX) > r(i64::MAX - 2, i64::MAX - 1));
+        assert_eq!(r(i64::MIN, 1).checked_mul(r(-1, 1)), None);
+        assert_eq!(r(1, 2).checked_pow(62), Some(r(1, 1 << 62)));
+        assert_eq!(r(1, 2).checked_pow(63), None);
+        assert_eq!(r(-2, 3).checked_pow(-3), Some(r(-27, 8)));
+        assert_eq!(r(0, 1).checked_pow(-1), None);
+        assert_eq!(r(0, 1).checked_pow(0), Some(r(1, 1)));
+    }
+    #[test]
+    fn rational_floor_ceil() {
+        assert_eq!((r(7, 2).floor(), r(7, 2).ceil()), (3, 4));
+        assert_eq!((r(-7, 2).floor(), r(-7, 2).ceil()), (-4, -3));
+        assert_eq!((r(-4, 2).floor(), r(-4, 2).ceil()), (-2, -2));
+        assert_eq!(r(i64::MIN, 1).ceil(), i64::MIN);
+        assert_eq!(r(i64::MAX, 2).ceil(), i64::MAX / 2 + 1);
+        assert_eq!(r(-5, 3).abs(), r(5, 3));
+    }
+    #[test]
+    fn rational_assign_ops() {
+        let mut x = r(1, 2);
+        x += r(1, 3);
+        assert_eq!(x, r(5, 6));
+        x -= r(1, 6);
+        assert_eq!(x, r(2, 3));
+        x *= r(9, 4);
+        assert_eq!(x, r(3, 2));
+        x /= r(3, 1);
+        assert_eq!(x, r(1, 2));
+        x %= r(1, 3);
+        assert_eq!(x, r(1, 6));
+        assert_eq!(-x, r(-1, 6));
+    }
+    #[test]
+    fn rational_display_from_str() {
+        assert_eq!(r(-6, 4).to_string(), "-3/2");
+        assert_eq!(r(10, 5).to_string(), "2");
+        for s in ["-3/2", "2", "0", "9223372036854775807/2"] {
+            assert_eq!(s.parse::<Rational>().unwrap().to_string(), s);
+        }
+        assert_eq!("6/-4".parse(), Ok(r(-3, 2)));
+        assert_eq!("1/0".parse::<Rational>(), Err(ParseRationalError::ZeroDenominator));
+        assert_eq!("-9223372036854775808/-1".parse::<Rational>(), Err(ParseRationalError::Overflow));
+        assert!(matches!("1/x".parse::<Rational>(), Err(ParseRationalError::Integer(_))));
+        assert!(matches!("".parse::<Rational>(), Err(ParseRationalError::Integer(_))));
+        assert!(matches!("1/2/3".parse::<Rational>(), Err(ParseRationalError::Integer(_))));
+    }
+}
@@ -0,0 +1,483 @@
+use crate::{discrete_log, factorize, gcd, gcd_extended, mod_mul, mod_pow, primitive_root};
+
+/// Computes the Jacobi symbol `(a/n)` for odd `n`.
+pub(crate) fn jacobi_u128(mut a: u128, mut n: u128) -> i8
+{
+    debug_assert!(n & 1 == 1);
+    a %= n;
+    let mut res = 1;
+    while a != 0 {
+        let zeros = a.trailing_zeros();
+        a >>= zeros;
+        if zeros & 1 == 1 && (n & 7 == 3 || n & 7 == 5) {
+            res = -res;
+        }
+        if a & 3 == 3 && n & 3 == 3 {
+            res = -res;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a %= n;
+    }
+    if n == 1 { res } else { 0 }
+}
+
+/// Computes the Jacobi symbol `(a/n)` for odd positive `n`.
+///
+/// The symbol is multiplicative in both arguments and for prime `n` equals the [legendre] symbol.
+/// It is computed without factorizing `n` by removing factors of two with the second supplementary law
+/// `(2/n) = (-1)^((n^2 - 1)/8)` and swapping the arguments by quadratic reciprocity, like in Euclid's algorithm.
+/// `(a/n) = -1` implies that `a` is not a square modulo `n`, but `(a/n) = 1` does not imply the opposite for composite `n`.
+///
+/// Panics if `n` is even.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(jacobi(2, 7), 1);
+/// assert_eq!(jacobi(-1, 7), -1);
+/// assert_eq!(jacobi(2, 15), 1);
+/// assert_eq!(jacobi(5, 15), 0);
+/// ```
+pub fn jacobi(a: i64, n: u64) -> i8
+{
+    assert!(n & 1 == 1, "Jacobi symbol is defined only for odd n, got {}", n);
+    jacobi_u128((a as i128).rem_euclid(n as i128) as u128, n as u128)
+}
+
+/// Computes the Legendre symbol `(a/p)` for an odd prime `p`, which is `0` if `p` divides `a`,
+/// `1` if `a` is a nonzero square modulo `p` and `-1` otherwise.
+///
+/// By Euler's criterion `(a/p) ≡ a^((p-1)/2) (mod p)`. Primality of `p` is not checked,
+/// for composite `p` the [jacobi] symbol is returned.
+///
+/// Panics if `p` is even.
+pub fn legendre(a: i64, p: u64) -> i8
+{
+    jacobi(a, p)
+}
+
+/// Computes a square root of `a` modulo a prime `p`.
+///
+/// Returns the smaller root `r <= p/2`, the other one is `p - r`. Returns `None` if `a` is not a quadratic residue.
+/// For `p ≡ 3 (mod 4)` the root is `a^((p+1)/4)`. Otherwise, writing `p - 1 = q * 2^s` with odd `q`,
+/// dispatches to [sqrt_mod_prime_tonelli_shanks], which takes `O(s^2)` multiplications, for small `s`
+/// and to [sqrt_mod_prime_cipolla], which takes `O(log p)` multiplications regardless of `s`, for the rest.
+/// Primality of `p` is not checked, for composite `p` the result is meaningless.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(sqrt_mod_prime(2, 7), Some(3));
+/// assert_eq!(sqrt_mod_prime(3, 7), None);
+/// assert_eq!(sqrt_mod_prime(0, 7), Some(0));
+/// ```
+pub fn sqrt_mod_prime(a: u64, p: u64) -> Option<u64>
+{
+    if (p - 1).trailing_zeros() < CIPOLLA_THRESHOLD {
+        sqrt_mod_prime_by(a, p, tonelli_shanks)
+    } else {
+        sqrt_mod_prime_by(a, p, cipolla)
+    }
+}
+
+/// Computes a square root of `a` modulo a prime `p` by the Tonelli–Shanks algorithm.
+/// Go to [sqrt_mod_prime] for further information.
+pub fn sqrt_mod_prime_tonelli_shanks(a: u64, p: u64) -> Option<u64>
+{
+    sqrt_mod_prime_by(a, p, tonelli_shanks)
+}
+
+/// Computes a square root of `a` modulo a prime `p` by Cipolla's algorithm.
+/// Go to [sqrt_mod_prime] for further information.
+pub fn sqrt_mod_prime_cipolla(a: u64, p: u64) -> Option<u64>
+{
+    sqrt_mod_prime_by(a, p, cipolla)
+}
+
+/// Smallest 2-adic valuation of `p - 1` for which [sqrt_mod_prime] prefers Cipolla's algorithm.
+const CIPOLLA_THRESHOLD: u32 = 16;
+
+/// Handles the trivial cases and nonresidues, then computes the root by `root` for odd `p ≡ 1 (mod 4)`.
+fn sqrt_mod_prime_by(a: u64, p: u64, root: fn(u64, u64) -> u64) -> Option<u64>
+{
+    let a = a % p;
+    if a == 0 || p == 2 {
+        return Some(a);
+    }
+    if jacobi_u128(a as u128, p as u128) != 1 {
+        return None;
+    }
+    let r = if p % 4 == 3 {
+        mod_pow(a, (p + 1) / 4, p)
+    } else {
+        root(a, p)
+    };
+    Some(r.min(p - r))
+}
+
+/// Computes a square root of a quadratic residue `a` modulo odd prime `p` by the Tonelli–Shanks algorithm.
+fn tonelli_shanks(a: u64, p: u64) -> u64
+{
+    let s = (p - 1).trailing_zeros();
+    let q = (p - 1) >> s;
+    let z = (2..p).find(|&z| jacobi_u128(z as u128, p as u128) == -1).unwrap();
+    let (mut m, mut c, mut t, mut r) = (s, mod_pow(z, q, p), mod_pow(a, q, p), mod_pow(a, q.div_ceil(2), p));
+    while t != 1 {
+        let mut i = 0;
+        let mut t2 = t;
+        while t2 != 1 {
+            t2 = mod_mul(t2, t2, p);
+            i += 1;
+        }
+        let b = mod_pow(c, 1 << (m - i - 1), p);
+        m = i;
+        c = mod_mul(b, b, p);
+        t = mod_mul(t, c, p);
+        r = mod_mul(r, b, p);
+    }
+    r
+}
+
+/// Computes a square root of a quadratic residue `a` modulo odd prime `p` by Cipolla's algorithm.
+///
+/// For `t` with nonresidue `w = t^2 - a` the root is `(t + ω)^((p+1)/2)` in `F_p[ω]/(ω^2 - w)`.
+fn cipolla(a: u64, p: u64) -> u64
+{
+    let add = |x: u64, y: u64| ((x as u128 + y as u128) % p as u128) as u64;
+    let (t, w) = (1..p).map(|t| (t, add(mod_mul(t, t, p), p - a)))
+        .find(|&(_, w)| jacobi_u128(w as u128, p as u128) == -1).unwrap();
+    let mul = |(x1, y1): (u64, u64), (x2, y2): (u64, u64)| {
+        (add(mod_mul(x1, x2, p), mod_mul(mod_mul(y1, y2, p), w, p)), add(mod_mul(x1, y2, p), mod_mul(x2, y1, p)))
+    };
+    let (mut base, mut res) = ((t, 1), (1, 0));
+    let mut exp = p / 2 + 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = mul(res, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    res.0
+}
+
+/// Computes all square roots of `a` modulo `n` in increasing order.
+///
+/// Factorizes `n`, finds the roots modulo every prime power `p^k` by [sqrt_mod_prime] and Hensel lifting
+/// and combines them by the Chinese remainder theorem. Modulo an odd prime power a unit has two roots or none,
+/// modulo `2^k` up to four, and numbers divisible by `p` can have many more, e.g. `x^2 ≡ 0 (mod p^k)`
+/// is solved by all multiples of `p^⌈k/2⌉`, so the result can be large.
+///
+/// Panics if `n` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(sqrt_mod(1, 8), vec![1, 3, 5, 7]);
+/// assert_eq!(sqrt_mod(4, 15), vec![2, 7, 8, 13]);
+/// assert_eq!(sqrt_mod(2, 15), vec![]);
+/// ```
+pub fn sqrt_mod(a: u64, n: u64) -> Vec<u64>
+{
+    let mut res = vec![0];
+    let mut modulus = 1;
+    for &(p, k) in factorize(n).factors() {
+        let pk = p.pow(k);
+        let roots = sqrt_mod_prime_power(a % pk, p, k);
+        let inv = inverse(modulus % pk, pk) as u128;
+        res = res.iter().flat_map(|&x| roots.iter().map(move |&r| {
+            // x + modulus * t ≡ r (mod p^k)
+            let t = (r as u128 + pk as u128 - (x % pk) as u128) * inv % pk as u128;
+            x + modulus * t as u64
+        })).collect();
+        modulus *= pk;
+    }
+    res.sort_unstable();
+    res
+}
+
+/// Computes all square roots of `a < p^k` modulo `p^k` in increasing order.
+fn sqrt_mod_prime_power(a: u64, p: u64, k: u32) -> Vec<u64>
+{
+    let pk = p.pow(k);
+    if a == 0 {
+        return (0..pk).step_by(p.pow(k.div_ceil(2)) as usize).collect();
+    }
+    // a = p^v * b with b coprime with p, roots are x = p^(v/2) * y with y^2 ≡ b (mod p^(k-v))
+    let mut v = 0;
+    let mut b = a;
+    while b.is_multiple_of(p) {
+        b /= p;
+        v += 1;
+    }
+    if v % 2 == 1 {
+        return Vec::new();
+    }
+    let m = k - v;
+    let pm = p.pow(m);
+    let unit_roots = if p == 2 { sqrt_mod_power_of_two(b, m) } else { sqrt_mod_odd_prime_power(b, p, pm) };
+    // y is determined modulo p^m, but x modulo p^k depends on y modulo p^(k - v/2)
+    let half = p.pow(v / 2);
+    let mut res: Vec<u64> = unit_roots.iter().flat_map(|&y| (0..half).map(move |t| half * (y + t * pm))).collect();
+    res.sort_unstable();
+    res
+}
+
+/// Computes the square roots of a unit `b` modulo `p^m` for odd prime `p`, lifting the root modulo `p` by Newton's iteration.
+fn sqrt_mod_odd_prime_power(b: u64, p: u64, pm: u64) -> Vec<u64>
+{
+    let mut r = match sqrt_mod_prime(b, p) {
+        Some(r) => r,
+        None => return Vec::new()
+    };
+    let b = b % pm;
+    loop {
+        let f = ((mod_mul(r, r, pm) as u128 + pm as u128 - b as u128) % pm as u128) as u64;
+        if f == 0 {
+            break;
+        }
+        // r - f / 2r
+        let step = mod_mul(f, inverse(mod_mul(2, r, pm), pm), pm);
+        r = ((r as u128 + pm as u128 - step as u128) % pm as u128) as u64;
+    }
+    vec![r.min(pm - r), r.max(pm - r)]
+}
+
+/// Computes the square roots of odd `b` modulo `2^m`.
+fn sqrt_mod_power_of_two(b: u64, m: u32) -> Vec<u64>
+{
+    let mask = if m == 64 { u64::MAX } else { (1 << m) - 1 };
+    match m {
+        0 => vec![0],
+        1 => vec![1],
+        2 if b % 4 == 1 => vec![1, 3],
+        _ if m >= 3 && b % 8 == 1 => {
+            // fix one bit at a time, if r^2 ≡ b (mod 2^j) then r or r + 2^(j-1) is a root modulo 2^(j+1)
+            let mut r: u64 = 1;
+            for j in 3..m {
+                let modulus_mask = (1u64 << (j + 1)).wrapping_sub(1);
+                if r.wrapping_mul(r).wrapping_sub(b) & modulus_mask != 0 {
+                    r += 1 << (j - 1);
+                }
+            }
+            let half = 1u64 << (m - 1);
+            let mut res: Vec<u64> = [r, r.wrapping_neg(), r.wrapping_add(half), r.wrapping_add(half).wrapping_neg()]
+                .iter().map(|&x| x & mask).collect();
+            res.sort_unstable();
+            res
+        }
+        _ => Vec::new()
+    }
+}
+
+/// Computes all solutions of `x^k ≡ a (mod p)` for a prime `p` in increasing order.
+/// Returns an empty vector if there is no solution.
+///
+/// With a primitive root `g` and `a ≡ g^e`, writing `x = g^y` turns the congruence into the linear congruence
+/// `k*y ≡ e (mod p - 1)`, which has `d = gcd(k, p - 1)` solutions if `d` divides `e` and none otherwise.
+/// The discrete logarithm `e` is found by [discrete_log], so the cost is dominated by the largest prime factor of `p - 1`.
+/// There are `gcd(k, p - 1)` solutions if any, so the result stays small unless `k` shares a large factor with `p - 1`.
+/// Primality of `p` is not checked, panics if `p` is not a prime with a primitive root.
+///
+/// Panics if `k` is zero, since then every residue would be a solution of `x^0 ≡ 1`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(kth_root_mod(1, 3, 7), vec![1, 2, 4]);
+/// assert_eq!(kth_root_mod(3, 3, 7), vec![]);
+/// assert_eq!(kth_root_mod(5, 3, 11), vec![3]);
+/// ```
+pub fn kth_root_mod(a: u64, k: u64, p: u64) -> Vec<u64>
+{
+    assert!(k != 0, "exponent must be positive");
+    let a = a % p;
+    if a == 0 || p == 2 {
+        return vec![a];
+    }
+    let order = p - 1;
+    let d = gcd(k, order);
+    if mod_pow(a, order / d, p) != 1 {
+        return Vec::new();
+    }
+    let g = primitive_root(p).expect("modulus must be a prime");
+    let e = discrete_log(g, a, p).unwrap();
+    // k*y ≡ e (mod p - 1) reduces to (k/d)*y ≡ e/d (mod (p - 1)/d)
+    let reduced = order / d;
+    let y = mod_mul((e / d) % reduced, inverse((k / d) % reduced, reduced), reduced);
+    // the solutions differ by the d-th roots of unity
+    let unity = mod_pow(g, reduced, p);
+    let mut x = mod_pow(g, y, p);
+    let mut res = Vec::with_capacity(d as usize);
+    for _ in 0..d {
+        res.push(x);
+        x = mod_mul(x, unity, p);
+    }
+    res.sort_unstable();
+    res
+}
+
+/// Computes the inverse of `a` modulo `m` for coprime `a` and `m`.
+pub(crate) fn inverse(a: u64, m: u64) -> u64
+{
+    gcd_extended(a as i128, m as i128).x0.rem_euclid(m as i128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn legendre_matches_euler_criterion() {
+        for &p in &[3u64, 5, 7, 11, 13, 101] {
+            for a in -3 * p as i64..3 * p as i64 {
+                let euler = crate::mod_pow(a.rem_euclid(p as i64) as u64, (p - 1) / 2, p);
+                let expected = if euler == p - 1 { -1 } else { euler as i8 };
+                assert_eq!(legendre(a, p), expected, "{} {}", a, p);
+            }
+        }
+    }
+    #[test]
+    fn jacobi_multiplicative() {
+        for n in (1..300u64).step_by(2) {
+            let f = crate::factorize(n);
+            for a in -50..50i64 {
+                let expected: i8 = f.factors().iter().map(|&(p, k)| legendre(a, p).pow(k)).product();
+                assert_eq!(jacobi(a, n), expected, "{} {}", a, n);
+            }
+        }
+    }
+    #[test]
+    fn jacobi_values() {
+        assert_eq!(jacobi(2, 15), 1);
+        assert_eq!(jacobi(7, 15), -1);
+        assert_eq!(jacobi(5, 15), 0);
+        assert_eq!(jacobi(0, 1), 1);
+        assert_eq!(jacobi(i64::MIN, u64::MAX), jacobi_u128((1u128 << 64) - 1 - (1 << 63), u64::MAX as u128));
+        assert_eq!(jacobi(-1, 18_446_744_073_709_551_557), 1);
+        assert_eq!(jacobi(i64::MAX, 18_446_744_073_709_551_557), jacobi_u128(i64::MAX as u128, 18_446_744_073_709_551_557));
+    }
+    #[test]
+    #[should_panic]
+    fn jacobi_even() {
+        jacobi(3, 10);
+    }
+
+    #[test]
+    fn sqrt_mod_prime_all_residues() {
+        for &p in &[2u64, 3, 5, 7, 13, 17, 41, 97, 257, 65537, 998_244_353] {
+            let mut squares = vec![false; p.min(500) as usize];
+            for x in 0..p.min(100_000) {
+                let a = mod_mul(x, x, p);
+                if a < squares.len() as u64 {
+                    squares[a as usize] = true;
+                }
+            }
+            for a in 0..p.min(500) {
+                assert_eq!(sqrt_mod_prime_tonelli_shanks(a, p), sqrt_mod_prime(a, p));
+                assert_eq!(sqrt_mod_prime_cipolla(a, p), sqrt_mod_prime(a, p));
+                match sqrt_mod_prime(a, p) {
+                    Some(r) => {
+                        assert!(r <= p / 2);
+                        assert_eq!(mod_mul(r, r, p), a, "{} {}", a, p);
+                    }
+                    None => assert_eq!(legendre(a as i64, p), -1, "{} {}", a, p)
+                }
+                if p < 100_000 {
+                    assert_eq!(sqrt_mod_prime(a, p).is_some(), squares[a as usize], "{} {}", a, p);
+                }
+            }
+        }
+    }
+    #[test]
+    fn sqrt_mod_prime_large() {
+        // 2^64 - 59 ≡ 5 (mod 8) and 2^64 - 2^32 + 1 has 2-adic valuation 32 of p - 1
+        for &p in &[18_446_744_073_709_551_557u64, 18_446_744_069_414_584_321] {
+            for x in 1..200u64 {
+                let x = mod_mul(x, 0x9e37_79b9_7f4a_7c15, p);
+                let r = sqrt_mod_prime(mod_mul(x, x, p), p).unwrap();
+                assert!(r == x || r == p - x);
+                assert_eq!(sqrt_mod_prime_tonelli_shanks(mod_mul(x, x, p), p), Some(r));
+                assert_eq!(sqrt_mod_prime_cipolla(mod_mul(x, x, p), p), Some(r));
+            }
+        }
+        assert_eq!(sqrt_mod_prime(1_000_000_008, 1_000_000_007), Some(1));
+    }
+
+    fn sqrt_mod_naive(a: u64, n: u64) -> Vec<u64> {
+        (0..n).filter(|&x| x * x % n == a % n).collect()
+    }
+
+    #[test]
+    fn sqrt_mod_brute_force() {
+        for n in 1..300u64 {
+            for a in 0..n + 3 {
+                assert_eq!(sqrt_mod(a, n), sqrt_mod_naive(a, n), "{} {}", a, n);
+            }
+        }
+        for &n in &[512u64, 729, 1024, 1372, 2048, 3125] {
+            for a in 0..n {
+                assert_eq!(sqrt_mod(a, n), sqrt_mod_naive(a, n), "{} {}", a, n);
+            }
+        }
+    }
+    #[test]
+    fn sqrt_mod_large() {
+        let n = 1_000_000_007 * 998_244_353;
+        let roots = sqrt_mod(mod_mul(123_456_789, 123_456_789, n), n);
+        assert_eq!(roots.len(), 4);
+        assert!(roots.contains(&123_456_789) && roots.contains(&(n - 123_456_789)));
+        assert!(roots.iter().all(|&r| mod_mul(r, r, n) == mod_mul(123_456_789, 123_456_789, n)));
+        let roots = sqrt_mod(17, 1 << 63);
+        assert_eq!(roots.len(), 4);
+        assert!(roots.iter().all(|&r| r.wrapping_mul(r) & ((1 << 63) - 1) == 17));
+        let p = 4_294_967_291u64;
+        let roots = sqrt_mod(mod_mul(5, 5, p * p), p * p);
+        assert_eq!(roots, vec![5, p * p - 5]);
+        assert_eq!(sqrt_mod(0, 1 << 40).len(), 1 << 20);
+        assert_eq!(sqrt_mod(3, u64::MAX).len(), 0);
+        assert_eq!(sqrt_mod(1, u64::MAX).len(), 128);
+        assert_eq!(sqrt_mod(5, 1), vec![0]);
+    }
+
+    #[test]
+    fn kth_root_mod_brute_force() {
+        for &p in &[2u64, 3, 5, 7, 11, 13, 17, 31, 37, 41, 97, 101] {
+            for k in 1..2 * p {
+                for a in 0..p {
+                    let expected: Vec<u64> = (0..p).filter(|&x| mod_pow(x, k, p) == a).collect();
+                    assert_eq!(kth_root_mod(a, k, p), expected, "{} {} {}", a, k, p);
+                }
+            }
+        }
+    }
+    #[test]
+    fn kth_root_mod_large() {
+        let p = 998_244_353;
+        let roots = kth_root_mod(1, 1 << 12, p);
+        assert_eq!(roots.len(), 1 << 12);
+        assert!(roots.iter().all(|&x| mod_pow(x, 1 << 12, p) == 1));
+        let roots = kth_root_mod(mod_pow(123_456, 7 * 17, p), 7 * 17, p);
+        assert_eq!(roots.len(), 7 * 17);
+        assert!(roots.contains(&123_456));
+        assert!(roots.iter().all(|&x| mod_pow(x, 7 * 17, p) == mod_pow(123_456, 7 * 17, p)));
+        let p = 1_000_000_007;
+        assert_eq!(kth_root_mod(mod_pow(987_654_321, 3, p), 3, p), vec![987_654_321]);
+        assert_eq!(kth_root_mod(5, 2, p).len(), sqrt_mod(5, p).len());
+        assert_eq!(kth_root_mod(0, 5, p), vec![0]);
+    }
+    #[test]
+    #[should_panic]
+    fn kth_root_mod_zero_exponent() {
+        kth_root_mod(1, 0, 1_000_000_007);
+    }
+}
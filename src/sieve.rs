@@ -0,0 +1,329 @@
+use crate::{Wheel, WHEEL_PRIMES};
+
+/// Sieve of Eratosthenes precomputing primality of all numbers up to a given limit.
+///
+/// Only odd numbers are stored, one bit each, so the sieve takes about `limit/16` bytes.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Sieve
+{
+    limit: u64,
+    // bit i is set iff 2*i + 1 is prime
+    bits: Vec<u64>
+}
+
+impl Sieve {
+    /// Sieves all numbers in `[0, limit]`.
+    pub fn new(limit: u64) -> Self {
+        let len = limit.div_ceil(2);
+        let mut bits = vec![u64::MAX; len.div_ceil(64) as usize];
+        if !len.is_multiple_of(64) {
+            *bits.last_mut().unwrap() = (1 << (len % 64)) - 1;
+        }
+        if let Some(first) = bits.first_mut() {
+            *first &= !1;
+        }
+        // Multiples of the odd wheel primes are crossed out directly, larger primes only cross out
+        // their multiples by wheel candidates, since the other ones are already crossed out.
+        for p in Wheel::starting_at(3).take_while(|&p| p * p <= limit) {
+            if bits[(p / 128) as usize] >> (p / 2 % 64) & 1 == 0 {
+                continue;
+            }
+            if WHEEL_PRIMES.contains(&p) {
+                for i in (p * p / 2..len).step_by(p as usize) {
+                    bits[(i / 64) as usize] &= !(1 << (i % 64));
+                }
+            } else {
+                for m in Wheel::starting_at(p).take_while(|&m| m <= limit / p) {
+                    let i = p * m / 2;
+                    bits[(i / 64) as usize] &= !(1 << (i % 64));
+                }
+            }
+        }
+        Sieve { limit, bits }
+    }
+
+    /// Largest number covered by the sieve.
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+
+    /// Returns true if `n` is a prime number. Otherwise returns false.
+    ///
+    /// Panics if `n` is greater than the limit of the sieve.
+    pub fn is_prime(&self, n: u64) -> bool {
+        assert!(n <= self.limit, "{} is out of the sieve range [0, {}]", n, self.limit);
+        if n.is_multiple_of(2) {
+            n == 2
+        } else {
+            self.bits[(n / 128) as usize] >> (n / 2 % 64) & 1 == 1
+        }
+    }
+
+    /// Iterates through all primes up to the limit in increasing order.
+    pub fn primes(&self) -> SievePrimes<'_> {
+        SievePrimes { sieve: self, two: self.limit >= 2, word_index: 0, word: self.bits.first().copied().unwrap_or(0) }
+    }
+
+    /// Number of primes up to the limit.
+    pub fn count(&self) -> usize {
+        let odd: usize = self.bits.iter().map(|w| w.count_ones() as usize).sum();
+        odd + (self.limit >= 2) as usize
+    }
+}
+
+/// Iterator through the primes of a [Sieve], returned by [Sieve::primes].
+#[derive(Debug, Clone)]
+pub struct SievePrimes<'a>
+{
+    sieve: &'a Sieve,
+    two: bool,
+    word_index: usize,
+    word: u64
+}
+
+impl<'a> Iterator for SievePrimes<'a> {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.two {
+            self.two = false;
+            return Some(2);
+        }
+        while self.word == 0 {
+            self.word_index += 1;
+            self.word = *self.sieve.bits.get(self.word_index)?;
+        }
+        let bit = self.word.trailing_zeros() as u64;
+        self.word &= self.word - 1;
+        Some(2 * (self.word_index as u64 * 64 + bit) + 1)
+    }
+}
+
+/// Lists all primes in the inclusive range `[lo, hi]` in increasing order.
+///
+/// Uses a segmented sieve, which needs the primes up to `sqrt(hi)` and memory proportional to
+/// a fixed segment size, so narrow windows of large numbers are cheap. Returns an empty vector if `lo > hi`.
+pub fn primes_in_range(lo: u64, hi: u64) -> Vec<u64>
+{
+    let mut res = Vec::new();
+    if lo > hi || hi < 2 {
+        return res;
+    }
+    let base_primes: Vec<u64> = Sieve::new(hi.isqrt()).primes().collect();
+    let mut start = lo.max(2);
+    loop {
+        let end = hi.min(start.saturating_add(SEGMENT - 1));
+        sieve_segment(start, end, &base_primes, &mut res);
+        if end == hi {
+            return res;
+        }
+        start = end + 1;
+    }
+}
+
+/// Size of a segment of the segmented sieve.
+const SEGMENT: u64 = 1 << 16;
+
+/// Appends primes in `[start, end]` to `out`, where `start >= 2` and `base_primes` contains
+/// at least all primes up to `sqrt(end)` in increasing order.
+fn sieve_segment(start: u64, end: u64, base_primes: &[u64], out: &mut Vec<u64>)
+{
+    let mut composite = vec![false; (end - start + 1) as usize];
+    for &p in base_primes.iter().take_while(|&&p| p * p <= end) {
+        let first = match start.checked_add((p - start % p) % p) {
+            Some(first) => first.max(p * p),
+            None => continue
+        };
+        for m in (first..=end).step_by(p as usize) {
+            composite[(m - start) as usize] = true;
+        }
+    }
+    out.extend((start..=end).filter(|&n| !composite[(n - start) as usize]));
+}
+
+/// Returns an unbounded iterator through all primes in increasing order.
+///
+/// Primes are produced by an incremental segmented sieve, so the iterator can be combined with
+/// adaptors like `take_while` when no bound is known up front.
+pub fn primes() -> Primes
+{
+    Primes { base_primes: Vec::new(), base_limit: 1, segment: Vec::new(), position: 0, next_start: Some(2) }
+}
+
+/// Unbounded iterator through all primes, returned by [primes].
+#[derive(Debug, Clone)]
+pub struct Primes
+{
+    base_primes: Vec<u64>,
+    base_limit: u64,
+    segment: Vec<u64>,
+    position: usize,
+    next_start: Option<u64>
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        while self.position == self.segment.len() {
+            let start = self.next_start?;
+            let end = start.saturating_add(SEGMENT - 1);
+            let root = end.isqrt();
+            if root > self.base_limit {
+                self.base_primes.extend(primes_in_range(self.base_limit + 1, root));
+                self.base_limit = root;
+            }
+            self.segment.clear();
+            self.position = 0;
+            sieve_segment(start, end, &self.base_primes, &mut self.segment);
+            self.next_start = end.checked_add(1);
+        }
+        self.position += 1;
+        Some(self.segment[self.position - 1])
+    }
+}
+
+/// Computes the prime counting function `π(x)`, the number of primes up to `x`.
+///
+/// Uses the Lucy_Hedgehog algorithm, which runs in `O(x^(3/4))` time and `O(sqrt(x))` memory,
+/// so `π(10^11)` takes a fraction of a second instead of sieving the whole range.
+pub fn prime_count(x: u64) -> u64
+{
+    if x < 2 {
+        return 0;
+    }
+    let root = x.isqrt() as usize;
+    // small[v] = S(v) for v <= root and large[i] = S(x/i) for 1 <= i <= root, where S(v) counts numbers
+    // in [2, v] not crossed out yet, so after processing all p <= sqrt(x) it is π(v).
+    let mut small: Vec<u64> = (0..=root as u64).map(|v| v.saturating_sub(1)).collect();
+    let mut large: Vec<u64> = (0..=root as u64).map(|i| x.checked_div(i).map_or(0, |v| v - 1)).collect();
+    for p in 2..=root {
+        if small[p] == small[p - 1] {
+            continue;
+        }
+        let below = small[p - 1];
+        let p2 = p * p;
+        let large_limit = root.min((x / p2 as u64) as usize);
+        for i in 1..=large_limit {
+            let d = i * p;
+            let s = if d <= root { large[d] } else { small[(x / d as u64) as usize] };
+            large[i] -= s - below;
+        }
+        for v in (p2..=root).rev() {
+            small[v] -= small[v / p] - below;
+        }
+    }
+    large[1]
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn sieve_matches_is_prime() {
+        let sieve = Sieve::new(10_000);
+        for n in 0..=10_000 {
+            assert_eq!(sieve.is_prime(n), crate::is_prime(n), "{}", n);
+        }
+    }
+    #[test]
+    fn sieve_primes() {
+        let sieve = Sieve::new(30);
+        assert_eq!(sieve.primes().collect::<Vec<_>>(), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(sieve.count(), 10);
+        assert_eq!(sieve.limit(), 30);
+    }
+    #[test]
+    fn sieve_small_limits() {
+        let expected = [0, 0, 1, 2, 2, 3, 3, 4, 4, 4, 4, 5];
+        for (limit, &count) in expected.iter().enumerate() {
+            let sieve = Sieve::new(limit as u64);
+            assert_eq!(sieve.count(), count, "{}", limit);
+            assert_eq!(sieve.primes().count(), count, "{}", limit);
+        }
+    }
+    #[test]
+    fn sieve_word_boundaries() {
+        for &limit in &[127u64, 128, 129, 131, 255, 256, 257] {
+            let sieve = Sieve::new(limit);
+            let expected: Vec<u64> = (0..=limit).filter(|&n| crate::is_prime(n)).collect();
+            assert_eq!(sieve.primes().collect::<Vec<_>>(), expected, "{}", limit);
+            assert_eq!(sieve.count(), expected.len());
+        }
+    }
+    #[test]
+    fn sieve_count_million() {
+        let sieve = Sieve::new(1_000_000);
+        assert_eq!(sieve.count(), 78_498);
+        assert_eq!(sieve.primes().last(), Some(999_983));
+    }
+    #[test]
+    #[should_panic]
+    fn sieve_out_of_range() {
+        Sieve::new(100).is_prime(101);
+    }
+
+    #[test]
+    fn primes_in_range_brute_force() {
+        for lo in 0..60 {
+            for hi in 0..60 {
+                let expected: Vec<u64> = (lo..=hi).filter(|&n| crate::is_prime(n)).collect();
+                assert_eq!(primes_in_range(lo, hi), expected, "{} {}", lo, hi);
+            }
+        }
+    }
+    #[test]
+    fn primes_in_range_matches_sieve() {
+        let sieve = Sieve::new(300_000);
+        let expected: Vec<u64> = sieve.primes().filter(|&p| p >= 1000).collect();
+        assert_eq!(primes_in_range(1000, 300_000), expected);
+    }
+    #[test]
+    fn primes_in_range_large_window() {
+        let lo = 1_000_000_000_000;
+        let primes = primes_in_range(lo, lo + 100_000);
+        let expected: Vec<u64> = (lo..=lo + 100_000).filter(|&n| crate::is_prime(n)).collect();
+        assert_eq!(primes, expected);
+        assert_eq!(primes.first(), Some(&1_000_000_000_039));
+    }
+    #[test]
+    fn primes_in_range_empty() {
+        assert!(primes_in_range(10, 5).is_empty());
+        assert!(primes_in_range(0, 1).is_empty());
+        assert!(primes_in_range(24, 28).is_empty());
+        assert_eq!(primes_in_range(2, 2), vec![2]);
+    }
+
+    #[test]
+    fn primes_iterator_matches_sieve() {
+        let sieve = Sieve::new(500_000);
+        assert!(primes().take_while(|&p| p <= 500_000).eq(sieve.primes()));
+    }
+    #[test]
+    fn primes_iterator_values() {
+        assert_eq!(primes().take(10).collect::<Vec<_>>(), vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(primes().nth(9_999), Some(104_729));
+        assert_eq!(primes().take_while(|&p| p < 1_000_000).count(), 78_498);
+    }
+
+    #[test]
+    fn prime_count_matches_sieve() {
+        let sieve = Sieve::new(3000);
+        let mut count = 0;
+        for x in 0..=3000 {
+            if sieve.is_prime(x) {
+                count += 1;
+            }
+            assert_eq!(prime_count(x), count, "{}", x);
+        }
+    }
+    #[test]
+    fn prime_count_powers_of_ten() {
+        let expected = [0, 4, 25, 168, 1229, 9592, 78_498, 664_579, 5_761_455, 50_847_534, 455_052_511];
+        for (k, &count) in expected.iter().enumerate() {
+            assert_eq!(prime_count(10u64.pow(k as u32)), count);
+        }
+    }
+}
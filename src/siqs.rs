@@ -0,0 +1,411 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::residues::jacobi_u128;
+use crate::wide::Montgomery;
+use crate::{factorize, gcd, is_probable_prime, mod_inverse, mod_mul, sqrt_mod_prime, Sieve};
+
+/// Factor base size and half length of the sieve interval by the number of bits of `kn`.
+const PARAMETERS: [(u32, usize, i64); 8] = [
+    (64, 80, 1 << 13),
+    (72, 120, 1 << 14),
+    (80, 200, 1 << 15),
+    (90, 330, 1 << 15),
+    (100, 550, 1 << 16),
+    (110, 900, 1 << 16),
+    (120, 1500, 1 << 16),
+    (126, 2200, 3 << 15)
+];
+
+/// Primes below this bound are not sieved, only trial divided.
+const SMALL_PRIME_BOUND: u64 = 30;
+
+/// Number of relations collected beyond the size of the factor base.
+const EXTRA_RELATIONS: usize = 32;
+
+/// Multipliers considered by the Knuth-Schroeppel function.
+const MULTIPLIERS: [u64; 20] = [1, 3, 5, 7, 11, 13, 15, 17, 19, 21, 23, 29, 31, 33, 35, 37, 39, 41, 43, 47];
+
+/// Tries to find a nontrivial factor of `n` by the self-initializing quadratic sieve.
+///
+/// Collects relations `(ax + b)^2 ≡ a*g(x) (mod kn)` with `g(x)` smooth over a factor base
+/// (allowing one large prime), finds dependencies by Gaussian elimination over GF(2)
+/// and computes the factor as `gcd(X - Y, n)` from the congruence of squares `X^2 ≡ Y^2 (mod n)`.
+/// Suitable for composites of 80 to 110 bits, whose factors are too large for [crate::ecm].
+/// Numbers fitting in `u64` are split by [factorize].
+///
+/// Returns `None` if `n` is prime, `n < 4` or `n >= 2^126`.
+pub fn siqs(n: u128) -> Option<u128>
+{
+    if !(4..1 << 126).contains(&n) || is_probable_prime(n) {
+        return None;
+    }
+    if n <= u64::MAX as u128 {
+        return factorize(n as u64).factors().first().map(|&(p, _)| p as u128);
+    }
+    if n.is_multiple_of(2) {
+        return Some(2);
+    }
+    let root = n.isqrt();
+    if root * root == n {
+        return Some(root);
+    }
+    let k = choose_multiplier(n);
+    let kn = k as u128 * n;
+    let bits = 128 - kn.leading_zeros();
+    let &(_, fb_size, half_len) = PARAMETERS.iter().find(|&&(b, _, _)| b >= bits).unwrap_or(&PARAMETERS[PARAMETERS.len() - 1]);
+    let fb = match FactorBase::new(n, k, fb_size) {
+        Ok(fb) => fb,
+        Err(p) => return Some(p as u128)
+    };
+    let mont = Montgomery::new(n);
+    let relations = collect_relations(&mont, kn, &fb, half_len);
+    find_factor(&mont, &fb, &relations)
+}
+
+/// Chooses the multiplier `k` maximizing the Knuth-Schroeppel function,
+/// which estimates how many small primes divide the values of the polynomials.
+fn choose_multiplier(n: u128) -> u64
+{
+    let small_primes: Vec<u64> = Sieve::new(1000).primes().collect();
+    let score = |k: u64| {
+        let kn = k as u128 * n;
+        let mut score = -0.5 * (k as f64).ln();
+        score += match kn % 8 {
+            1 => 2.0,
+            5 => 1.0,
+            _ => 0.5
+        } * 2f64.ln();
+        for &p in &small_primes[1..] {
+            let ln = (p as f64).ln();
+            if k.is_multiple_of(p) {
+                score += ln / p as f64;
+            } else if jacobi_u128(kn % p as u128, p as u128) == 1 {
+                score += 2.0 * ln / (p - 1) as f64;
+            }
+        }
+        score
+    };
+    let candidates = MULTIPLIERS.iter().copied().filter(|&k| n < (1 << 126) / k as u128);
+    candidates.map(|k| (k, score(k))).fold((1, f64::MIN), |best, c| if c.1 > best.1 { c } else { best }).0
+}
+
+/// Primes `p` such that `kn` is a quadratic residue modulo `p`, together with the square roots
+/// of `kn` modulo `p` and rounded binary logarithms.
+struct FactorBase
+{
+    primes: Vec<u64>,
+    sqrts: Vec<u64>,
+    logs: Vec<u8>
+}
+
+impl FactorBase {
+    /// Returns `Err(p)` if some prime `p` considered for the factor base divides `n`.
+    fn new(n: u128, k: u64, size: usize) -> Result<Self, u64> {
+        let kn = k as u128 * n;
+        let mut fb = FactorBase { primes: vec![2], sqrts: vec![1], logs: vec![1] };
+        for p in crate::primes().skip(1) {
+            if fb.primes.len() == size {
+                break;
+            }
+            let r = (kn % p as u128) as u64;
+            if n.is_multiple_of(p as u128) {
+                return Err(p);
+            }
+            if r != 0 && jacobi_u128(r as u128, p as u128) == 1 {
+                fb.primes.push(p);
+                fb.sqrts.push(sqrt_mod_prime(r, p).unwrap());
+                fb.logs.push((p as f64).log2().round() as u8);
+            }
+        }
+        Ok(fb)
+    }
+
+    fn len(&self) -> usize {
+        self.primes.len()
+    }
+}
+
+/// Relation `x^2 ≡ y^2 * Π factors (mod n)`, where `factors` are indices of the matrix columns,
+/// column 0 standing for -1 and column `i + 1` for the `i`-th prime of the factor base.
+/// Both `x` and `y` are in Montgomery form.
+struct Relation
+{
+    x: u128,
+    y: u128,
+    factors: Vec<usize>
+}
+
+/// Small deterministic generator used for choosing the primes of `a`.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next_below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+/// Chooses indices of distinct factor base primes whose product `a` is close to `2^target_log`.
+fn choose_a(fb: &FactorBase, target_log: f64, rng: &mut XorShift) -> (u128, Vec<usize>)
+{
+    let lo = fb.primes.iter().position(|&p| p > 2 * SMALL_PRIME_BOUND).unwrap_or(1).min(fb.len() / 2);
+    let hi = fb.len();
+    let log = |i: usize| (fb.primes[i] as f64).log2();
+    let average = (log(lo) + log(hi - 1)) / 2.0;
+    let s = ((target_log / average).round() as usize).clamp(1, (hi - lo) / 2);
+    let mut best: Option<(f64, Vec<usize>)> = None;
+    for _ in 0..30 {
+        let mut indices = Vec::with_capacity(s);
+        let mut sum = 0.0;
+        while indices.len() + 1 < s {
+            let i = lo + rng.next_below(hi - lo);
+            if !indices.contains(&i) {
+                indices.push(i);
+                sum += log(i);
+            }
+        }
+        let rest = target_log - sum;
+        let last = (lo..hi).filter(|i| !indices.contains(i))
+            .min_by(|&i, &j| (log(i) - rest).abs().partial_cmp(&(log(j) - rest).abs()).unwrap()).unwrap();
+        indices.push(last);
+        let error = (sum + log(last) - target_log).abs();
+        if best.as_ref().is_none_or(|(e, _)| error < *e) {
+            best = Some((error, indices));
+        }
+    }
+    let mut indices = best.unwrap().1;
+    indices.sort_unstable();
+    let a = indices.iter().map(|&i| fb.primes[i] as u128).product();
+    (a, indices)
+}
+
+/// Sieves polynomials `g(x) = ((ax + b)^2 - kn)/a` for `x` in `[-half_len, half_len)`
+/// until enough relations are found.
+fn collect_relations(mont: &Montgomery, kn: u128, fb: &FactorBase, half_len: i64) -> Vec<Relation>
+{
+    let n = mont.modulus();
+    let needed = fb.len() + 1 + EXTRA_RELATIONS;
+    let p_max = fb.primes[fb.len() - 1];
+    let large_bound = p_max * 64;
+    let kn_i = kn as i128;
+    let target_log = ((2.0 * kn as f64).sqrt() / half_len as f64).log2();
+    let g_max_log = (half_len as f64).log2() + ((kn / 2) as f64).log2() / 2.0;
+    let threshold = (g_max_log - (large_bound as f64).log2() - 2.0).max(0.0) as u8;
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    let mut used_a = HashSet::new();
+    let mut relations = Vec::new();
+    let mut partials: HashMap<u64, Relation> = HashMap::new();
+    let len = 2 * half_len as usize;
+    let mut sieve = vec![0u8; len];
+    while relations.len() < needed {
+        let (a, a_indices) = choose_a(fb, target_log, &mut rng);
+        if !used_a.insert(a) {
+            continue;
+        }
+        let s = a_indices.len();
+        // B_l ≡ sqrt(kn) (mod q_l) and B_l ≡ 0 modulo the other primes of a
+        let big_b: Vec<i128> = a_indices.iter().map(|&i| {
+            let q = fb.primes[i];
+            let a_q = a / q as u128;
+            let inv = mod_inverse((a_q % q as u128) as i64, q as i64).unwrap() as u64;
+            let mut gamma = mod_mul(fb.sqrts[i], inv, q);
+            if gamma > q / 2 {
+                gamma = q - gamma;
+            }
+            (a_q * gamma as u128) as i128
+        }).collect();
+        let mut b: i128 = big_b.iter().sum();
+        let mut signs = vec![1i128; s];
+        let in_a: Vec<bool> = (0..fb.len()).map(|i| a_indices.contains(&i)).collect();
+        let a_inv: Vec<u64> = (0..fb.len()).map(|i| {
+            if in_a[i] { 0 } else { mod_inverse((a % fb.primes[i] as u128) as i64, fb.primes[i] as i64).unwrap() as u64 }
+        }).collect();
+        let two_b_a_inv: Vec<Vec<u64>> = big_b.iter().map(|&bl| (0..fb.len()).map(|i| {
+            let p = fb.primes[i];
+            mod_mul((2 * bl).rem_euclid(p as i128) as u64, a_inv[i], p)
+        }).collect()).collect();
+        let mut roots: Vec<(u64, u64)> = (0..fb.len()).map(|i| {
+            let p = fb.primes[i];
+            let b_p = b.rem_euclid(p as i128) as u64;
+            let t = fb.sqrts[i];
+            (mod_mul((t + p - b_p) % p, a_inv[i], p), mod_mul((2 * p - t - b_p) % p, a_inv[i], p))
+        }).collect();
+        for poly in 0..1usize << (s - 1) {
+            if poly > 0 {
+                let l = poly.trailing_zeros() as usize + 1;
+                let old = signs[l];
+                b -= 2 * old * big_b[l];
+                signs[l] = -old;
+                for i in 0..fb.len() {
+                    let p = fb.primes[i];
+                    let delta = if old == 1 { two_b_a_inv[l][i] } else { (p - two_b_a_inv[l][i]) % p };
+                    roots[i] = ((roots[i].0 + delta) % p, (roots[i].1 + delta) % p);
+                }
+            }
+            let c = (b * b - kn_i) / a as i128;
+            sieve.iter_mut().for_each(|v| *v = 0);
+            let offsets: Vec<(usize, usize)> = (0..fb.len()).map(|i| {
+                let p = fb.primes[i];
+                let shift = half_len as u64 % p;
+                (((roots[i].0 + shift) % p) as usize, ((roots[i].1 + shift) % p) as usize)
+            }).collect();
+            for i in 0..fb.len() {
+                let p = fb.primes[i];
+                if p < SMALL_PRIME_BOUND || in_a[i] {
+                    continue;
+                }
+                let (o1, o2) = offsets[i];
+                for j in (o1..len).step_by(p as usize) {
+                    sieve[j] = sieve[j].wrapping_add(fb.logs[i]);
+                }
+                if o2 != o1 {
+                    for j in (o2..len).step_by(p as usize) {
+                        sieve[j] = sieve[j].wrapping_add(fb.logs[i]);
+                    }
+                }
+            }
+            for (pos, &log) in sieve.iter().enumerate() {
+                if log < threshold {
+                    continue;
+                }
+                let x = pos as i128 - half_len as i128;
+                let g = (a as i128 * x + 2 * b) * x + c;
+                if g == 0 {
+                    continue;
+                }
+                let mut factors: Vec<usize> = a_indices.iter().map(|&i| i + 1).collect();
+                if g < 0 {
+                    factors.push(0);
+                }
+                let mut v = g.unsigned_abs();
+                for i in 0..fb.len() {
+                    let p = fb.primes[i];
+                    let candidate = p < SMALL_PRIME_BOUND || in_a[i] || pos % p as usize == offsets[i].0 || pos % p as usize == offsets[i].1;
+                    if candidate {
+                        while v.is_multiple_of(p as u128) {
+                            v /= p as u128;
+                            factors.push(i + 1);
+                        }
+                    }
+                }
+                let x_side = (a as i128 * x + b).rem_euclid(n as i128) as u128;
+                let relation = Relation { x: mont.encode(x_side), y: mont.one(), factors };
+                if v == 1 {
+                    relations.push(relation);
+                } else if v < large_bound as u128 {
+                    let large = v as u64;
+                    match partials.remove(&large) {
+                        Some(other) => {
+                            let mut factors = relation.factors;
+                            factors.extend(other.factors);
+                            let (x, y) = (mont.mul(relation.x, other.x), mont.encode(large as u128));
+                            relations.push(Relation { x, y, factors });
+                        },
+                        None => {
+                            partials.insert(large, relation);
+                        }
+                    }
+                }
+            }
+            if relations.len() >= needed {
+                break;
+            }
+        }
+    }
+    relations
+}
+
+/// Finds dependencies among the relations by Gaussian elimination over GF(2)
+/// and tries to split `n` by each of them.
+fn find_factor(mont: &Montgomery, fb: &FactorBase, relations: &[Relation]) -> Option<u128>
+{
+    let n = mont.modulus();
+    let cols = fb.len() + 1;
+    let rows = relations.len();
+    let (col_words, row_words) = (cols.div_ceil(64), rows.div_ceil(64));
+    let mut matrix: Vec<Vec<u64>> = relations.iter().enumerate().map(|(r, relation)| {
+        let mut row = vec![0u64; col_words + row_words];
+        for &f in &relation.factors {
+            row[f / 64] ^= 1 << (f % 64);
+        }
+        row[col_words + r / 64] |= 1 << (r % 64);
+        row
+    }).collect();
+    let mut is_pivot = vec![false; rows];
+    for col in 0..cols {
+        let pivot = match (0..rows).find(|&r| !is_pivot[r] && (matrix[r][col / 64] >> (col % 64)) & 1 == 1) {
+            Some(pivot) => pivot,
+            None => continue
+        };
+        is_pivot[pivot] = true;
+        let pivot_row = matrix[pivot].clone();
+        for (r, row) in matrix.iter_mut().enumerate() {
+            if r != pivot && (row[col / 64] >> (col % 64)) & 1 == 1 {
+                row.iter_mut().zip(&pivot_row).for_each(|(w, &p)| *w ^= p);
+            }
+        }
+    }
+    for (r, row) in matrix.iter().enumerate() {
+        if is_pivot[r] {
+            continue;
+        }
+        let mut exponents = vec![0u32; cols];
+        let (mut x, mut y) = (mont.one(), mont.one());
+        for (i, relation) in relations.iter().enumerate() {
+            if (row[col_words + i / 64] >> (i % 64)) & 1 == 1 {
+                x = mont.mul(x, relation.x);
+                y = mont.mul(y, relation.y);
+                for &f in &relation.factors {
+                    exponents[f] += 1;
+                }
+            }
+        }
+        for (i, &e) in exponents.iter().enumerate().skip(1) {
+            debug_assert!(e % 2 == 0);
+            y = mont.mul(y, mont.pow(mont.encode(fb.primes[i - 1] as u128), (e / 2) as u128));
+        }
+        let g = gcd(mont.sub(x, y), n);
+        if g > 1 && g < n {
+            return Some(g);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn siqs_semiprimes() {
+        // 35-bit times 36-bit and 40-bit times 42-bit primes
+        for &(p, q) in &[(34_359_738_337u128, 68_719_476_731u128), (1_099_511_627_689, 4_398_046_511_093)] {
+            let d = siqs(p * q).unwrap();
+            assert!(d == p || d == q, "{} {}", p * q, d);
+        }
+    }
+    #[test]
+    fn siqs_three_factors() {
+        let n = 1_000_003u128 * 1_000_033 * 1_000_037 * 1_000_039;
+        let d = siqs(n).unwrap();
+        assert!(d > 1 && d < n && n.is_multiple_of(d));
+    }
+    #[test]
+    fn siqs_special_cases() {
+        assert_eq!(siqs((1 << 127) - 1), None);
+        assert_eq!(siqs(3), None);
+        assert_eq!(siqs(1_000_000_007 * 998_244_353), Some(998_244_353));
+        assert_eq!(siqs(((1 << 61) - 1) * ((1 << 61) - 1)), Some((1 << 61) - 1));
+        assert_eq!(siqs(1 << 126), None);
+        assert_eq!(siqs((1 << 80) * 3), Some(2));
+    }
+    #[test]
+    fn choose_multiplier_squarefree() {
+        let n = 34_359_738_337u128 * 68_719_476_731;
+        let k = choose_multiplier(n);
+        assert!(MULTIPLIERS.contains(&k));
+    }
+}
@@ -0,0 +1,241 @@
+use crate::{factorize, is_prime, sqrt_mod_prime};
+
+/// Below this bound [sum_of_four_squares] searches exhaustively instead of sampling.
+const FOUR_SQUARES_EXHAUSTIVE_BOUND: u64 = 1 << 16;
+
+/// Finds `x, y` with `x^2 + y^2 = p` for a prime `p = 2` or `p ≡ 1 (mod 4)` by Cornacchia's algorithm.
+pub(crate) fn prime_as_two_squares(p: u64) -> (u64, u64)
+{
+    if p == 2 {
+        return (1, 1);
+    }
+    let (mut a, mut b) = (p, sqrt_mod_prime(p - 1, p).unwrap());
+    while b as u128 * b as u128 > p as u128 {
+        let r = a % b;
+        a = b;
+        b = r;
+    }
+    (b, (p - b * b).isqrt())
+}
+
+/// Finds a representation of `n` as a sum of two squares `x^2 + y^2` with `x <= y`.
+///
+/// By Fermat's theorem such a representation exists if and only if every prime `p ≡ 3 (mod 4)`
+/// occurs in the factorization of `n` with an even exponent. Each remaining prime is split by Cornacchia's algorithm
+/// and the parts are multiplied as Gaussian integers.
+/// Returns `None` if `n` is not a sum of two squares.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// assert_eq!(sum_of_two_squares(25), Some((3, 4)));
+/// assert_eq!(sum_of_two_squares(65), Some((4, 7)));
+/// assert_eq!(sum_of_two_squares(21), None);
+/// ```
+pub fn sum_of_two_squares(n: u64) -> Option<(u64, u64)>
+{
+    if n == 0 {
+        return Some((0, 0));
+    }
+    let (mut x, mut y, mut scale) = (1i128, 0i128, 1u64);
+    for &(p, e) in factorize(n).factors() {
+        if p % 4 == 3 {
+            if e % 2 == 1 {
+                return None;
+            }
+            scale *= p.pow(e / 2);
+            continue;
+        }
+        let (a, b) = prime_as_two_squares(p);
+        let (a, b) = (a as i128, b as i128);
+        for _ in 0..e {
+            let next_x = x * a - y * b;
+            y = x * b + y * a;
+            x = next_x;
+        }
+    }
+    let (x, y) = (x.unsigned_abs() as u64 * scale, y.unsigned_abs() as u64 * scale);
+    Some((x.min(y), x.max(y)))
+}
+
+/// Computes `r2(n)`, the number of ordered pairs of integers `(x, y)` with `x^2 + y^2 = n`, signs included.
+///
+/// Equals `4 (d1(n) - d3(n))`, where `d1` and `d3` count the divisors congruent to 1 and 3 modulo 4.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// // (±1, ±2) and (±2, ±1)
+/// assert_eq!(count_representations_r2(5), 8);
+/// assert_eq!(count_representations_r2(3), 0);
+/// assert_eq!(count_representations_r2(25), 12);
+/// ```
+pub fn count_representations_r2(n: u64) -> u64
+{
+    if n == 0 {
+        return 1;
+    }
+    let mut count = 4;
+    for &(p, e) in factorize(n).factors() {
+        match p % 4 {
+            1 => count *= e as u64 + 1,
+            3 if e % 2 == 1 => return 0,
+            _ => ()
+        }
+    }
+    count
+}
+
+/// Finds a representation of `n` as a sum of four squares `a^2 + b^2 + c^2 + d^2` with `a <= b <= c <= d`.
+///
+/// Such a representation exists for every `n` by Lagrange's theorem. After removing factors of 4 the function follows
+/// Rabin and Shallit: it samples `x, y` until `n - x^2 - y^2` is a prime `p ≡ 1 (mod 4)`, which is then split
+/// into two squares by Cornacchia's algorithm. The samples come from a generator seeded by `n`,
+/// so the result is deterministic. Small inputs are searched exhaustively.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let (a, b, c, d) = sum_of_four_squares(1_000_000_007);
+/// assert_eq!(a * a + b * b + c * c + d * d, 1_000_000_007);
+/// assert_eq!(sum_of_four_squares(7), (1, 1, 1, 2));
+/// ```
+pub fn sum_of_four_squares(n: u64) -> (u64, u64, u64, u64)
+{
+    if n == 0 {
+        return (0, 0, 0, 0);
+    }
+    let shift = n.trailing_zeros() / 2;
+    let m = n >> (2 * shift);
+    let mut res = if m < FOUR_SQUARES_EXHAUSTIVE_BOUND { four_squares_exhaustive(m) } else { four_squares_sampled(m) };
+    res.sort_unstable();
+    let [a, b, c, d] = res;
+    (a << shift, b << shift, c << shift, d << shift)
+}
+
+/// Finds four squares summing to `m` by trying every `x <= y` and splitting the rest into two squares.
+fn four_squares_exhaustive(m: u64) -> [u64; 4]
+{
+    for x in 0..=m.isqrt() {
+        for y in x..=(m - x * x).isqrt() {
+            if let Some((a, b)) = sum_of_two_squares(m - x * x - y * y) {
+                return [x, y, a, b];
+            }
+        }
+    }
+    unreachable!()
+}
+
+/// Finds four squares summing to `m ≢ 0 (mod 4)` by sampling `x, y` until `m - x^2 - y^2` is a prime `p ≡ 1 (mod 4)`.
+fn four_squares_sampled(m: u64) -> [u64; 4]
+{
+    // parities of x, y making x^2 + y^2 ≡ m - 1 (mod 4)
+    let (x_odd, y_odd) = match m % 4 {
+        1 => (0, 0),
+        2 => (1, 0),
+        _ => (1, 1)
+    };
+    let bound = (m / 2).isqrt() + 1;
+    let mut state = m ^ 0x9e37_79b9_7f4a_7c15;
+    let mut sample = |parity: u64| {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound) & !1 | parity
+    };
+    loop {
+        let (x, y) = (sample(x_odd), sample(y_odd));
+        let rest = match (x * x).checked_add(y * y).and_then(|s| m.checked_sub(s)) {
+            Some(rest) => rest,
+            None => continue
+        };
+        if rest == 1 {
+            return [x, y, 0, 1];
+        }
+        if is_prime(rest) {
+            let (a, b) = prime_as_two_squares(rest);
+            return [x, y, a, b];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn r2_naive(n: u64) -> u64 {
+        let r = n.isqrt() as i64;
+        (-r..=r).map(|x| (-r..=r).filter(|&y| (x * x + y * y) as u64 == n).count() as u64).sum()
+    }
+
+    #[test]
+    fn prime_as_two_squares_primes() {
+        for p in crate::primes().take_while(|&p| p < 100_000).filter(|&p| p % 4 != 3) {
+            let (a, b) = prime_as_two_squares(p);
+            assert_eq!(a * a + b * b, p, "{}", p);
+        }
+        let p = 18_446_744_073_709_551_557; // largest prime below 2^64, ≡ 1 mod 4
+        let (a, b) = prime_as_two_squares(p);
+        assert_eq!(a as u128 * a as u128 + b as u128 * b as u128, p as u128);
+    }
+    #[test]
+    fn sum_of_two_squares_brute_force() {
+        for n in 0..3000u64 {
+            let expected = (0..=n.isqrt()).any(|x| (n - x * x).isqrt().pow(2) == n - x * x);
+            match sum_of_two_squares(n) {
+                Some((x, y)) => assert!(x <= y && x * x + y * y == n, "{}", n),
+                None => assert!(!expected, "{}", n)
+            }
+            assert_eq!(sum_of_two_squares(n).is_some(), expected, "{}", n);
+        }
+    }
+    #[test]
+    fn sum_of_two_squares_large() {
+        for n in [1 << 63, (1 << 62) + 1, 9 * 1_000_000_009 * 998_244_353, 4_000_000_000 * 4_000_000_000, 3_999_999_999u64.pow(2)] {
+            let (x, y) = sum_of_two_squares(n).unwrap();
+            assert_eq!(x as u128 * x as u128 + y as u128 * y as u128, n as u128, "{}", n);
+        }
+        assert_eq!(sum_of_two_squares(u64::MAX), None);
+        assert_eq!(sum_of_two_squares(3 * 5 * 13), None);
+    }
+    #[test]
+    fn count_representations_r2_brute_force() {
+        for n in 0..1000 {
+            assert_eq!(count_representations_r2(n), r2_naive(n), "{}", n);
+        }
+        assert_eq!(count_representations_r2(5 * 5 * 13 * 9), 24);
+        assert_eq!(count_representations_r2(u64::MAX), 0);
+    }
+
+    fn test_four_squares(n: u64) {
+        let (a, b, c, d) = sum_of_four_squares(n);
+        assert!(a <= b && b <= c && c <= d, "{}", n);
+        assert_eq!([a, b, c, d].iter().map(|&x| x as u128 * x as u128).sum::<u128>(), n as u128, "{}", n);
+    }
+
+    #[test]
+    fn sum_of_four_squares_small() {
+        for n in 0..3000 {
+            test_four_squares(n);
+        }
+        assert_eq!(sum_of_four_squares(0), (0, 0, 0, 0));
+        assert_eq!(sum_of_four_squares(7 << 40), (1 << 20, 1 << 20, 1 << 20, 2 << 20));
+    }
+    #[test]
+    fn sum_of_four_squares_sampled() {
+        for n in (FOUR_SQUARES_EXHAUSTIVE_BOUND..FOUR_SQUARES_EXHAUSTIVE_BOUND + 2000).chain(u64::MAX - 2000..=u64::MAX) {
+            test_four_squares(n);
+        }
+        for n in [1_000_000_007 * 998_244_353, 7 << 60, 15 << 58, (1 << 62) - 1, 3 << 62] {
+            test_four_squares(n);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use std::convert::TryFrom;
+
+use crate::Rational;
+
+/// Node of the Stern-Brocot tree, which contains every positive rational exactly once.
+///
+/// Each node is the mediant `(a + c)/(b + d)` of its bounds `a/b` and `c/d`, the nearest ancestors to its left and right.
+/// The root is `1/1` with bounds `0/1` and `1/0`, going left replaces the right bound by the node
+/// and going right replaces the left bound. The bounds of every node satisfy `bc - ad = 1`.
+///
+/// # Examples
+///
+/// ```
+/// use dma::*;
+///
+/// let mut node = SternBrocot::new();
+/// node.go_left();
+/// node.go_right();
+/// assert_eq!(node.value(), Rational::new(2, 3));
+/// assert_eq!(node.bounds(), ((1, 2), (1, 1)));
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct SternBrocot
+{
+    left: (i64, i64),
+    right: (i64, i64)
+}
+
+impl SternBrocot {
+    /// Creates the root `1/1`.
+    pub fn new() -> Self {
+        SternBrocot { left: (0, 1), right: (1, 0) }
+    }
+
+    /// Returns the value of the node.
+    pub fn value(&self) -> Rational {
+        let (p, q) = self.mediant();
+        Rational::new(p, q)
+    }
+
+    /// Returns the bounds `(a, b)` and `(c, d)` of the node as numerator and denominator pairs,
+    /// the right bound of the rightmost nodes is `(1, 0)`.
+    pub fn bounds(&self) -> ((i64, i64), (i64, i64)) {
+        (self.left, self.right)
+    }
+
+    /// Moves to the left child. Panics if its value does not fit, leaving the node unchanged.
+    pub fn go_left(&mut self) {
+        let right = self.mediant();
+        // make sure the value of the new node fits before moving
+        mediant(self.left, right);
+        self.right = right;
+    }
+
+    /// Moves to the right child. Panics if its value does not fit, leaving the node unchanged.
+    pub fn go_right(&mut self) {
+        let left = self.mediant();
+        // make sure the value of the new node fits before moving
+        mediant(left, self.right);
+        self.left = left;
+    }
+
+    fn mediant(&self) -> (i64, i64) {
+        mediant(self.left, self.right)
+    }
+
+    /// Finds the rational with the smallest denominator in the open interval `(lo, hi)`, and the smallest absolute value
+    /// among those. Returns `None` if the interval is empty.
+    ///
+    /// If the interval contains no integer, it is shifted into `(0, 1)` and the tree is descended from the root
+    /// until a node falls inside. The first such node is the only one with the smallest denominator,
+    /// since every other rational in the interval is its descendant. Runs of steps in the same direction
+    /// are taken at once, so the descent follows the continued fractions of the bounds and takes `O(log)` time.
+    /// Useful for recovering an exact rational from an approximation with a known error.
+    ///
+    /// Panics if the result does not fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use dma::*;
+    ///
+    /// // 0.333 and 0.334 are rounded values of 1/3
+    /// assert_eq!(SternBrocot::simplest_between(Rational::new(333, 1000), Rational::new(334, 1000)), Some(Rational::new(1, 3)));
+    /// assert_eq!(SternBrocot::simplest_between(Rational::new(-5, 2), Rational::new(3, 1)), Some(Rational::from(0)));
+    /// ```
+    pub fn simplest_between(lo: Rational, hi: Rational) -> Option<Rational> {
+        if lo >= hi {
+            return None;
+        }
+        let n = lo.floor() as i128;
+        let (lo_p, lo_q, hi_p, hi_q) = (lo.numer() as i128, lo.denom() as i128, hi.numer() as i128, hi.denom() as i128);
+        if (n + 1) * hi_q < hi_p {
+            // some integer lies strictly inside, take the one closest to zero
+            let res = if lo_p < 0 && hi_p > 0 { 0 } else if lo_p >= 0 { n + 1 } else { -(-hi_p).div_euclid(hi_q) - 1 };
+            return Some(Rational::from(res as i64));
+        }
+        // shift the interval by -n into (0, 1]
+        let (lo_p, hi_p) = (lo_p - n * lo_q, hi_p - n * hi_q);
+        let (mut left, mut right) = ((0i128, 1i128), (1i128, 0i128));
+        loop {
+            let (p, q) = (left.0 + right.0, left.1 + right.1);
+            if p * lo_q <= lo_p * q {
+                // take the largest k with (left + k*right) <= lo
+                let k = (lo_p * left.1 - lo_q * left.0) / (lo_q * right.0 - lo_p * right.1);
+                left = (left.0 + k * right.0, left.1 + k * right.1);
+            } else if p * hi_q >= hi_p * q {
+                // take the largest k with (right + k*left) >= hi
+                let k = (hi_q * right.0 - hi_p * right.1) / (hi_p * left.1 - hi_q * left.0);
+                right = (right.0 + k * left.0, right.1 + k * left.1);
+            } else {
+                let to_i64 = |x: i128| i64::try_from(x).expect("rational overflow");
+                return Some(Rational::new(to_i64(p + n * q), to_i64(q)));
+            }
+        }
+    }
+}
+
+impl Default for SternBrocot {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn mediant(left: (i64, i64), right: (i64, i64)) -> (i64, i64)
+{
+    let add = |x: i64, y: i64| x.checked_add(y).expect("rational overflow");
+    (add(left.0, right.0), add(left.1, right.1))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn r(p: i64, q: i64) -> Rational {
+        Rational::new(p, q)
+    }
+
+    #[test]
+    fn stern_brocot_navigation() {
+        let mut node = SternBrocot::new();
+        assert_eq!(node.value(), r(1, 1));
+        // 3/7 = [0; 2, 3] is reached by L, L, R, R
+        node.go_left();
+        node.go_left();
+        node.go_right();
+        node.go_right();
+        assert_eq!(node.value(), r(3, 7));
+        let ((a, b), (c, d)) = node.bounds();
+        assert_eq!(b * c - a * d, 1);
+        assert_eq!(((a, b), (c, d)), ((2, 5), (1, 2)));
+        let mut node = SternBrocot::default();
+        for k in 2..100 {
+            node.go_right();
+            assert_eq!(node.value(), Rational::from(k));
+        }
+    }
+    #[test]
+    fn stern_brocot_levels() {
+        // level k of the tree consists of the rationals with continued fraction term sum k + 1
+        let mut level = vec![SternBrocot::new()];
+        for depth in 0..12 {
+            let mut values: Vec<Rational> = level.iter().map(|node| node.value()).collect();
+            assert!(values.windows(2).all(|w| w[0] < w[1]));
+            values.dedup();
+            assert_eq!(values.len(), 1 << depth);
+            for node in &level {
+                let terms = crate::continued_fraction(node.value().numer(), node.value().denom());
+                assert_eq!(terms.iter().sum::<i64>(), depth + 1);
+            }
+            level = level.iter().flat_map(|&node| {
+                let (mut left, mut right) = (node, node);
+                left.go_left();
+                right.go_right();
+                vec![left, right]
+            }).collect();
+        }
+    }
+
+    fn simplest_between_naive(lo: Rational, hi: Rational) -> Option<Rational> {
+        if lo >= hi {
+            return None;
+        }
+        // the numerators strictly between lo*q and hi*q, the first denominator with one is already reduced
+        (1..).find_map(|q| {
+            let p_min = (lo.numer() * q).div_euclid(lo.denom()) + 1;
+            let p_max = -(-hi.numer() * q).div_euclid(hi.denom()) - 1;
+            if p_min > p_max {
+                return None;
+            }
+            Some(r(if p_min > 0 { p_min } else if p_max < 0 { p_max } else { 0 }, q))
+        })
+    }
+
+    #[test]
+    fn simplest_between_brute_force() {
+        let values: Vec<Rational> = (1..9).flat_map(|q| (-20..=20).map(move |p| r(p, q))).collect();
+        for &lo in &values {
+            for &hi in &values {
+                assert_eq!(SternBrocot::simplest_between(lo, hi), simplest_between_naive(lo, hi), "{} {}", lo, hi);
+            }
+        }
+    }
+    #[test]
+    fn simplest_between_large() {
+        let x = r(123_457, 67_891);
+        let eps = r(1, 1 << 40);
+        assert_eq!(SternBrocot::simplest_between(x - eps, x + eps), Some(x));
+        let (a, b) = (r(i64::MAX - 1, i64::MAX), r(i64::MAX - 3, i64::MAX - 1));
+        let res = SternBrocot::simplest_between(b, a).unwrap();
+        assert!(b < res && res < a);
+        assert_eq!(SternBrocot::simplest_between(r(i64::MIN, 1), r(i64::MAX, 1)), Some(r(0, 1)));
+        assert_eq!(SternBrocot::simplest_between(r(i64::MAX - 2, 1), r(i64::MAX, 1)), Some(r(i64::MAX - 1, 1)));
+        assert_eq!(SternBrocot::simplest_between(r(1, 3), r(1, 3)), None);
+    }
+    #[test]
+    fn stern_brocot_overflow_keeps_node() {
+        let mut node = SternBrocot { left: (i64::MAX - 1, 1), right: (1, 0) };
+        assert_eq!(node.value(), r(i64::MAX, 1));
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| node.go_right()));
+        assert!(res.is_err());
+        assert_eq!(node.bounds(), ((i64::MAX - 1, 1), (1, 0)));
+        assert_eq!(node.value(), r(i64::MAX, 1));
+    }
+    #[test]
+    #[should_panic]
+    fn simplest_between_overflow() {
+        // (2^63 - 3/2) does not fit
+        SternBrocot::simplest_between(r(i64::MAX - 1, 1), r(i64::MAX, 1));
+    }
+}
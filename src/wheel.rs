@@ -0,0 +1,138 @@
+/// Primes whose multiples are skipped by the [Wheel].
+pub const WHEEL_PRIMES: [u64; 4] = [2, 3, 5, 7];
+
+/// Product of [WHEEL_PRIMES].
+pub const WHEEL_MODULUS: u64 = 210;
+
+/// Residues modulo 210 coprime with 210.
+const RESIDUES: [u64; 48] = [1, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103,
+    107, 109, 113, 121, 127, 131, 137, 139, 143, 149, 151, 157, 163, 167, 169, 173, 179, 181, 187, 191, 193, 197, 199, 209];
+
+/// Iterator through prime candidates produced by the 2·3·5·7 wheel.
+///
+/// Yields the wheel primes 2, 3, 5, 7 followed by all numbers greater than 7 coprime with 210 in increasing order.
+/// Every prime is yielded, while only 48 of every 210 numbers are candidates.
+/// The iterator ends when the next candidate would not fit in `u64`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Wheel
+{
+    small: usize,
+    base: u64,
+    index: usize,
+    done: bool
+}
+
+impl Wheel {
+    /// Creates a wheel yielding all candidates.
+    pub fn new() -> Self {
+        Self::starting_at(0)
+    }
+
+    /// Creates a wheel yielding all candidates greater or equal to `n`.
+    pub fn starting_at(n: u64) -> Self {
+        let small = WHEEL_PRIMES.iter().take_while(|&&p| p < n).count();
+        let n = n.max(8);
+        let (base, r) = (n - n % WHEEL_MODULUS, n % WHEEL_MODULUS);
+        match RESIDUES.iter().position(|&x| x >= r) {
+            Some(index) => Wheel { small, base, index, done: false },
+            None => match base.checked_add(WHEEL_MODULUS) {
+                Some(base) => Wheel { small, base, index: 0, done: false },
+                None => Wheel { small, base, index: 0, done: true }
+            }
+        }
+    }
+}
+
+impl Default for Wheel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for Wheel {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.small < WHEEL_PRIMES.len() {
+            self.small += 1;
+            return Some(WHEEL_PRIMES[self.small - 1]);
+        }
+        if self.done {
+            return None;
+        }
+        let res = match self.base.checked_add(RESIDUES[self.index]) {
+            Some(res) => res,
+            None => {
+                self.done = true;
+                return None;
+            }
+        };
+        self.index += 1;
+        if self.index == RESIDUES.len() {
+            self.index = 0;
+            match self.base.checked_add(WHEEL_MODULUS) {
+                Some(base) => self.base = base,
+                None => self.done = true
+            }
+        }
+        Some(res)
+    }
+}
+
+/// Computes the largest number less than `n` coprime with 210. Expects `n > 11`.
+pub(crate) fn prev_wheel_candidate(n: u64) -> u64
+{
+    let (base, r) = (n - n % WHEEL_MODULUS, n % WHEEL_MODULUS);
+    match RESIDUES.iter().rposition(|&x| x < r) {
+        Some(index) => base + RESIDUES[index],
+        None => base - WHEEL_MODULUS + RESIDUES[RESIDUES.len() - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn is_candidate(n: u64) -> bool {
+        WHEEL_PRIMES.contains(&n) || (n > 7 && WHEEL_PRIMES.iter().all(|&p| !n.is_multiple_of(p)))
+    }
+
+    #[test]
+    fn wheel_first_values() {
+        let expected = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+            101, 103, 107, 109, 113, 121, 127];
+        assert_eq!(Wheel::new().take(expected.len()).collect::<Vec<_>>(), expected);
+    }
+    #[test]
+    fn wheel_brute_force() {
+        let expected: Vec<u64> = (0..5000).filter(|&n| is_candidate(n)).collect();
+        assert_eq!(Wheel::new().take_while(|&n| n < 5000).collect::<Vec<_>>(), expected);
+    }
+    #[test]
+    fn wheel_starting_at() {
+        for n in 0..1000 {
+            let expected: Vec<u64> = (n..n + 500).filter(|&m| is_candidate(m)).collect();
+            let wheel: Vec<u64> = Wheel::starting_at(n).take_while(|&m| m < n + 500).collect();
+            assert_eq!(wheel, expected, "{}", n);
+        }
+    }
+    #[test]
+    fn wheel_contains_primes() {
+        assert!(crate::primes().take_while(|&p| p < 100_000).eq(Wheel::new().filter(|&n| crate::is_prime(n)).take_while(|&p| p < 100_000)));
+    }
+    #[test]
+    fn wheel_u64_max() {
+        let last: Vec<u64> = Wheel::starting_at(u64::MAX - 300).collect();
+        let expected: Vec<u64> = (u64::MAX - 300..=u64::MAX).filter(|&m| is_candidate(m)).collect();
+        assert_eq!(last, expected);
+        assert_eq!(Wheel::starting_at(u64::MAX).count(), 0);
+    }
+    #[test]
+    fn prev_wheel_candidate_brute_force() {
+        for n in 12..3000 {
+            assert_eq!(prev_wheel_candidate(n), (0..n).rev().find(|&m| is_candidate(m)).unwrap(), "{}", n);
+        }
+        assert_eq!(prev_wheel_candidate(u64::MAX), (u64::MAX - 300..u64::MAX).rev().find(|&m| is_candidate(m)).unwrap());
+    }
+}
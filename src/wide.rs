@@ -0,0 +1,155 @@
+//! Double-width arithmetic on `u128` used by the 128-bit algorithms of this crate.
+
+/// Computes the 256-bit product `a * b` as `(low, high)` halves.
+pub(crate) fn mul_wide(a: u128, b: u128) -> (u128, u128)
+{
+    let (a0, a1) = (a as u64 as u128, a >> 64);
+    let (b0, b1) = (b as u64 as u128, b >> 64);
+    let (p00, p01, p10, p11) = (a0 * b0, a0 * b1, a1 * b0, a1 * b1);
+    let mid = (p00 >> 64) + (p01 as u64 as u128) + (p10 as u64 as u128);
+    let low = (p00 as u64 as u128) | (mid << 64);
+    let high = p11 + (p01 >> 64) + (p10 >> 64) + (mid >> 64);
+    (low, high)
+}
+
+/// Computes the upper 128 bits of the 256-bit product `a * b`.
+pub(crate) fn mul_high(a: u128, b: u128) -> u128
+{
+    mul_wide(a, b).1
+}
+
+/// Montgomery multiplication modulo an odd `u128` modulus.
+///
+/// Values in Montgomery form are `x * 2^128 mod n`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) struct Montgomery
+{
+    n: u128,
+    n_neg_inv: u128,
+    r2: u128,
+    one: u128
+}
+
+impl Montgomery {
+    /// Panics if `n` is even.
+    pub(crate) fn new(n: u128) -> Self {
+        assert!(n & 1 == 1, "Montgomery modulus must be odd");
+        // Newton iteration doubles the number of correct low bits, starting from 3 bits.
+        let mut inv = n;
+        for _ in 0..7 {
+            inv = inv.wrapping_mul(2u128.wrapping_sub(n.wrapping_mul(inv)));
+        }
+        let one = (u128::MAX % n + 1) % n;
+        let mut r2 = one;
+        for _ in 0..128 {
+            r2 = add_mod(r2, r2, n);
+        }
+        Montgomery { n, n_neg_inv: inv.wrapping_neg(), r2, one }
+    }
+
+    pub(crate) fn modulus(&self) -> u128 {
+        self.n
+    }
+
+    /// Montgomery form of 1.
+    pub(crate) fn one(&self) -> u128 {
+        self.one
+    }
+
+    pub(crate) fn encode(&self, x: u128) -> u128 {
+        self.mul(x % self.n, self.r2)
+    }
+
+    #[cfg(test)]
+    pub(crate) fn decode(&self, x: u128) -> u128 {
+        self.reduce(x, 0)
+    }
+
+    fn reduce(&self, low: u128, high: u128) -> u128 {
+        let m = low.wrapping_mul(self.n_neg_inv);
+        let (mn_low, mn_high) = mul_wide(m, self.n);
+        let carry = low.overflowing_add(mn_low).1 as u128;
+        let (t, overflow1) = high.overflowing_add(mn_high);
+        let (t, overflow2) = t.overflowing_add(carry);
+        if overflow1 || overflow2 || t >= self.n { t.wrapping_sub(self.n) } else { t }
+    }
+
+    pub(crate) fn mul(&self, a: u128, b: u128) -> u128 {
+        let (low, high) = mul_wide(a, b);
+        self.reduce(low, high)
+    }
+
+    pub(crate) fn add(&self, a: u128, b: u128) -> u128 {
+        add_mod(a, b, self.n)
+    }
+
+    pub(crate) fn sub(&self, a: u128, b: u128) -> u128 {
+        if a >= b { a - b } else { a.wrapping_sub(b).wrapping_add(self.n) }
+    }
+
+    /// Computes `a / 2`, works both for plain values and values in Montgomery form.
+    pub(crate) fn half(&self, a: u128) -> u128 {
+        if a & 1 == 0 { a >> 1 } else { (a >> 1) + (self.n >> 1) + 1 }
+    }
+
+    /// Computes `base^exp`, where `base` and the result are in Montgomery form.
+    pub(crate) fn pow(&self, mut base: u128, mut exp: u128) -> u128 {
+        let mut res = self.one;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = self.mul(res, base);
+            }
+            base = self.mul(base, base);
+            exp >>= 1;
+        }
+        res
+    }
+}
+
+/// Computes `a + b mod n` for `a, b < n`.
+fn add_mod(a: u128, b: u128, n: u128) -> u128
+{
+    let (sum, overflow) = a.overflowing_add(b);
+    if overflow || sum >= n { sum.wrapping_sub(n) } else { sum }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn mul_wide_values() {
+        assert_eq!(mul_wide(u128::MAX, u128::MAX), (1, u128::MAX - 1));
+        assert_eq!(mul_wide(1 << 64, 1 << 64), (0, 1));
+        assert_eq!(mul_wide(1 << 127, 2), (0, 1));
+        assert_eq!(mul_wide(u64::MAX as u128, u64::MAX as u128), ((u64::MAX as u128) * (u64::MAX as u128), 0));
+        assert_eq!(mul_high(u128::MAX, 1 << 100), (1 << 100) - 1);
+    }
+
+    #[test]
+    fn montgomery_mul_matches_u64() {
+        for &n in &[1u128, 3, 5, 1_000_000_007, 18_446_744_073_709_551_557, u64::MAX as u128] {
+            let mont = Montgomery::new(n);
+            for &a in &[0u128, 1, 2, n - 1, n / 2, n / 3] {
+                for &b in &[0u128, 1, 7, n - 1, n / 5] {
+                    let expected = a % n * (b % n) % n;
+                    assert_eq!(mont.decode(mont.mul(mont.encode(a), mont.encode(b))), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn montgomery_large_modulus() {
+        let n = u128::MAX - 158; // largest prime below 2^128
+        let mont = Montgomery::new(n);
+        let a = mont.encode(u128::MAX);
+        assert_eq!(mont.decode(a), 158);
+        assert_eq!(mont.decode(mont.mul(a, a)), 158 * 158);
+        assert_eq!(mont.decode(mont.pow(mont.encode(3), n - 1)), 1);
+        assert_eq!(mont.decode(mont.half(mont.encode(1))), n / 2 + 1);
+        assert_eq!(mont.decode(mont.sub(mont.one(), a)), n - 157);
+        assert_eq!(mont.decode(mont.add(a, mont.encode(n - 158))), 0);
+    }
+}